@@ -0,0 +1,233 @@
+//! Rewrites calls of functions annotated with `#[defamed::defamed]` into calls of their macros,
+//! such as `area(2, 3)` into `area!(2, 3)`, to adopt the macros in existing code.
+//!
+//! ```sh
+//! cargo defamed-migrate src          # lists the calls that would be rewritten
+//! cargo defamed-migrate src --write  # rewrites them in place
+//! ```
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use syn::visit::Visit;
+
+const USAGE: &str = "\
+usage: cargo defamed-migrate <DIR> [--write]
+
+Find the functions annotated with `#[defamed::defamed]` in the Rust files of DIR,
+and list every call of them that can be rewritten into a call of its macro.
+
+options:
+  --write  rewrite the calls in place";
+
+fn main() -> ExitCode {
+    // `cargo defamed-migrate` passes the name of the subcommand on
+    let args = std::env::args()
+        .skip(1)
+        .skip_while(|arg| arg == "defamed-migrate")
+        .collect::<Vec<_>>();
+    let write = args.iter().any(|arg| arg == "--write");
+    let dir = match args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [dir] => PathBuf::from(dir),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match migrate(&dir, write) {
+        Ok(calls) => {
+            let verb = match write {
+                true => "rewrote",
+                false => "found",
+            };
+            println!("{} {} calls", verb, calls);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// List or rewrite the calls of annotated functions in every Rust file of `dir`,
+/// returning the number of calls.
+fn migrate(dir: &Path, write: bool) -> Result<usize, String> {
+    let mut files = vec![];
+    rust_files(dir, &mut files).map_err(|e| format!("cannot read {}: {}", dir.display(), e))?;
+    files.sort();
+
+    let sources = files
+        .into_iter()
+        .map(|path| {
+            let source = std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+            let file = syn::parse_file(&source)
+                .map_err(|e| format!("cannot parse {}: {}", path.display(), e))?;
+
+            Ok((path, source, file))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    // functions are called across files, so all of them are collected first
+    let mut annotated = HashSet::new();
+    for (_, _, file) in &sources {
+        annotated_fns(&file.items, false, &mut annotated);
+    }
+
+    let mut total = 0;
+    for (path, source, file) in sources {
+        let mut calls = Calls {
+            annotated: &annotated,
+            found: vec![],
+        };
+        calls.visit_file(&file);
+
+        for call in &calls.found {
+            println!(
+                "{}:{}:{}: {}",
+                path.display(),
+                call.line,
+                call.column + 1 - call.name.chars().count(),
+                call.name
+            );
+        }
+        total += calls.found.len();
+
+        if write && !calls.found.is_empty() {
+            std::fs::write(&path, rewrite(&source, &calls.found))
+                .map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+        }
+    }
+
+    Ok(total)
+}
+
+/// Collect the Rust files in `dir` and its subdirectories, skipping build output.
+fn rust_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        match path.is_dir() {
+            true if path.file_name().is_some_and(|name| name == "target") => (),
+            true => rust_files(&path, files)?,
+            false if path.extension().is_some_and(|ext| ext == "rs") => files.push(path),
+            false => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Collect the names of the functions that generate a macro among `items`:
+/// annotated functions, and public functions of annotated modules (`in_module`) that are not skipped.
+fn annotated_fns(items: &[syn::Item], in_module: bool, names: &mut HashSet<String>) {
+    for item in items {
+        match item {
+            syn::Item::Fn(f) => {
+                let generates = match f.attrs.iter().find(|a| is_defamed_attr(a)) {
+                    Some(attr) => !is_skip(attr),
+                    None => in_module && matches!(f.vis, syn::Visibility::Public(_)),
+                };
+
+                if generates {
+                    names.insert(f.sig.ident.to_string());
+                }
+            }
+            syn::Item::Mod(syn::ItemMod {
+                attrs,
+                content: Some((_, items)),
+                ..
+            }) => {
+                let annotated = attrs.iter().any(|a| is_defamed_attr(a) && !is_skip(a));
+                annotated_fns(items, annotated, names);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Checks if an attribute invokes the attribute macro.
+fn is_defamed_attr(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|seg| seg.ident == "defamed")
+}
+
+/// Checks if an attribute opts an item out with `#[defamed(skip)]`.
+fn is_skip(attr: &syn::Attribute) -> bool {
+    attr.parse_args_with(syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)
+        .is_ok_and(|metas| metas.iter().any(|meta| meta.path().is_ident("skip")))
+}
+
+/// A call of an annotated function.
+struct Call {
+    name: String,
+    /// Line of the end of the function name, starting at 1
+    line: usize,
+    /// Column of the end of the function name, in characters
+    column: usize,
+}
+
+/// Visitor collecting the calls of annotated functions.
+///
+/// Calls inside macro invocations are left alone, as their arguments are not parsed.
+struct Calls<'a> {
+    annotated: &'a HashSet<String>,
+    found: Vec<Call>,
+}
+
+impl<'ast> Visit<'ast> for Calls<'_> {
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(syn::ExprPath {
+            qself: None, path, ..
+        }) = &*call.func
+        {
+            // macros cannot take generic arguments
+            let segment = path.segments.last().filter(|seg| seg.arguments.is_none());
+
+            if let Some(segment) =
+                segment.filter(|seg| self.annotated.contains(&seg.ident.to_string()))
+            {
+                let end = segment.ident.span().end();
+                self.found.push(Call {
+                    name: segment.ident.to_string(),
+                    line: end.line,
+                    column: end.column,
+                });
+            }
+        }
+
+        syn::visit::visit_expr_call(self, call);
+    }
+}
+
+/// Insert a `!` after the name of every call, keeping the rest of the source as it is.
+fn rewrite(source: &str, calls: &[Call]) -> String {
+    let mut lines = source
+        .split_inclusive('\n')
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    for call in calls {
+        let line = &mut lines[call.line - 1];
+        let offset = line
+            .char_indices()
+            .nth(call.column)
+            .map(|(idx, _)| idx)
+            .unwrap_or(line.len());
+        line.insert(offset, '!');
+    }
+
+    lines.concat()
+}
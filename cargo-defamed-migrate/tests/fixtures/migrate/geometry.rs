@@ -0,0 +1,11 @@
+#[defamed::defamed(crate::geometry)]
+pub mod shapes {
+    pub fn area(width: i32, #[def(1)] height: i32) -> i32 {
+        width * height
+    }
+
+    #[defamed(skip)]
+    pub fn volume(width: i32, height: i32, depth: i32) -> i32 {
+        area(width, height) * depth
+    }
+}
@@ -0,0 +1,22 @@
+//! A crate migrated by the tests, never compiled.
+
+mod geometry;
+
+#[defamed::defamed]
+pub fn scale(value: i32, #[def(2)] factor: i32) -> i32 {
+    value * factor
+}
+
+pub fn plain(value: i32) -> i32 {
+    value
+}
+
+pub fn total() -> i32 {
+    let doubled = scale(plain(1));
+    let tripled = crate::scale(2, 3);
+
+    // calls inside macros are left alone
+    assert_eq!(scale(1, 1), 1);
+
+    doubled + tripled + geometry::shapes::area(2, 3) + geometry::shapes::volume(1, 2, 3)
+}
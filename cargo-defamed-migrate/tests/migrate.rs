@@ -0,0 +1,82 @@
+use std::{path::Path, process::Command};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/migrate");
+
+/// Run the binary as `cargo defamed-migrate` would, returning its success, stdout and stderr.
+fn run(args: &[&str]) -> (bool, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-defamed-migrate"))
+        .arg("defamed-migrate")
+        .args(args)
+        .output()
+        .expect("binary runs");
+
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// Copy the fixture crate to a directory of its own, so it can be rewritten.
+fn copy_fixture(name: &str) -> String {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for file in ["lib.rs", "geometry.rs"] {
+        std::fs::copy(Path::new(FIXTURE).join(file), dir.join(file)).unwrap();
+    }
+
+    dir.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_dry_run() {
+    let (success, stdout, _) = run(&[FIXTURE]);
+
+    assert!(success);
+    assert!(stdout.contains("lib.rs:15:19: scale\n"));
+    assert!(stdout.contains("lib.rs:16:26: scale\n"));
+    assert!(stdout.contains("lib.rs:21:43: area\n"));
+    assert!(stdout.contains("geometry.rs:9:9: area\n"));
+    assert!(stdout.ends_with("found 4 calls\n"));
+
+    // nothing is written without `--write`
+    let source = std::fs::read_to_string(Path::new(FIXTURE).join("lib.rs")).unwrap();
+    assert!(source.contains("scale(plain(1))"));
+}
+
+#[test]
+fn test_write() {
+    let dir = copy_fixture("migrate_write");
+    let (success, stdout, _) = run(&[&dir, "--write"]);
+
+    assert!(success);
+    assert!(stdout.ends_with("rewrote 4 calls\n"));
+
+    let lib = std::fs::read_to_string(Path::new(&dir).join("lib.rs")).unwrap();
+    assert!(lib.contains("let doubled = scale!(plain(1));"));
+    assert!(lib.contains("let tripled = crate::scale!(2, 3);"));
+    assert!(lib.contains("assert_eq!(scale(1, 1), 1);"));
+    assert!(lib.contains(
+        "doubled + tripled + geometry::shapes::area!(2, 3) + geometry::shapes::volume(1, 2, 3)"
+    ));
+
+    let geometry = std::fs::read_to_string(Path::new(&dir).join("geometry.rs")).unwrap();
+    assert!(geometry.contains("area!(width, height) * depth"));
+
+    // migrated calls are not found again
+    let (success, stdout, _) = run(&[&dir]);
+    assert!(success);
+    assert_eq!(stdout, "found 0 calls\n");
+}
+
+#[test]
+fn test_errors() {
+    let (success, _, stderr) = run(&[]);
+    assert!(!success);
+    assert!(stderr.starts_with("usage: cargo defamed-migrate"));
+
+    let (success, _, stderr) = run(&["missing-dir"]);
+    assert!(!success);
+    assert!(stderr.contains("cannot read missing-dir"));
+}
@@ -0,0 +1,343 @@
+//! Prints the macro arms that `#[defamed::defamed]` generates for an item,
+//! to debug invocations that no arm accepts.
+//!
+//! Items are expanded by the same code as the attribute, from `defamed-core`.
+//!
+//! ```sh
+//! cargo defamed src/lib.rs complex_function
+//! cargo defamed src/lib.rs Counter::increment --expand
+//! ```
+
+use std::process::ExitCode;
+
+use defamed_core::{
+    expand::{
+        block_logic,
+        config::{CrateOptions, MacroConfig},
+        debug_dir, ROOT_VISIBILITY_IDENT,
+    },
+    CRATE_IDENT,
+};
+use proc_macro2 as pm2;
+
+const USAGE: &str = "\
+usage: cargo defamed <FILE> <ITEM> [--expand]
+
+Print the macro arms generated for ITEM in FILE, which is the name of a function or struct,
+or `Type::method` for methods of annotated `impl` blocks and traits.
+
+options:
+  --expand  print all of the generated code instead of the patterns of the arms";
+
+fn main() -> ExitCode {
+    // `cargo defamed` passes the name of the subcommand on
+    let args = std::env::args()
+        .skip(1)
+        .skip_while(|arg| arg == "defamed")
+        .collect::<Vec<_>>();
+    let expand = args.iter().any(|arg| arg == "--expand");
+    let positional = args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .collect::<Vec<_>>();
+
+    let (file, item) = match positional.as_slice() {
+        [file, item] => (file, item),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match inspect(file, item, expand) {
+        Ok(report) => {
+            print!("{}", report);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Report on the macro generated for `item` in the source file at `path`.
+fn inspect(path: &str, item: &str, expand: bool) -> Result<String, String> {
+    let source =
+        std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    let file = syn::parse_file(&source).map_err(|e| format!("cannot parse {}: {}", path, e))?;
+    let options = crate_options(std::path::Path::new(path))?;
+
+    let generated = generate(&file.items, item, None, &options)?
+        .ok_or_else(|| format!("no item `{}` annotated with `#[defamed]` in {}", item, path))?;
+
+    match expand {
+        true => Ok(debug_dir::render(&generated)),
+        false => report_arms(&generated),
+    }
+}
+
+/// Options of the crate a source file belongs to, from the nearest manifest above it.
+fn crate_options(path: &std::path::Path) -> Result<CrateOptions, String> {
+    let manifest = path
+        .canonicalize()
+        .map_err(|e| format!("cannot find {}: {}", path.display(), e))?
+        .ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|manifest| manifest.is_file());
+
+    match manifest {
+        Some(manifest) => {
+            let contents = std::fs::read_to_string(&manifest)
+                .map_err(|e| format!("cannot read {}: {}", manifest.display(), e))?;
+
+            CrateOptions::from_manifest(&contents)
+                .map_err(|e| format!("{}: {}", manifest.display(), e))
+        }
+        None => Ok(CrateOptions::default()),
+    }
+}
+
+/// Generate the macro of the item named `target` among `items`, searching inline modules as well.
+///
+/// Functions of annotated modules are expanded with the configuration of their module.
+fn generate(
+    items: &[syn::Item],
+    target: &str,
+    module_config: Option<&MacroConfig>,
+    options: &CrateOptions,
+) -> Result<Option<pm2::TokenStream>, String> {
+    let (self_ty, method) = match target.rsplit_once("::") {
+        Some((self_ty, method)) => (Some(self_ty), method),
+        None => (None, target),
+    };
+
+    for item in items {
+        match item {
+            syn::Item::Fn(f) if self_ty.is_none() && f.sig.ident == method => {
+                let config = match (item_config(&f.attrs, false, options)?, module_config) {
+                    (Some(config), _) if !config.skip => config,
+                    (Some(_), _) => continue,
+                    (None, Some(config)) if matches!(f.vis, syn::Visibility::Public(_)) => {
+                        config.clone()
+                    }
+                    _ => continue,
+                };
+                let mut f = f.clone();
+                f.attrs.retain(|a| !is_defamed_attr(a));
+
+                return Ok(Some(block_logic::item_fn(f, config).generated));
+            }
+            syn::Item::Struct(s) if self_ty.is_none() && s.ident == method => {
+                let config = match item_config(&s.attrs, false, options)? {
+                    Some(config) if !config.skip => config,
+                    _ => continue,
+                };
+                let mut s = s.clone();
+                s.attrs.retain(|a| !is_defamed_attr(a));
+
+                return Ok(Some(block_logic::item_struct(s, config).generated));
+            }
+            syn::Item::Impl(i) if self_ty.is_some_and(|ty| type_name(&i.self_ty) == ty) => {
+                let config = match item_config(&i.attrs, true, options)? {
+                    Some(config) => config,
+                    None => continue,
+                };
+                let has_method = i
+                    .items
+                    .iter()
+                    .any(|item| matches!(item, syn::ImplItem::Fn(f) if f.sig.ident == method));
+                if !has_method {
+                    continue;
+                }
+
+                // the other methods generate macros of their own
+                let mut i = i.clone();
+                i.attrs.retain(|a| !is_defamed_attr(a));
+                i.items
+                    .retain(|item| matches!(item, syn::ImplItem::Fn(f) if f.sig.ident == method));
+
+                return Ok(Some(block_logic::item_impl(i, config).generated));
+            }
+            syn::Item::Trait(t) if self_ty.is_some_and(|ty| t.ident == ty) => {
+                let config = match item_config(&t.attrs, true, options)? {
+                    Some(config) => config,
+                    None => continue,
+                };
+                let has_method = t
+                    .items
+                    .iter()
+                    .any(|item| matches!(item, syn::TraitItem::Fn(f) if f.sig.ident == method));
+                if !has_method {
+                    continue;
+                }
+
+                let mut t = t.clone();
+                t.attrs.retain(|a| !is_defamed_attr(a));
+                t.items
+                    .retain(|item| matches!(item, syn::TraitItem::Fn(f) if f.sig.ident == method));
+
+                return Ok(Some(block_logic::item_trait(t, config).generated));
+            }
+            syn::Item::Mod(syn::ItemMod {
+                attrs,
+                ident,
+                content: Some((_, items)),
+                ..
+            }) => {
+                // functions of annotated modules are found by the path to the module
+                let config = item_config(attrs, false, options)?.map(|config| {
+                    let path: syn::Path = match &config.path {
+                        Some(p) if p.is_ident(ROOT_VISIBILITY_IDENT) => syn::parse_quote! {#ident},
+                        Some(p) => syn::parse_quote! {#p::#ident},
+                        None => syn::parse_quote! {#ident},
+                    };
+
+                    MacroConfig {
+                        path: Some(path),
+                        ..config
+                    }
+                });
+
+                if let Some(generated) = generate(items, target, config.as_ref(), options)? {
+                    return Ok(Some(generated));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Configuration of the `#[defamed]` attribute among `attrs`, if any.
+fn item_config(
+    attrs: &[syn::Attribute],
+    methods: bool,
+    options: &CrateOptions,
+) -> Result<Option<MacroConfig>, String> {
+    let attr = match attrs.iter().find(|a| is_defamed_attr(a)) {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+
+    let config = match &attr.meta {
+        syn::Meta::List(list) => syn::parse2::<MacroConfig>(list.tokens.clone())
+            .map_err(|e| format!("invalid `#[defamed]` options: {}", e))?,
+        _ => MacroConfig::default(),
+    };
+
+    config
+        .with_options(options, methods)
+        .map(Some)
+        .map_err(|e| format!("invalid `#[defamed]` options: {}", e))
+}
+
+/// Checks if an attribute invokes the attribute macro.
+fn is_defamed_attr(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|seg| seg.ident == CRATE_IDENT)
+}
+
+/// Name of the type an `impl` block is for, without generics.
+fn type_name(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .map(|seg| seg.ident.to_string())
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// List the patterns of the arms of the generated macro, along with their kind.
+fn report_arms(generated: &pm2::TokenStream) -> Result<String, String> {
+    let (ident, body) = macro_body(generated.clone()).ok_or_else(|| {
+        format!(
+            "no macro was generated:\n{}",
+            debug_dir::render(generated).trim_end()
+        )
+    })?;
+
+    let arms = body
+        .into_iter()
+        .collect::<Vec<_>>()
+        .split(|token| matches!(token, pm2::TokenTree::Punct(p) if p.as_char() == ';'))
+        .filter_map(|arm| match (arm.first(), arm.last()) {
+            (Some(pm2::TokenTree::Group(pattern)), Some(pm2::TokenTree::Group(body))) => Some((
+                pattern.stream(),
+                arm_kind(&pattern.stream(), &body.stream()),
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let count = |kind: &str| arms.iter().filter(|(_, k)| *k == kind).count();
+    let mut report = format!(
+        "`{}!`: {} arms ({} call forms, {} errors, {} internal)\n",
+        ident,
+        arms.len(),
+        count("call"),
+        count("error"),
+        count("internal")
+    );
+
+    for (idx, (pattern, kind)) in arms.iter().enumerate() {
+        let mut rendered = String::new();
+        debug_dir::render_inline(
+            &pm2::Group::new(pm2::Delimiter::Parenthesis, pattern.clone()),
+            &mut rendered,
+        );
+        report.push_str(&format!(
+            "{:>4} {:<8} {}\n",
+            idx + 1,
+            kind,
+            rendered.trim_end()
+        ));
+    }
+
+    Ok(report)
+}
+
+/// Kind of a macro arm: `error` if it reports an invalid invocation, `call` if it calls the item
+/// with the arguments of a call form, and `internal` for the arms that validate arguments
+/// or describe the parameters.
+fn arm_kind(pattern: &pm2::TokenStream, body: &pm2::TokenStream) -> &'static str {
+    let body = body.to_string();
+
+    match pattern.to_string() {
+        _ if body.contains("compile_error") || body.contains("unreachable") => "error",
+        pattern if pattern.starts_with("@ __defamed_call") => "call",
+        _ => "internal",
+    }
+}
+
+/// Name and arms of the `macro_rules!` macro in the generated code.
+///
+/// Macros are usually defined under an internal name, and re-exported under their own.
+fn macro_body(generated: pm2::TokenStream) -> Option<(pm2::Ident, pm2::TokenStream)> {
+    let tokens = generated.into_iter().collect::<Vec<_>>();
+
+    let (ident, body) = tokens.windows(4).find_map(|window| match window {
+        [pm2::TokenTree::Ident(rules), pm2::TokenTree::Punct(bang), pm2::TokenTree::Ident(ident), pm2::TokenTree::Group(body)]
+            if rules == "macro_rules" && bang.as_char() == '!' =>
+        {
+            Some((ident.clone(), body.stream()))
+        }
+        _ => None,
+    })?;
+    let reexport = tokens.windows(4).find_map(|window| match window {
+        [pm2::TokenTree::Ident(kw_use), pm2::TokenTree::Ident(defined), pm2::TokenTree::Ident(kw_as), pm2::TokenTree::Ident(name)]
+            if kw_use == "use" && *defined == ident && kw_as == "as" =>
+        {
+            Some(name.clone())
+        }
+        _ => None,
+    });
+
+    Some((reexport.unwrap_or(ident), body))
+}
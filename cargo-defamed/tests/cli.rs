@@ -0,0 +1,67 @@
+use std::process::Command;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/items.rs");
+
+/// Run the binary as `cargo defamed` would, returning its success, stdout and stderr.
+fn run(args: &[&str]) -> (bool, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-defamed"))
+        .arg("defamed")
+        .args(args)
+        .output()
+        .expect("binary runs");
+
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_report_arms() {
+    let (success, stdout, _) = run(&[FIXTURE, "scale"]);
+    assert!(success);
+    assert!(stdout.starts_with("`scale!`: "));
+    assert!(stdout.contains("   1 internal (@ params)"));
+    assert!(stdout.lines().any(|line| {
+        line.starts_with("   2 call     (@ __defamed_call_")
+            && line.ends_with(" : tt) *] ($value_val : expr) ,)")
+    }));
+
+    let (success, stdout, _) = run(&[FIXTURE, "area"]);
+    assert!(success);
+    assert!(stdout.starts_with("`area!`: "));
+
+    let (success, stdout, _) = run(&[FIXTURE, "Counter::increment"]);
+    assert!(success);
+    assert!(stdout.starts_with("`counter_increment!`: "));
+
+    let (success, stdout, _) = run(&[FIXTURE, "Greet::greet"]);
+    assert!(success);
+    assert!(stdout.starts_with("`greet_greet!`: "));
+}
+
+#[test]
+fn test_expand() {
+    let (success, stdout, _) = run(&[FIXTURE, "scale", "--expand"]);
+
+    assert!(success);
+    assert!(stdout.contains("macro_rules ! __scale_"));
+    assert!(stdout.contains("__ as scale ;\n"));
+}
+
+#[test]
+fn test_errors() {
+    let (success, _, stderr) = run(&[FIXTURE, "missing"]);
+    assert!(!success);
+    assert!(stderr.contains("no item `missing` annotated with `#[defamed]`"));
+
+    // errors of the attribute are reported instead of the arms
+    let (success, _, stderr) = run(&[FIXTURE, "invalid"]);
+    assert!(!success);
+    assert!(stderr.contains("default parameters must come after all required parameters"));
+
+    let (success, _, stderr) = run(&[FIXTURE]);
+    assert!(!success);
+    assert!(stderr.starts_with("usage: cargo defamed"));
+}
@@ -0,0 +1,33 @@
+//! Items inspected by the tests, never compiled.
+
+#[defamed::defamed]
+fn scale(value: u32, #[def(2)] factor: u32) -> u32 {
+    value * factor
+}
+
+#[defamed::defamed(crate)]
+pub mod shapes {
+    pub fn area(width: u32, #[def(1)] height: u32) -> u32 {
+        width * height
+    }
+}
+
+struct Counter {
+    count: i32,
+}
+
+#[defamed::defamed]
+impl Counter {
+    fn increment(&mut self, #[def(1)] step: i32) -> i32 {
+        self.count += step;
+        self.count
+    }
+}
+
+#[defamed::defamed]
+trait Greet {
+    fn greet(&self, #[def("hello")] greeting: &str) -> impl std::fmt::Display;
+}
+
+#[defamed::defamed]
+fn invalid(#[def] a: u32, b: u32) {}
@@ -0,0 +1,119 @@
+//! Benchmarks for the permutation engine and the macro arms emitted from it.
+//!
+//! ```sh
+//! cargo bench -p defamed-core
+//! ```
+
+use std::rc::Rc;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use defamed_core::{
+    expand::{block_logic, config},
+    permute,
+    traits::ToMacroPattern,
+    DEFAULT_HELPER_ATTR,
+};
+
+/// Number of required and default parameters of the benchmarked items
+const ARITIES: [(usize, usize); 5] = [(1, 1), (2, 2), (3, 3), (4, 4), (5, 3)];
+
+/// A function with the given number of required and default parameters.
+fn item_fn(required: usize, default: usize) -> syn::ItemFn {
+    let params = (0..required)
+        .map(|idx| format!("r{}: u32", idx))
+        .chain((0..default).map(|idx| format!("#[def] d{}: u32", idx)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    syn::parse_str(&format!("fn item({}) {{}}", params)).expect("valid function")
+}
+
+/// Parameters of [item_fn], split into required and default ones.
+fn params(
+    required: usize,
+    default: usize,
+) -> (
+    Vec<Rc<permute::params::FunctionParam>>,
+    Vec<Rc<permute::params::FunctionParam>>,
+) {
+    let helper = syn::Ident::new(DEFAULT_HELPER_ATTR, proc_macro2::Span::call_site());
+    let params = permute::params::FunctionParams::from_punctuated(
+        item_fn(required, default).sig.inputs,
+        &helper,
+    )
+    .expect("valid parameters")
+    .params
+    .into_iter()
+    .map(Rc::new)
+    .collect::<Vec<_>>();
+
+    permute::split_defaults(params)
+}
+
+fn bench_permute(c: &mut Criterion) {
+    let mut group = c.benchmark_group("permute_forms");
+
+    for (required, default) in ARITIES {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}+{}", required, default)),
+            &(required, default),
+            |b, &(required, default)| {
+                b.iter(|| {
+                    permute::permute_forms::<usize>(
+                        black_box((0..required).collect()),
+                        black_box((required..required + default).collect()),
+                    )
+                    .count()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_macro_pattern(c: &mut Criterion) {
+    let mut group = c.benchmark_group("to_macro_pattern");
+
+    for (required, default) in ARITIES {
+        let (required_params, default_params) = params(required, default);
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}+{}", required, default)),
+            &(required_params, default_params),
+            |b, (required, default)| {
+                b.iter(|| {
+                    permute::permute_forms(required.clone(), default.clone())
+                        .flatten()
+                        .filter_map(|item| item.to_macro_pattern())
+                        .count()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_item_fn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("item_fn");
+    let config = config::MacroConfig {
+        strategy: Some(config::Strategy::Permute),
+        ..Default::default()
+    };
+
+    for (required, default) in ARITIES {
+        let item = item_fn(required, default);
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}+{}", required, default)),
+            &item,
+            |b, item| b.iter(|| block_logic::item_fn(item.clone(), config.clone()).generated),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_permute, bench_macro_pattern, bench_item_fn);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+//! Detects features of the compiler that the generated macros can use.
+
+use std::{env, process::Command};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(collapse_debuginfo)");
+
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let minor = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .and_then(|version| version.split('.').nth(1)?.parse::<u32>().ok());
+
+    // `#[collapse_debuginfo(yes)]` is stable since 1.79
+    if minor.is_some_and(|minor| minor >= 79) {
+        println!("cargo:rustc-cfg=collapse_debuginfo");
+    }
+}
@@ -0,0 +1,169 @@
+//! Code generation backends, which turn the call forms of an item into the arms of its macro.
+
+use proc_macro2 as pm2;
+
+use crate::traits::{PatternSyntax, ToMacroPattern};
+
+/// Generates the arms of a macro from the call forms it accepts.
+///
+/// Every call form lists the items of the underlying item (its parameters or fields)
+/// in the order they are passed to the macro, each marked as positional, named or defaulted.
+/// A backend renders the pattern matching each form, and writes the arms calling the item from them
+/// into the body of the macro, one at a time.
+///
+/// The strategies of `defamed` are built-in backends,
+/// and other proc-macro crates can implement their own expansion strategy the same way.
+///
+/// ```
+/// use defamed_core::{
+///     backend::{Backend, MacroArms},
+///     traits::{PatternSyntax, ToMacroPattern},
+/// };
+/// use proc_macro2::TokenStream;
+/// use quote::{format_ident, quote};
+///
+/// /// A parameter that is always passed positionally
+/// struct Param(&'static str);
+///
+/// impl ToMacroPattern for Param {
+///     fn to_macro_pattern_with(&self, syntax: PatternSyntax) -> Option<TokenStream> {
+///         Some(syntax.capture(&format_ident!("{}", self.0)))
+///     }
+///
+///     fn to_func_call_pattern(&self) -> TokenStream {
+///         let var = format_ident!("{}", self.0);
+///         quote! {$#var}
+///     }
+///
+///     fn to_default_value(&self) -> Option<TokenStream> {
+///         None
+///     }
+/// }
+///
+/// /// Only accepts all arguments in declaration order
+/// struct Positional;
+///
+/// impl Backend<Param> for Positional {
+///     fn render_pattern(&self, form: &[Param]) -> TokenStream {
+///         let patterns = form.iter().filter_map(|p| self.item_pattern(p));
+///         quote! {#(#patterns),*}
+///     }
+///
+///     fn emit_arms(
+///         &mut self,
+///         forms: &mut dyn Iterator<Item = Vec<Param>>,
+///         arms: &mut MacroArms,
+///     ) -> syn::Result<()> {
+///         let declared = forms.next().unwrap_or_default();
+///         let pattern = self.render_pattern(&declared);
+///         let args = declared.iter().map(Param::to_func_call_pattern);
+///
+///         arms.push(quote! {(#pattern) => { area(#(#args),*) }});
+///         Ok(())
+///     }
+/// }
+///
+/// let mut forms = std::iter::once(vec![Param("width"), Param("height")]);
+/// let mut arms = MacroArms::new(quote! {;});
+/// Positional.emit_arms(&mut forms, &mut arms).unwrap();
+///
+/// assert_eq!(arms.len(), 1);
+/// assert_eq!(
+///     arms.into_body().to_string(),
+///     "($ width : expr , $ height : expr) => { area ($ width , $ height) }"
+/// );
+/// ```
+pub trait Backend<P: ToMacroPattern> {
+    /// Syntax the arguments are matched with, or `None` for the syntax of each item.
+    fn pattern_syntax(&self) -> Option<PatternSyntax> {
+        None
+    }
+
+    /// Pattern matching one item of a call form, with the syntax selected by [pattern_syntax](Self::pattern_syntax).
+    fn item_pattern(&self, item: &P) -> Option<pm2::TokenStream> {
+        match self.pattern_syntax() {
+            Some(syntax) => item.to_macro_pattern_with(syntax),
+            None => item.to_macro_pattern(),
+        }
+    }
+
+    /// Render the pattern matching the arguments of one call form, in an arm of the macro.
+    fn render_pattern(&self, form: &[P]) -> pm2::TokenStream;
+
+    /// Write the arms of the macro accepting every call form in `forms` into `arms`.
+    ///
+    /// The first form lists the items in declaration order, which is also the order they are called in.
+    /// Forms that cannot be called are reported as an error at the item.
+    fn emit_arms(
+        &mut self,
+        forms: &mut dyn Iterator<Item = Vec<P>>,
+        arms: &mut MacroArms,
+    ) -> syn::Result<()>;
+}
+
+/// Arms of a macro, written into its body as they are emitted.
+pub struct MacroArms {
+    body: pm2::TokenStream,
+    /// Tokens between two arms
+    separator: pm2::TokenStream,
+    /// Applied to every arm before it is written
+    rewrite: Option<Box<dyn Fn(pm2::TokenStream) -> pm2::TokenStream>>,
+    count: usize,
+}
+
+impl MacroArms {
+    /// An empty body, whose arms are separated by `separator`: `;` in `macro_rules!`, `,` in macros 2.0.
+    pub fn new(separator: pm2::TokenStream) -> Self {
+        Self {
+            body: pm2::TokenStream::new(),
+            separator,
+            rewrite: None,
+            count: 0,
+        }
+    }
+
+    /// Rewrite every arm with `rewrite` before it is written.
+    pub fn with_rewrite(
+        mut self,
+        rewrite: impl Fn(pm2::TokenStream) -> pm2::TokenStream + 'static,
+    ) -> Self {
+        self.rewrite = Some(Box::new(rewrite));
+        self
+    }
+
+    /// Write an arm after the arms written so far.
+    pub fn push(&mut self, arm: pm2::TokenStream) {
+        if self.count > 0 {
+            self.body.extend(self.separator.clone());
+        }
+
+        match &self.rewrite {
+            Some(rewrite) => self.body.extend(rewrite(arm)),
+            None => self.body.extend(arm),
+        }
+        self.count += 1;
+    }
+
+    /// Number of arms written.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Checks if no arm was written.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Body of the macro, with every arm written.
+    pub fn into_body(self) -> pm2::TokenStream {
+        self.body
+    }
+}
+
+impl Extend<pm2::TokenStream> for MacroArms {
+    fn extend<I: IntoIterator<Item = pm2::TokenStream>>(&mut self, iter: I) {
+        for arm in iter {
+            self.push(arm);
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! Expansion of the `#[defamed]` attribute.
+//!
+//! The attribute is a thin proc-macro crate calling into these modules,
+//! which `cargo-defamed` and the benchmarks also call to expand items without a compiler.
+//! They only follow the needs of the attribute, and are not covered by the semver guarantees of this crate.
+
+pub mod block_logic;
+pub mod config;
+pub mod debug_dir;
+pub mod diagnostic;
+pub mod macro_gen;
+pub mod signatures;
+pub mod timings;
+
+mod shared;
+
+pub use shared::*;
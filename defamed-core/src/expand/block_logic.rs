@@ -0,0 +1,3007 @@
+//! Various methods for manipulating a particular block of code.
+
+use std::rc::Rc;
+
+use proc_macro as pm;
+use proc_macro2 as pm2;
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+
+use super::{
+    config::{MacroConfig, NamedArgs, Receiver, RequireDocs, Strategy},
+    diagnostic::Diagnostic,
+    macro_gen::{self, MacroType},
+    timings::Timings,
+};
+use crate::{
+    permute::{
+        self,
+        fields::{StructField, StructFields},
+        params, Forms, ParamAttr, PermutedItem,
+    },
+    traits::{DocInfo, StripAttributes, ToDocInfo},
+};
+
+/// Output of a processing function
+pub struct ProcOutput {
+    /// Modified code to be substituted in-place
+    pub modified: pm2::TokenStream,
+    /// Generated code to be appended to the end of macro invocation
+    pub generated: pm2::TokenStream,
+}
+
+impl From<pm::TokenStream> for ProcOutput {
+    fn from(value: pm::TokenStream) -> Self {
+        Self {
+            modified: value.into(),
+            generated: Default::default(),
+        }
+    }
+}
+
+impl From<pm2::TokenStream> for ProcOutput {
+    fn from(value: pm2::TokenStream) -> Self {
+        Self {
+            modified: value,
+            generated: Default::default(),
+        }
+    }
+}
+
+impl From<ProcOutput> for pm::TokenStream {
+    fn from(value: ProcOutput) -> Self {
+        let mut modified = value.modified;
+        modified.extend(value.generated);
+
+        modified.into()
+    }
+}
+
+/// Process a standalone function.
+/// The crate path of the funciton is passed as an optional parameter.
+pub fn item_fn(input: syn::ItemFn, config: MacroConfig) -> ProcOutput {
+    let mut fallback = syn::Item::Fn(input.clone());
+    let helper = config.helper_attr();
+
+    let syn::ItemFn {
+        mut attrs,
+        vis,
+        sig,
+        block,
+    } = input;
+
+    // a test with parameters cannot run on its own, only as the helper of the tests of `test_matrix`
+    if let Some(test) = attrs
+        .iter()
+        .find(|a| !sig.inputs.is_empty() && a.path().is_ident("test"))
+    {
+        if !config.test_matrix {
+            let error = syn::Error::new_spanned(
+                test,
+                "a `#[test]` function with parameters cannot run on its own, \
+                add the `test_matrix` option to call it from generated tests",
+            );
+            // the test harness would report the parameters again
+            if let syn::Item::Fn(f) = &mut fallback {
+                compile_with_tests(&mut f.attrs);
+            }
+
+            return recover(fallback, &helper, error);
+        }
+
+        compile_with_tests(&mut attrs);
+    }
+
+    if let Err(e) = check_fn_path(&vis, &config, &sig.ident) {
+        return recover(fallback, &helper, e);
+    }
+
+    let (new_sig, generated) = match fn_sig_macro(
+        &mut attrs,
+        &vis,
+        &sig,
+        None,
+        None,
+        &config,
+        MacroType::Function,
+    ) {
+        Ok(res) => res,
+        Err(e) => return recover(fallback, &helper, e),
+    };
+
+    inline_exported(&mut attrs, &vis, &block, &config);
+
+    let mod_fn = syn::ItemFn {
+        attrs,
+        vis,
+        sig: new_sig,
+        block,
+    }
+    .to_token_stream();
+
+    ProcOutput {
+        modified: mod_fn,
+        generated,
+    }
+}
+
+/// Mark a small function `#[inline]` with `auto_inline` if its macro is exported,
+/// as callers in other crates cannot inline it otherwise.
+///
+/// Functions already annotated with `#[inline]` or `#[cold]` are left as they are.
+fn inline_exported(
+    attrs: &mut Vec<syn::Attribute>,
+    vis: &syn::Visibility,
+    block: &syn::Block,
+    config: &MacroConfig,
+) {
+    let annotated = attrs
+        .iter()
+        .any(|a| a.path().is_ident("inline") || a.path().is_ident("cold"));
+
+    if config.auto_inline
+        && matches!(vis, syn::Visibility::Public(_))
+        && block.stmts.len() <= crate::expand::AUTO_INLINE_STMTS
+        && !annotated
+    {
+        attrs.push(syn::parse_quote! {#[inline]});
+    }
+}
+
+/// Check visibility vs provided path: the macros of public functions refer to them through the path.
+fn check_fn_path(
+    vis: &syn::Visibility,
+    config: &MacroConfig,
+    ident: &syn::Ident,
+) -> Result<(), syn::Error> {
+    match (vis, config.path.as_ref()) {
+        (syn::Visibility::Restricted(syn::VisRestricted { path, .. }), None)
+            if !path.is_ident("self") =>
+        {
+            Err(syn::Error::new(
+                ident.span(),
+                "Attribute requires a path to the function for public functions",
+            ))
+        }
+        (syn::Visibility::Public(_), None) => Err(syn::Error::new(
+            ident.span(),
+            "Attribute requires a path to the function for public functions",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Parse the parameters of a function signature and generate its macro.
+///
+/// Returns the signature with all helper attributes stripped, along with the generated macro.
+/// The default values of the parameters are documented in `attrs`.
+fn fn_sig_macro(
+    attrs: &mut Vec<syn::Attribute>,
+    vis: &syn::Visibility,
+    sig: &syn::Signature,
+    impl_generics: Option<&syn::Generics>,
+    self_ty: Option<&syn::PathSegment>,
+    config: &MacroConfig,
+    output: MacroType,
+) -> Result<(syn::Signature, pm2::TokenStream), syn::Error> {
+    let mut timings = Timings::start();
+    let mut params =
+        params::FunctionParams::from_punctuated(sig.inputs.clone(), &config.helper_attr())?;
+    let mut generics = sig.generics.clone();
+    params.extract_const_defaults(&mut generics)?;
+    if let Some(variadic) = &sig.variadic {
+        params.push_variadic(variadic)?;
+    }
+    if config.const_defaults {
+        params.require_const()?;
+    }
+    let doc_warnings = check_default_docs(config, params.undocumented_defaults(), false)?;
+
+    // default values are expanded where the macro is called, outside the `impl` block
+    if let Some(span) = params.first_self_default() {
+        match self_ty {
+            Some(ty) => params.resolve_self({
+                let root = macro_gen::path_root(config.path.as_ref());
+                quote! {#root #ty}
+            }),
+            None => {
+                return Err(syn::Error::new(
+                    span,
+                    "default values can only refer to `Self` in `impl` blocks of a type \
+                    without generic parameters, which is named where the macro is called",
+                ))
+            }
+        }
+    }
+
+    check_default_generics(
+        params.params.iter().map(|p| &p.default_value),
+        &impl_generics
+            .into_iter()
+            .chain([&sig.generics])
+            .collect::<Vec<_>>(),
+        &config.helper_attr(),
+    )?;
+
+    if let Some((default, required)) = params.first_misplaced_default() {
+        return Err(misplaced_default_error(
+            default.inner_span(),
+            required.inner_span(),
+            &format!("`{}`", required.name()),
+        ));
+    }
+
+    // permutations share each parameter instead of cloning it
+    let params_inner = params
+        .params
+        .iter()
+        .cloned()
+        .map(Rc::new)
+        .collect::<Vec<_>>();
+    let (positional, default) = permute::split_defaults(params_inner);
+
+    let mut config = resolve_strategy(
+        config,
+        &sig.ident,
+        config.named,
+        positional.len(),
+        default.len(),
+    )?;
+    let infers = infers_placeholders(
+        &sig.generics,
+        sig.inputs.iter().filter_map(|arg| match arg {
+            syn::FnArg::Typed(pat) => Some(&*pat.ty),
+            syn::FnArg::Receiver(_) => None,
+        }),
+    );
+    // placeholders do not match the brackets around variadic arguments
+    config.doctest &= infers && !params.is_variadic();
+    config.self_test &= infers && !params.is_variadic();
+    let config = &config;
+    check_test_matrix(config, sig, &params, &output)?;
+    timings.phase("parse");
+
+    // variadic arguments are forwarded as a whole, from the arm matching them
+    if params.is_variadic() && matches!(config.strategy(), Strategy::Muncher | Strategy::ArgsStruct)
+    {
+        return Err(syn::Error::new(
+            sig.ident.span(),
+            "variadic arguments are only supported by the `permute` and `canonical` strategies",
+        ));
+    }
+
+    let permuted_concat: Forms<_> = match config.strategy() {
+        Strategy::Permute => match config.named {
+            NamedArgs::Any => crate::permute::permute_forms(positional, default),
+            NamedArgs::Ordered => Box::new(crate::permute::permute_ordered(positional, default)),
+            NamedArgs::Disabled => {
+                Box::new(crate::permute::permute_tuple_struct(positional, default))
+            }
+        },
+        Strategy::Muncher => Box::new(std::iter::once(crate::permute::declaration_order(
+            positional, default,
+        ))),
+        Strategy::Canonical => Box::new(crate::permute::canonical_forms(positional, default)),
+        Strategy::ArgsStruct => match output {
+            // const arguments cannot be collected at runtime
+            _ if sig.generics.const_params().any(|c| c.default.is_some()) => {
+                return Err(syn::Error::new(
+                    sig.ident.span(),
+                    "the `args_struct` strategy does not support const parameters with a default",
+                ))
+            }
+            MacroType::Function => Box::new(std::iter::once(crate::permute::declaration_order(
+                positional, default,
+            ))),
+            _ => return Err(args_struct_unsupported(sig.ident.span())),
+        },
+    };
+
+    // let permuted = params.permute_params();
+    let new_args = params.to_punctuated();
+    let mut new_sig = sig.clone();
+    new_sig.inputs = new_args;
+    new_sig.generics = generics;
+
+    // let doc_attrs = attrs
+    //     .iter()
+    //     .cloned()
+    //     .filter(|a| a.path().is_ident("doc"))
+    //     .collect::<Vec<_>>();
+
+    let macro_link = macro_gen::macro_link(config, &output.macro_ident(&sig.ident));
+    let mut generated = macro_gen::generate_func_macro(
+        vis.clone(),
+        attrs,
+        // package_name,
+        config,
+        new_sig.ident.clone(),
+        permuted_concat,
+        output,
+        sig.asyncness,
+        timings,
+    );
+
+    if config.strategy() == Strategy::ArgsStruct {
+        generated.extend(macro_gen::generate_args_struct(
+            vis,
+            attrs,
+            config,
+            &new_sig,
+            &params.params,
+        )?);
+    }
+
+    generated.extend(doc_warnings);
+    if config.test_matrix {
+        generated.extend(macro_gen::create_test_matrix(
+            attrs,
+            config,
+            &new_sig,
+            &params.params,
+        ));
+    }
+
+    attrs.extend(item_docs(
+        config,
+        macro_link,
+        params.params.iter().map(ToDocInfo::to_doc_info),
+        false,
+    ));
+
+    Ok((new_sig, generated))
+}
+
+/// Check that the tests of `test_matrix` can call the function without arguments,
+/// and that `cases` are only given along with it.
+fn check_test_matrix(
+    config: &MacroConfig,
+    sig: &syn::Signature,
+    params: &params::FunctionParams,
+    output: &MacroType,
+) -> Result<(), syn::Error> {
+    if !config.test_matrix {
+        return match params.params.iter().find_map(|p| p.cases().first()) {
+            Some(case) => Err(syn::Error::new_spanned(
+                case,
+                "`cases` are only tested with the `test_matrix` option",
+            )),
+            None => Ok(()),
+        };
+    }
+
+    if !matches!(output, MacroType::Function) {
+        return Err(syn::Error::new(
+            sig.ident.span(),
+            "`test_matrix` only applies to free functions",
+        ));
+    }
+    // test functions are neither async nor unsafe to call
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "`test_matrix` cannot call async functions from tests",
+        ));
+    }
+    if let Some(unsafety) = &sig.unsafety {
+        return Err(syn::Error::new_spanned(
+            unsafety,
+            "`test_matrix` cannot call unsafe functions from tests",
+        ));
+    }
+    match params
+        .params
+        .iter()
+        .find(|p| matches!(p.default_value, ParamAttr::None))
+    {
+        Some(required) => Err(syn::Error::new(
+            required.inner_span(),
+            format!(
+                "`test_matrix` requires a default for every parameter, \
+                as tests are called without arguments: `{}` has none",
+                required.name()
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Choose the strategy for an item with the given number of required and default parameters,
+/// which accepts `named` arguments.
+///
+/// Without a configured strategy, items accepting named arguments in any order
+/// use the canonical strategy if they have no defaults, so none of their orders are permuted,
+/// and switch to the muncher strategy once permuting them would take more than
+/// [crate::expand::AUTO_MUNCHER_FORMS] call forms.
+///
+/// Items whose macro would still have more call forms than the configured limit are rejected.
+fn resolve_strategy(
+    config: &MacroConfig,
+    ident: &syn::Ident,
+    named: NamedArgs,
+    required: usize,
+    default: usize,
+) -> Result<MacroConfig, syn::Error> {
+    let mut config = config.clone();
+    let permuted = Strategy::Permute.form_count(named, required, default);
+
+    // without defaults, sorting the arguments leaves a single call arm
+    match config.strategy.is_none() && named == NamedArgs::Any {
+        true if default == 0 => config.strategy = Some(Strategy::Canonical),
+        true if permuted > crate::expand::AUTO_MUNCHER_FORMS => {
+            config.strategy = Some(Strategy::Muncher)
+        }
+        _ => (),
+    }
+
+    let count = config.strategy().form_count(named, required, default);
+    let limit = config.arm_limit();
+
+    match count > limit {
+        true => Err(syn::Error::new(
+            ident.span(),
+            format!(
+                "the macro for `{}` would have {} call forms, more than the limit of {}\n\n\
+                reduce the number of parameters, use the `ordered_named` or `no_named` options \
+                or another `strategy`, or raise the limit with `max_arms`",
+                ident.unraw(),
+                match count {
+                    usize::MAX => "an overflowing number of".to_string(),
+                    _ => count.to_string(),
+                },
+                limit,
+            ),
+        )),
+        false => Ok(config),
+    }
+}
+
+/// Documentation appended to the item itself, linking to its macro
+/// and listing the value of every default in a "Default values" section.
+/// It is only included along with the macro if it is gated behind a feature.
+///
+/// Fields of tuple structs are listed by their index.
+fn item_docs(
+    config: &MacroConfig,
+    macro_link: String,
+    infos: impl Iterator<Item = DocInfo>,
+    tuple: bool,
+) -> Vec<syn::Attribute> {
+    let defaults = infos
+        .enumerate()
+        .filter_map(|(idx, info)| {
+            let name = match tuple {
+                true => idx.to_string(),
+                false => info.ident,
+            };
+
+            info.default_value.map(|val| match info.doc {
+                Some(doc) => format!("- `{}`: `{}`, {}", name, val, doc),
+                None => format!("- `{}`: `{}`", name, val),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let section = match defaults.is_empty() {
+        true => vec![],
+        false => vec!["".to_string(), "# Default values".to_string()],
+    };
+
+    [
+        "".to_string(),
+        format!("See {} for named and default arguments.", macro_link),
+    ]
+    .into_iter()
+    .chain(section)
+    .chain(defaults)
+    .map(|line| match &config.feature {
+        Some(feature) => syn::parse_quote!(#[cfg_attr(feature = #feature, doc = #line)]),
+        None => syn::parse_quote!(#[doc = #line]),
+    })
+    .collect()
+}
+
+/// Report the `undocumented` defaults of an item for `require_docs`, along with their names:
+/// errors are returned, and warnings are emitted in the returned tokens.
+///
+/// Fields can also be described by their doc comments, if `doc_comments` is set.
+fn check_default_docs<'a>(
+    config: &MacroConfig,
+    undocumented: impl Iterator<Item = (pm2::Span, &'a str)>,
+    doc_comments: bool,
+) -> Result<pm2::TokenStream, syn::Error> {
+    let level = match config.require_docs {
+        Some(level) => level,
+        None => return Ok(pm2::TokenStream::new()),
+    };
+    let remedy = match doc_comments {
+        true => format!(
+            "add a doc comment or `#[{}(..., doc = \"...\")]`",
+            config.helper_attr()
+        ),
+        false => format!("add `#[{}(..., doc = \"...\")]`", config.helper_attr()),
+    };
+    let message = |name| format!("the default of `{}` is not documented, {}", name, remedy);
+
+    match level {
+        RequireDocs::Deny => crate::collect_results(
+            undocumented.map(|(span, name)| Err::<(), _>(syn::Error::new(span, message(name)))),
+        )
+        .map(|_| pm2::TokenStream::new()),
+        RequireDocs::Warn => Ok(undocumented
+            .map(|(span, name)| {
+                Diagnostic::warning("UndocumentedDefault", span, message(name)).emit()
+            })
+            .collect()),
+    }
+}
+
+/// Whether an item can be called with placeholder arguments in doctests and self-tests,
+/// which cannot be inferred as generic or `impl Trait` parameters.
+fn infers_placeholders<'a>(
+    generics: &syn::Generics,
+    mut types: impl Iterator<Item = &'a syn::Type>,
+) -> bool {
+    generics.type_params().next().is_none()
+        && generics.const_params().next().is_none()
+        && !types.any(|ty| matches!(ty, syn::Type::ImplTrait(_)))
+}
+
+/// Reject default values that refer to a type or const parameter of `generics`.
+///
+/// Default values are expanded where the macro is called, which is outside the scope of the parameters.
+fn check_default_generics<'a>(
+    defaults: impl IntoIterator<Item = &'a ParamAttr>,
+    generics: &[&syn::Generics],
+    helper: &syn::Ident,
+) -> Result<(), syn::Error> {
+    let names = generics
+        .iter()
+        .flat_map(|g| {
+            g.type_params()
+                .map(|p| &p.ident)
+                .chain(g.const_params().map(|p| &p.ident))
+        })
+        .collect::<Vec<_>>();
+
+    /// First identifier among `tokens` that is one of `names`
+    fn find(tokens: pm2::TokenStream, names: &[&syn::Ident]) -> Option<pm2::Ident> {
+        tokens.into_iter().find_map(|token| match token {
+            pm2::TokenTree::Ident(ident) if names.iter().any(|n| **n == ident) => Some(ident),
+            pm2::TokenTree::Group(group) => find(group.stream(), names),
+            _ => None,
+        })
+    }
+
+    let found = defaults.into_iter().find_map(|default| match default {
+        ParamAttr::Value(expr) => find(expr.to_token_stream(), &names),
+        ParamAttr::None | ParamAttr::Default => None,
+    });
+
+    match found {
+        Some(ident) => Err(syn::Error::new(
+            ident.span(),
+            format!(
+                "default values are expanded where the macro is called, outside the scope of the generic parameter `{}`. \
+                Use `#[{}]` to default to `Default::default()` instead",
+                ident, helper
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Error for an item that cannot be called through an args struct.
+fn args_struct_unsupported(span: pm2::Span) -> syn::Error {
+    syn::Error::new(
+        span,
+        "the `args_struct` strategy is only supported on free functions",
+    )
+}
+
+/// Error for a default parameter that is followed by a required parameter.
+/// The error points at the misplaced default, with a secondary error on the required parameter.
+fn misplaced_default_error(
+    default: pm2::Span,
+    required: pm2::Span,
+    required_name: &str,
+) -> syn::Error {
+    let mut err = syn::Error::new(
+        default,
+        "default parameters must come after all required parameters",
+    );
+    err.combine(syn::Error::new(
+        required,
+        format!(
+            "required parameter {} follows a default parameter",
+            required_name
+        ),
+    ));
+
+    err
+}
+
+/// Process a struct definition
+pub fn item_struct(input: syn::ItemStruct, config: MacroConfig) -> ProcOutput {
+    let fallback = syn::Item::Struct(input.clone());
+    let helper = config.helper_attr();
+
+    let res = match input.fields {
+        _ if input.fields.is_empty() => {
+            let warning = Diagnostic::warning(
+                "IrrelevantMacro",
+                pm2::Span::call_site(),
+                "Remove this attribute macro.",
+            )
+            .span_note(
+                input.ident.span(),
+                "Structs without fields cannot have default parameters.",
+            )
+            .emit();
+
+            Ok(quote! {
+                #input
+                #warning
+            }
+            .into())
+        }
+        syn::Fields::Named(named_fields) => item_struct_struct(
+            config,
+            input.attrs,
+            input.vis,
+            input.ident,
+            input.generics,
+            named_fields,
+            None,
+        ),
+        syn::Fields::Unnamed(unnamed_fields) => item_struct_tuple(
+            config,
+            input.attrs,
+            input.vis,
+            input.ident,
+            input.generics,
+            unnamed_fields,
+        ),
+        syn::Fields::Unit => {
+            let warning = Diagnostic::warning(
+                "IrrelevantMacro",
+                pm2::Span::call_site(),
+                "Remove this attribute macro.",
+            )
+            .span_note(
+                input.ident.span(),
+                "Unit structs do not contain any fields and cannot have default parameters.",
+            )
+            .emit();
+
+            Ok(quote! {
+                #warning
+            }
+            .into())
+        }
+    };
+
+    res.unwrap_or_else(|e| recover(fallback, &helper, e))
+}
+
+/// Process a normal struct
+fn item_struct_struct(
+    config: MacroConfig,
+    mut attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    ident: syn::Ident,
+    generics: syn::Generics,
+    fields: syn::FieldsNamed,
+    self_path: Option<pm2::TokenStream>,
+) -> Result<ProcOutput, syn::Error> {
+    let mut timings = Timings::start();
+
+    match (&vis, config.path.as_ref()) {
+        (syn::Visibility::Restricted(syn::VisRestricted { path, .. }), p) => {
+            if !fields.named.iter().all(|f| {
+                matches!(
+                    f.vis,
+                    syn::Visibility::Public(_) | syn::Visibility::Restricted(_)
+                )
+            }) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Non-private structs must have non-private fields",
+                ));
+            }
+
+            if p.is_none() && !path.is_ident("self") {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Attribute requires a path to the struct for public structs",
+                ));
+            }
+        }
+        (syn::Visibility::Public(_), p) => {
+            if !fields
+                .named
+                .iter()
+                .all(|f| matches!(f.vis, syn::Visibility::Public(_)))
+            {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Public structs must have public fields",
+                ));
+            }
+
+            if p.is_none() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Attribute requires a path to the struct for public structs",
+                ));
+            }
+        }
+        (syn::Visibility::Inherited, _) => (),
+    }
+
+    let mut n_fields =
+        StructFields::from_named(ident.clone(), fields.named.clone(), &config.helper_attr())?;
+    if let Some(path) = self_path {
+        n_fields.resolve_self(path);
+    }
+    if config.const_defaults {
+        n_fields.require_const()?;
+    }
+    let doc_warnings = check_default_docs(&config, n_fields.undocumented_defaults(), true)?;
+
+    if let Some((default, required)) = n_fields.first_misplaced_default() {
+        return Err(misplaced_default_error(
+            default.ident.span(),
+            required.ident.span(),
+            &format!("`{}`", required.ident),
+        ));
+    }
+    check_default_generics(
+        n_fields.fields.iter().map(|f| &f.default_value),
+        &[&generics],
+        &config.helper_attr(),
+    )?;
+
+    let stripped_fields = n_fields.strip_attributes();
+    let docs = item_docs(
+        &config,
+        macro_gen::macro_link(&config, &ident),
+        n_fields.fields.iter().map(ToDocInfo::to_doc_info),
+        false,
+    );
+    let fields_inner = n_fields.fields.into_iter().map(Rc::new).collect::<Vec<_>>();
+
+    let (positional, defaults) = permute::split_defaults(fields_inner);
+
+    let mut config = resolve_strategy(
+        &config,
+        &ident,
+        config.named,
+        positional.len(),
+        defaults.len(),
+    )?;
+    let infers = infers_placeholders(&generics, fields.named.iter().map(|f| &f.ty));
+    config.doctest &= infers;
+    config.self_test &= infers;
+    timings.phase("parse");
+
+    let joined: Forms<_> = match config.strategy() {
+        Strategy::Permute => {
+            let permuted: Forms<_> = match config.named {
+                NamedArgs::Any => crate::permute::permute_forms(positional, defaults),
+                NamedArgs::Ordered => {
+                    Box::new(crate::permute::permute_ordered(positional, defaults))
+                }
+                NamedArgs::Disabled => {
+                    Box::new(crate::permute::permute_tuple_struct(positional, defaults))
+                }
+            };
+
+            let dot_dot = Rc::new(StructField::dot_dot());
+
+            Box::new(permuted.map(move |mut permutation| {
+                let has_missing = permutation
+                    .iter()
+                    .any(|item| matches!(item, PermutedItem::Default(_)));
+
+                if has_missing {
+                    permutation.push(PermutedItem::Default(Rc::clone(&dot_dot)));
+                }
+
+                permutation
+            }))
+        }
+        Strategy::Muncher => Box::new(std::iter::once(crate::permute::declaration_order(
+            positional, defaults,
+        ))),
+        Strategy::Canonical => Box::new(crate::permute::canonical_forms(positional, defaults)),
+        Strategy::ArgsStruct => return Err(args_struct_unsupported(ident.span())),
+    };
+
+    let mut generated = macro_gen::generate_func_macro(
+        vis.clone(),
+        &attrs,
+        &config,
+        ident.clone(),
+        joined,
+        MacroType::Struct,
+        None,
+        timings,
+    );
+    generated.extend(doc_warnings);
+
+    attrs.extend(docs);
+
+    Ok(ProcOutput {
+        modified: syn::ItemStruct {
+            attrs,
+            vis,
+            struct_token: Default::default(),
+            ident,
+            generics,
+            fields: stripped_fields,
+            semi_token: None,
+        }
+        .to_token_stream(),
+        generated,
+    })
+}
+
+/// Process a tuple struct
+fn item_struct_tuple(
+    config: MacroConfig,
+    mut attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    ident: syn::Ident,
+    generics: syn::Generics,
+    fields: syn::FieldsUnnamed,
+) -> Result<ProcOutput, syn::Error> {
+    let mut timings = Timings::start();
+
+    match (&vis, config.path.as_ref()) {
+        (syn::Visibility::Restricted(syn::VisRestricted { path, .. }), p) => {
+            if !fields.unnamed.iter().all(|f| {
+                matches!(
+                    f.vis,
+                    syn::Visibility::Public(_) | syn::Visibility::Restricted(_)
+                )
+            }) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Non-private struct tuples must have non-private items",
+                ));
+            }
+
+            if p.is_none() && !path.is_ident("self") {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Attribute requires a path to the struct tuple for public structs",
+                ));
+            }
+        }
+        (syn::Visibility::Public(_), p) => {
+            if !fields
+                .unnamed
+                .iter()
+                .all(|f| matches!(f.vis, syn::Visibility::Public(_)))
+            {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Public struct tuples must have public items",
+                ));
+            }
+
+            if p.is_none() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Attribute requires a path to the struct for public struct tuples",
+                ));
+            }
+        }
+        (syn::Visibility::Inherited, _) => (),
+    }
+
+    let mut un_fields =
+        StructFields::from_unnamed(ident.clone(), fields.unnamed.clone(), &config.helper_attr())?;
+    if config.const_defaults {
+        un_fields.require_const()?;
+    }
+    let doc_warnings = check_default_docs(&config, un_fields.undocumented_defaults(), true)?;
+
+    if let Some((default, required)) = un_fields.first_misplaced_default() {
+        return Err(misplaced_default_error(
+            default.ident.span(),
+            required.ident.span(),
+            &format!("of type `{}`", required.ty.to_token_stream()),
+        ));
+    }
+    check_default_generics(
+        un_fields.fields.iter().map(|f| &f.default_value),
+        &[&generics],
+        &config.helper_attr(),
+    )?;
+
+    let stripped_fields = un_fields.strip_attributes();
+    let docs = item_docs(
+        &config,
+        macro_gen::macro_link(&config, &ident),
+        un_fields.fields.iter().map(ToDocInfo::to_doc_info),
+        true,
+    );
+    let fields_inner = un_fields
+        .fields
+        .into_iter()
+        .map(Rc::new)
+        .collect::<Vec<_>>();
+
+    let (positional, defaults) = permute::split_defaults(fields_inner);
+
+    // tuple structs only take positional arguments
+    let mut config = resolve_strategy(
+        &config,
+        &ident,
+        NamedArgs::Disabled,
+        positional.len(),
+        defaults.len(),
+    )?;
+    let infers = infers_placeholders(&generics, fields.unnamed.iter().map(|f| &f.ty));
+    config.doctest &= infers;
+    config.self_test &= infers;
+    timings.phase("parse");
+
+    let permuted: Forms<_> = match config.strategy() {
+        Strategy::Permute => Box::new(crate::permute::permute_tuple_struct(positional, defaults)),
+        Strategy::Muncher => Box::new(std::iter::once(crate::permute::declaration_order(
+            positional, defaults,
+        ))),
+        Strategy::Canonical => Box::new(crate::permute::canonical_forms(positional, defaults)),
+        Strategy::ArgsStruct => return Err(args_struct_unsupported(ident.span())),
+    };
+
+    let mut generated = macro_gen::generate_func_macro(
+        vis.clone(),
+        &attrs,
+        &config,
+        ident.clone(),
+        permuted,
+        MacroType::StructTuple,
+        None,
+        timings,
+    );
+    generated.extend(doc_warnings);
+
+    attrs.extend(docs);
+
+    Ok(ProcOutput {
+        modified: syn::ItemStruct {
+            attrs,
+            vis,
+            struct_token: Default::default(),
+            ident,
+            generics,
+            fields: stripped_fields,
+            semi_token: None,
+        }
+        .to_token_stream(),
+        generated,
+    })
+}
+
+/// Processes all methods and associated functions inside an `impl` block.
+///
+/// Each function generates its own macro, named after the implementing type and the function.
+pub fn item_impl(input: syn::ItemImpl, mut config: MacroConfig) -> ProcOutput {
+    // receivers of self-tests are written as the implementing type,
+    // which cannot name the parameters of the impl outside of it
+    config.self_test &= input.generics.params.is_empty();
+
+    let self_segment = match input.self_ty.as_ref() {
+        syn::Type::Path(syn::TypePath { path, .. }) => match path.segments.last() {
+            Some(seg) => seg.clone(),
+            None => {
+                return recover(
+                    syn::Item::Impl(input.clone()),
+                    &config.helper_attr(),
+                    syn::Error::new_spanned(&input.self_ty, "Expected a type path"),
+                )
+            }
+        },
+        other => {
+            return recover(
+                syn::Item::Impl(input.clone()),
+                &config.helper_attr(),
+                syn::Error::new_spanned(other, "Only impl blocks for named types are supported"),
+            )
+        }
+    };
+
+    let self_ident = self_segment.ident.clone();
+
+    // associated functions are called through the type, inferring the generic arguments of the impl
+    let mut assoc_ty = self_segment;
+    match &mut assoc_ty.arguments {
+        syn::PathArguments::AngleBracketed(args) if input.generics.params.is_empty() => {
+            args.colon2_token = Some(Default::default())
+        }
+        _ => assoc_ty.arguments = syn::PathArguments::None,
+    }
+    // `Self` in default values is only named by the type of a non-generic impl
+    let self_ty = input.generics.params.is_empty().then_some(&assoc_ty);
+
+    let mut assoc_config = config.clone();
+    assoc_config.receiver = Some(Receiver::Positional);
+    assoc_config.ext_trait = false;
+
+    let mut generated = pm2::TokenStream::new();
+    let mut items = Vec::with_capacity(input.items.len());
+
+    for item in input.items {
+        match item {
+            syn::ImplItem::Fn(mut f) if is_skipped(&f.attrs) => {
+                f.attrs.retain(|a| !is_defamed_attr(a));
+                strip_sig_helpers(&mut f.sig, &config.helper_attr());
+                items.push(syn::ImplItem::Fn(f));
+            }
+            syn::ImplItem::Fn(mut f) if f.sig.receiver().is_some() => {
+                match fn_sig_macro(
+                    &mut f.attrs,
+                    &f.vis,
+                    &f.sig,
+                    Some(&input.generics),
+                    self_ty,
+                    &config,
+                    MacroType::Method(self_ident.clone(), input.self_ty.clone(), config.receiver()),
+                ) {
+                    Ok((sig, method_macro)) => {
+                        if config.ext_trait {
+                            generated.extend(
+                                method_ext_trait(
+                                    &f,
+                                    &self_ident,
+                                    &input.self_ty,
+                                    &assoc_ty,
+                                    &input.generics,
+                                    &config,
+                                )
+                                .unwrap_or_else(|e| e.to_compile_error()),
+                            );
+                        }
+
+                        f.sig = sig;
+                        inline_exported(&mut f.attrs, &f.vis, &f.block, &config);
+                        generated.extend(method_macro);
+                    }
+                    Err(e) => {
+                        strip_sig_helpers(&mut f.sig, &config.helper_attr());
+                        generated.extend(e.to_compile_error());
+                    }
+                }
+
+                items.push(syn::ImplItem::Fn(f));
+            }
+            syn::ImplItem::Fn(mut f) => {
+                match fn_sig_macro(
+                    &mut f.attrs,
+                    &f.vis,
+                    &f.sig,
+                    Some(&input.generics),
+                    self_ty,
+                    &assoc_config,
+                    MacroType::Associated(Box::new(assoc_ty.clone())),
+                ) {
+                    Ok((sig, assoc_macro)) => {
+                        f.sig = sig;
+                        inline_exported(&mut f.attrs, &f.vis, &f.block, &config);
+                        generated.extend(assoc_macro);
+                    }
+                    Err(e) => {
+                        strip_sig_helpers(&mut f.sig, &config.helper_attr());
+                        generated.extend(e.to_compile_error());
+                    }
+                }
+
+                items.push(syn::ImplItem::Fn(f));
+            }
+            other => items.push(other),
+        }
+    }
+
+    ProcOutput {
+        modified: syn::ItemImpl { items, ..input }.to_token_stream(),
+        generated,
+    }
+}
+
+/// Generate the extension trait of a method, along with the struct collecting its arguments.
+///
+/// The struct is annotated like any other, so its macro fills in the default arguments:
+/// `value.method_with(TypeMethodArgs! { .. })`.
+/// Methods without parameters are called postfix already, and are left alone.
+fn method_ext_trait(
+    method: &syn::ImplItemFn,
+    self_ident: &syn::Ident,
+    self_ty: &syn::Type,
+    self_segment: &syn::PathSegment,
+    impl_generics: &syn::Generics,
+    config: &MacroConfig,
+) -> Result<pm2::TokenStream, syn::Error> {
+    let sig = &method.sig;
+    let helper = config.helper_attr();
+    let params = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            syn::FnArg::Typed(pat) => Some(pat),
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    if params.is_empty() {
+        return Ok(pm2::TokenStream::new());
+    }
+    if let Some(param) = impl_generics.params.first().or(sig.generics.params.first()) {
+        return Err(syn::Error::new_spanned(
+            param,
+            "`ext_trait` does not support generic parameters",
+        ));
+    }
+    if let Some(asyncness) = sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "`ext_trait` does not support async methods",
+        ));
+    }
+    if let Some(span) = params
+        .iter()
+        .find_map(|p| macro_gen::unnameable_type(p.ty.to_token_stream()))
+    {
+        return Err(syn::Error::new(
+            span,
+            "`ext_trait` requires types that can be named outside the method, \
+            without `impl Trait` or elided lifetimes",
+        ));
+    }
+
+    let names = params
+        .iter()
+        .map(|p| match &*p.pat {
+            syn::Pat::Ident(pat) => Ok(pat.ident.clone()),
+            other => Err(syn::Error::new_spanned(
+                other,
+                "`ext_trait` requires parameters to be plain identifiers",
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // fields keep the defaults and descriptions of their parameters
+    let vis = &method.vis;
+    let fields = params.iter().zip(&names).map(|(p, name)| {
+        let attrs = p
+            .attrs
+            .iter()
+            .filter(|a| crate::is_helper_attr(a, &helper) || a.path().is_ident("doc"));
+        let ty = &p.ty;
+        quote! {#(#attrs)* #vis #name: #ty}
+    });
+
+    let (trait_ident, args_ident) = macro_gen::ext_trait_idents(self_ident, &sig.ident);
+    let doc = format!(
+        " Arguments of [`{}::{}`], passed to [`{}`]",
+        self_ident,
+        sig.ident.unraw(),
+        trait_ident
+    );
+    let mut attrs = macro_gen::cfg_attrs(config, &method.attrs);
+    attrs.push(syn::parse_quote! {#[doc = #doc]});
+    let fields: syn::FieldsNamed = syn::parse_quote! {{ #(#fields,)* }};
+
+    // only the struct itself is generated here,
+    // with `Self` in the defaults naming the implementing type instead of the struct
+    let mut struct_config = config.clone();
+    struct_config.ext_trait = false;
+    struct_config.receiver = Some(Receiver::Positional);
+    // undocumented defaults are reported at the method
+    struct_config.require_docs = None;
+    // the struct is already gated along with the method
+    struct_config.feature = None;
+    let root = macro_gen::path_root(config.path.as_ref());
+    let ProcOutput {
+        modified,
+        generated,
+    } = item_struct_struct(
+        struct_config,
+        attrs,
+        vis.clone(),
+        args_ident,
+        syn::Generics::default(),
+        fields,
+        Some(quote! {#root #self_segment}),
+    )?;
+
+    let ext_trait =
+        macro_gen::generate_ext_trait(vis, &method.attrs, config, self_ident, self_ty, sig, &names);
+
+    Ok(quote! {
+        #modified
+        #generated
+        #ext_trait
+    })
+}
+
+/// Processes all methods declared in a trait definition.
+///
+/// Each method generates its own macro, named after the trait and the method,
+/// which calls the method on any type implementing the trait.
+pub fn item_trait(input: syn::ItemTrait, mut config: MacroConfig) -> ProcOutput {
+    if config.ext_trait {
+        return recover(
+            syn::Item::Trait(input.clone()),
+            &config.helper_attr(),
+            syn::Error::new(
+                input.ident.span(),
+                "`ext_trait` only applies to methods, in annotated `impl` blocks",
+            ),
+        );
+    }
+
+    // receivers of self-tests need a type implementing the trait
+    config.self_test = false;
+
+    let self_ident = input.ident.clone();
+    let self_ty: syn::Type = syn::parse_quote! {#self_ident};
+
+    let mut generated = pm2::TokenStream::new();
+    let mut items = Vec::with_capacity(input.items.len());
+
+    for item in input.items {
+        match item {
+            syn::TraitItem::Fn(mut f) if is_skipped(&f.attrs) => {
+                f.attrs.retain(|a| !is_defamed_attr(a));
+                strip_sig_helpers(&mut f.sig, &config.helper_attr());
+                items.push(syn::TraitItem::Fn(f));
+            }
+            syn::TraitItem::Fn(mut f) if f.sig.receiver().is_some() => {
+                // trait items share the visibility of the trait
+                match fn_sig_macro(
+                    &mut f.attrs,
+                    &input.vis,
+                    &f.sig,
+                    Some(&input.generics),
+                    // `Self` is the implementor, which is not known here
+                    None,
+                    &config,
+                    MacroType::Method(
+                        self_ident.clone(),
+                        Box::new(self_ty.clone()),
+                        config.receiver(),
+                    ),
+                ) {
+                    Ok((sig, method_macro)) => {
+                        f.sig = sig;
+                        generated.extend(method_macro);
+                    }
+                    Err(e) => {
+                        strip_sig_helpers(&mut f.sig, &config.helper_attr());
+                        generated.extend(e.to_compile_error());
+                    }
+                }
+
+                items.push(syn::TraitItem::Fn(f));
+            }
+            other => items.push(other),
+        }
+    }
+
+    ProcOutput {
+        modified: syn::ItemTrait { items, ..input }.to_token_stream(),
+        generated,
+    }
+}
+
+/// Processes all functions declared in an `extern` block.
+///
+/// Each function generates its own macro, which calls the foreign function.
+/// The variadic arguments of C-variadic functions are forwarded as they are.
+pub fn item_foreign_mod(input: syn::ItemForeignMod, config: MacroConfig) -> ProcOutput {
+    let helper = config.helper_attr();
+    let mut generated = pm2::TokenStream::new();
+    let mut items = Vec::with_capacity(input.items.len());
+
+    for item in input.items {
+        match item {
+            syn::ForeignItem::Fn(mut f) if is_skipped(&f.attrs) => {
+                f.attrs.retain(|a| !is_defamed_attr(a));
+                strip_sig_helpers(&mut f.sig, &helper);
+                items.push(syn::ForeignItem::Fn(f));
+            }
+            syn::ForeignItem::Fn(mut f) => {
+                // foreign functions are unsafe to call from tests
+                let matrix = match config.test_matrix {
+                    true => Err(syn::Error::new(
+                        f.sig.ident.span(),
+                        "`test_matrix` cannot call foreign functions from tests",
+                    )),
+                    false => Ok(()),
+                };
+                match check_fn_path(&f.vis, &config, &f.sig.ident)
+                    .and(matrix)
+                    .and_then(|_| {
+                        fn_sig_macro(
+                            &mut f.attrs,
+                            &f.vis,
+                            &f.sig,
+                            None,
+                            None,
+                            &config,
+                            MacroType::Function,
+                        )
+                    }) {
+                    Ok((sig, fn_macro)) => {
+                        f.sig = sig;
+                        generated.extend(fn_macro);
+                    }
+                    Err(e) => {
+                        strip_sig_helpers(&mut f.sig, &helper);
+                        generated.extend(e.to_compile_error());
+                    }
+                }
+
+                items.push(syn::ForeignItem::Fn(f));
+            }
+            other => items.push(other),
+        }
+    }
+
+    ProcOutput {
+        modified: syn::ItemForeignMod { items, ..input }.to_token_stream(),
+        generated,
+    }
+}
+
+/// Processes all public functions inside an inline module.
+///
+/// The attribute path is the path to the module containing the annotated module,
+/// similar to any other item.
+/// Functions that are annotated separately are left to expand on their own.
+pub fn item_mod(input: syn::ItemMod, config: MacroConfig) -> ProcOutput {
+    let mod_ident = &input.ident;
+
+    let items = match &input.content {
+        Some((_, items)) => items.clone(),
+        None => {
+            return recover(
+                syn::Item::Mod(input.clone()),
+                &config.helper_attr(),
+                syn::Error::new(mod_ident.span(), "Only inline modules are supported"),
+            )
+        }
+    };
+
+    let fn_path: syn::Path = match &config.path {
+        Some(p) if p.is_ident(crate::expand::ROOT_VISIBILITY_IDENT) => {
+            syn::parse_quote! {#mod_ident}
+        }
+        Some(p) => syn::parse_quote! {#p::#mod_ident},
+        None => {
+            return recover(
+                syn::Item::Mod(input.clone()),
+                &config.helper_attr(),
+                syn::Error::new(
+                    mod_ident.span(),
+                    "Attribute requires a path to the module for public functions",
+                ),
+            )
+        }
+    };
+
+    let items = items
+        .into_iter()
+        .map(|item| match item {
+            syn::Item::Fn(mut f) if is_skipped(&f.attrs) => {
+                f.attrs.retain(|a| !is_defamed_attr(a));
+                strip_sig_helpers(&mut f.sig, &config.helper_attr());
+                syn::Item::Fn(f)
+            }
+            syn::Item::Fn(f)
+                if matches!(f.vis, syn::Visibility::Public(_))
+                    && !f.attrs.iter().any(is_defamed_attr) =>
+            {
+                let fn_config = MacroConfig {
+                    path: Some(fn_path.clone()),
+                    ..config.clone()
+                };
+
+                syn::Item::Verbatim(pm::TokenStream::from(item_fn(f, fn_config)).into())
+            }
+            other => other,
+        })
+        .collect::<Vec<_>>();
+
+    syn::ItemMod {
+        content: input.content.map(|(brace, _)| (brace, items)),
+        ..input
+    }
+    .to_token_stream()
+    .into()
+}
+
+/// Emits an item with its helper attributes removed, followed by the error that
+/// prevented its macro from being generated.
+///
+/// Keeping the item around means the error is not buried under unresolved name errors
+/// from every place the item is used.
+pub fn recover(mut item: syn::Item, helper: &syn::Ident, error: syn::Error) -> ProcOutput {
+    strip_helpers(&mut item, helper);
+
+    ProcOutput {
+        modified: item.to_token_stream(),
+        generated: Diagnostic::from(error).emit(),
+    }
+}
+
+/// Replace the `#[test]` attribute of a function with `#[cfg(test)]`,
+/// so it is compiled along with the tests of its test matrix instead of running on its own.
+pub fn compile_with_tests(attrs: &mut [syn::Attribute]) {
+    if let Some(test) = attrs.iter_mut().find(|a| a.path().is_ident("test")) {
+        *test = syn::parse_quote! {#[cfg(test)]};
+    }
+}
+
+/// Remove the helper attributes from an item, so it compiles without its macro.
+pub fn strip_helpers(item: &mut syn::Item, helper: &syn::Ident) {
+    match item {
+        syn::Item::Fn(f) => strip_sig_helpers(&mut f.sig, helper),
+        syn::Item::Struct(s) => s
+            .fields
+            .iter_mut()
+            .for_each(|field| field.attrs.retain(|a| !crate::is_helper_attr(a, helper))),
+        syn::Item::Impl(i) => i.items.iter_mut().for_each(|item| {
+            if let syn::ImplItem::Fn(f) = item {
+                strip_sig_helpers(&mut f.sig, helper)
+            }
+        }),
+        syn::Item::Trait(t) => t.items.iter_mut().for_each(|item| {
+            if let syn::TraitItem::Fn(f) = item {
+                strip_sig_helpers(&mut f.sig, helper)
+            }
+        }),
+        syn::Item::ForeignMod(m) => m.items.iter_mut().for_each(|item| {
+            if let syn::ForeignItem::Fn(f) = item {
+                strip_sig_helpers(&mut f.sig, helper)
+            }
+        }),
+        syn::Item::Mod(m) => {
+            if let Some((_, items)) = &mut m.content {
+                items.iter_mut().for_each(|item| {
+                    if let syn::Item::Fn(f) = item {
+                        strip_sig_helpers(&mut f.sig, helper)
+                    }
+                })
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Removes helper attributes from the parameters of a function signature.
+fn strip_sig_helpers(sig: &mut syn::Signature, helper: &syn::Ident) {
+    for arg in sig.inputs.iter_mut() {
+        let attrs = match arg {
+            syn::FnArg::Receiver(r) => &mut r.attrs,
+            syn::FnArg::Typed(t) => &mut t.attrs,
+        };
+        attrs.retain(|a| !crate::is_helper_attr(a, helper));
+    }
+}
+
+/// Checks if an item is opted out of macro generation with `#[defamed(skip)]`.
+fn is_skipped(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().filter(|a| is_defamed_attr(a)).any(|a| {
+        a.parse_args::<MacroConfig>()
+            .map(|config| config.skip)
+            .unwrap_or(false)
+    })
+}
+
+/// Checks if an attribute invokes this macro.
+fn is_defamed_attr(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .map(|seg| seg.ident == "defamed")
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::*;
+
+    /// Hash suffixed to the internal identifiers of the macro `name`, for items without a path.
+    fn hash(name: &str) -> String {
+        macro_gen::expansion_hash(
+            &MacroConfig::default(),
+            &syn::Ident::new(name, pm2::Span::call_site()),
+        )
+    }
+
+    #[test]
+    fn test_cfg_propagation() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            #[cfg(feature = "gui")]
+            fn render(width: u32, #[def] height: u32) {}
+        })
+        .unwrap();
+
+        let output = item_fn(item, MacroConfig::default());
+        let generated = output.generated.to_string();
+
+        // the macro definition, its re-export, and the module of parameters
+        assert_eq!(generated.matches("cfg (feature = \"gui\")").count(), 3);
+    }
+
+    #[test]
+    fn test_skip_impl_method() {
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Counter {
+                fn increment(&mut self, #[def(1)] step: i32) {}
+
+                #[defamed::defamed(skip)]
+                fn reset(&mut self, #[def(0)] count: i32) {}
+            }
+        })
+        .unwrap();
+
+        let output = item_impl(item, MacroConfig::default());
+
+        assert!(output.generated.to_string().contains("counter_increment"));
+        assert!(!output.generated.to_string().contains("counter_reset"));
+        assert!(!output.modified.to_string().contains("defamed"));
+        // skipped items compile without the macro
+        assert!(output
+            .modified
+            .to_string()
+            .contains("fn reset (& mut self , count : i32)"));
+    }
+
+    #[test]
+    fn test_forward_param_attrs() {
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Counter {
+                fn step(
+                    #[cfg_attr(all(), allow(unused))] &self,
+                    #[allow(unused_variables)] #[def] by: i32,
+                ) {}
+            }
+        })
+        .unwrap();
+
+        let modified = item_impl(item, MacroConfig::default()).modified.to_string();
+
+        assert!(modified.contains("# [cfg_attr (all () , allow (unused))] & self"));
+        assert!(modified.contains("# [allow (unused_variables)] by : i32"));
+        assert!(!modified.contains("[def"));
+    }
+
+    #[test]
+    fn test_muncher_arm_count() {
+        let count_arms = |strategy| {
+            let item: syn::ItemFn = syn::parse2(quote! {
+                fn item(a: i32, #[def] b: i32, #[def] c: i32, #[def] d: i32, #[def] e: i32) {}
+            })
+            .unwrap();
+            let config = MacroConfig {
+                strategy,
+                ..Default::default()
+            };
+
+            item_fn(item, config)
+                .generated
+                .to_string()
+                .matches("=>")
+                .count()
+        };
+
+        let permute = count_arms(Some(Strategy::Permute));
+        let muncher = count_arms(Some(Strategy::Muncher));
+        let canonical = count_arms(Some(Strategy::Canonical));
+
+        assert!(muncher < permute);
+        assert!(canonical < permute);
+        // each parameter adds a default arm and two named arms
+        assert!(muncher < 30);
+    }
+
+    #[test]
+    fn test_args_struct() {
+        let config = MacroConfig {
+            strategy: Some(Strategy::ArgsStruct),
+            ..Default::default()
+        };
+
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32, #[def(3)] c: i32) {}
+        })
+        .unwrap();
+        let generated = item_fn(item, config.clone()).generated.to_string();
+
+        assert!(generated.contains("struct __defamed_args_item"));
+        assert!(generated.contains("let _ = SuppliedArguments :: a ;"));
+        assert!(!generated.contains("SuppliedArguments :: b"));
+
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: &str, #[def] b: i32) {}
+        })
+        .unwrap();
+        let generated = item_fn(item, config.clone()).generated.to_string();
+        assert!(generated.contains("elided lifetimes"));
+
+        // the call keeps the semantics of codegen attributes
+        let item: syn::ItemFn = syn::parse2(quote! {
+            #[target_feature(enable = "avx2")]
+            #[no_mangle]
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+        let output = item_fn(item, config.clone());
+        assert!(output
+            .modified
+            .to_string()
+            .starts_with("# [target_feature (enable = \"avx2\")] # [no_mangle]"));
+        assert!(output
+            .generated
+            .to_string()
+            .contains("# [target_feature (enable = \"avx2\")] fn call (self)"));
+
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Counter {
+                fn step(&self, #[def] by: i32) {}
+            }
+        })
+        .unwrap();
+        let generated = item_impl(item, config).generated.to_string();
+        assert!(generated.contains("only supported on free functions"));
+    }
+
+    #[test]
+    fn test_arm_limit() {
+        let item = || -> syn::ItemFn {
+            syn::parse2(quote! {
+                fn item(a: i32, b: i32, #[def] c: i32, #[def] d: i32) {}
+            })
+            .unwrap()
+        };
+        let limited = MacroConfig {
+            max_arms: Some(20),
+            ..Default::default()
+        };
+
+        let output = item_fn(item(), limited.clone()).generated.to_string();
+        assert!(output.contains("would have 23 call forms, more than the limit of 20"));
+
+        // other named arguments generate fewer forms
+        let ordered = MacroConfig {
+            named: NamedArgs::Ordered,
+            ..limited
+        };
+        let output = item_fn(item(), ordered).generated.to_string();
+        assert!(!output.contains("call forms"));
+    }
+
+    #[test]
+    fn test_auto_strategy() {
+        let item = || -> syn::ItemFn {
+            syn::parse2(quote! {
+                fn item(
+                    a: i32, b: i32, c: i32, d: i32, e: i32, f: i32,
+                    #[def] g: i32, #[def] h: i32, #[def] i: i32,
+                    #[def] j: i32, #[def] k: i32, #[def] l: i32,
+                ) {}
+            })
+            .unwrap()
+        };
+
+        // too many forms to permute, so arguments are munched
+        let output = item_fn(item(), MacroConfig::default())
+            .generated
+            .to_string();
+        assert!(output.contains("@ __defamed_arg_"));
+        assert!(output.matches("=>").count() < 100);
+
+        let permute = MacroConfig {
+            strategy: Some(Strategy::Permute),
+            ..Default::default()
+        };
+        let output = item_fn(item(), permute).generated.to_string();
+        assert!(output.contains("more than the limit of"));
+
+        // small items are still permuted
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+        let output = item_fn(item, MacroConfig::default()).generated.to_string();
+        assert!(!output.contains("@ __defamed_arg_"));
+
+        // without defaults, arguments are sorted into a single call arm
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, b: i32, c: i32, d: i32, e: i32, f: i32, g: i32) {}
+        })
+        .unwrap();
+        let output = item_fn(item, MacroConfig::default()).generated.to_string();
+        let call_arms = output
+            .matches(&format!(
+                "(@ __defamed_call_{0} [$ ($ __defamed_args_{0} : tt) *] ($",
+                hash("item")
+            ))
+            .count();
+        assert_eq!(call_arms, 1);
+    }
+
+    #[test]
+    fn test_macro_doc() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def(3)] b: i32) {}
+        })
+        .unwrap();
+        let output = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(output.contains("# [doc = \"# Parameters\"]"));
+        assert!(
+            output.contains("# [doc = \"| `a` | `i32` | positionally or by name | required |\"]")
+        );
+        assert!(output.contains("# [doc = \"| `b` | `i32` | positionally or by name | `3` |\"]"));
+        assert!(output.contains("# [doc = \"item!(<a>)\"]"));
+        assert!(output.contains("# [doc = \"item!(a = <a>)\"]"));
+
+        // only public items are reachable from doctests
+        let item = || -> syn::ItemStruct {
+            syn::parse2(quote! {
+                pub struct Item { pub a: i32, #[def] pub b: i32, #[def] pub c: i32 }
+            })
+            .unwrap()
+        };
+        let config = MacroConfig {
+            path: Some(syn::parse_quote!(crate)),
+            doctest: true,
+            krate: Some("lib".to_string()),
+            ..Default::default()
+        };
+        let output = item_struct(item(), config).generated.to_string();
+        assert!(output.contains("# [doc = \"let _ = lib::Item! { todo!(), .. };\"]"));
+        assert!(output.contains("# [doc = \"let _ = lib::Item! { a: todo!(), c: todo!(), .. };\"]"));
+
+        let item: syn::ItemStruct = syn::parse2(quote! {
+            struct Item { a: i32, #[def] b: i32, #[def] c: i32 }
+        })
+        .unwrap();
+        let config = MacroConfig {
+            doctest: true,
+            ..Default::default()
+        };
+        let output = item_struct(item, config).generated.to_string();
+        assert!(output.contains("# [doc = \"Item! { a: <a>, c: <c>, .. }\"]"));
+    }
+
+    #[test]
+    fn test_self_test() {
+        let item = || -> syn::ItemFn {
+            syn::parse2(quote! {
+                fn item(a: i32, #[def] b: i32) {}
+            })
+            .unwrap()
+        };
+        let config = MacroConfig {
+            self_test: true,
+            ..Default::default()
+        };
+        let output = item_fn(item(), config.clone()).generated.to_string();
+
+        assert!(output.contains("Expands every call form of `item!`"));
+        assert!(output.contains("item ! (:: core :: todo ! ())"));
+        assert!(output.contains("item ! (:: core :: todo ! () , :: core :: todo ! ())"));
+        assert!(output.contains("item ! (a = :: core :: todo ! () , b = :: core :: todo ! ())"));
+
+        let output = item_fn(item(), MacroConfig::default())
+            .generated
+            .to_string();
+        assert!(!output.contains("Expands every call form"));
+
+        // type parameters cannot be inferred from placeholders
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item<T: Default>(a: T, #[def] b: i32) {}
+        })
+        .unwrap();
+        let output = item_fn(item, config).generated.to_string();
+        assert!(!output.contains("Expands every call form"));
+    }
+
+    #[test]
+    fn test_where_clause() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item<I, F>(a: I, f: F, #[def] b: i32) -> i32
+            where
+                I: IntoIterator<Item = u8>,
+                F: for<'a> Fn(&'a u8) -> i32,
+            {
+                0
+            }
+        })
+        .unwrap();
+        let where_clause = item.sig.generics.where_clause.to_token_stream().to_string();
+        let output = item_fn(item, MacroConfig::default());
+
+        // bounds are inferred at the call site, and never restated by the macro
+        assert!(output.modified.to_string().contains(&where_clause));
+        assert!(!output.generated.to_string().contains("where"));
+    }
+
+    #[test]
+    fn test_default_type_params() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item<T: Default = String>(a: i32, #[def] b: T) {}
+        })
+        .unwrap();
+        let output = item_fn(item, MacroConfig::default());
+
+        assert!(output
+            .modified
+            .to_string()
+            .contains("fn item < T : Default = String > (a : i32 , b : T)"));
+
+        // the receiver of the self-test is the type the impl is for
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Item<u8> {
+                fn method(&self, #[def] a: i32) {}
+            }
+        })
+        .unwrap();
+        let config = MacroConfig {
+            self_test: true,
+            ..Default::default()
+        };
+        let output = item_impl(item, config).generated.to_string();
+        assert!(output.contains("let receiver : Item < u8 > = :: core :: todo ! ()"));
+    }
+
+    #[test]
+    fn test_generic_default() {
+        let item: syn::ItemStruct = syn::parse2(quote! {
+            struct Item<const N: usize> { a: [u8; N], #[def(N)] len: usize }
+        })
+        .unwrap();
+        let output = item_struct(item, MacroConfig::default());
+        assert!(output
+            .generated
+            .to_string()
+            .contains("generic parameter `N`"));
+
+        // parameters of the impl are not in scope either
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl<T> Item<T> {
+                fn method(&self, #[def(Vec::<T>::new())] a: Vec<T>) {}
+            }
+        })
+        .unwrap();
+        let output = item_impl(item, MacroConfig::default());
+        assert!(output
+            .generated
+            .to_string()
+            .contains("generic parameter `T`"));
+
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl<T> Item<T> {
+                fn method(&self, #[def] a: Vec<T>) {}
+            }
+        })
+        .unwrap();
+        let output = item_impl(item, MacroConfig::default());
+        assert!(!output.generated.to_string().contains("generic parameter"));
+    }
+
+    #[test]
+    fn test_trait_methods() {
+        let item: syn::ItemTrait = syn::parse2(quote! {
+            trait Item {
+                fn method(&self, #[def] a: i32) -> impl Clone;
+                fn associated(#[def] a: i32) -> Self;
+            }
+        })
+        .unwrap();
+        let output = item_trait(item, MacroConfig::default());
+
+        // helper attributes are stripped from the declarations
+        assert!(output
+            .modified
+            .to_string()
+            .contains("fn method (& self , a : i32) -> impl Clone ;"));
+        assert!(output.generated.to_string().contains("item_method"));
+        assert!(!output.generated.to_string().contains("item_associated"));
+    }
+
+    #[test]
+    fn test_ext_trait() {
+        let config: MacroConfig = syn::parse2(quote! {ext_trait}).unwrap();
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Item {
+                fn consume(mut self, a: i32, #[def] b: i32) -> Self { self }
+                fn get(&self) -> i32 { 0 }
+            }
+        })
+        .unwrap();
+        let generated = item_impl(item, config.clone()).generated.to_string();
+
+        assert!(generated.contains("struct ItemConsumeArgs"));
+        assert!(generated.contains("trait ItemConsumeExt"));
+        // `mut self` is a pattern, which is not passed on
+        assert!(generated.contains("fn consume_with (self , args : ItemConsumeArgs) -> Self ;"));
+        assert!(!generated.contains("ItemGet"));
+
+        // the fields of the struct must be nameable
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Item {
+                fn borrow(&self, a: &str) {}
+            }
+        })
+        .unwrap();
+        let generated = item_impl(item, config.clone()).generated.to_string();
+        assert!(generated.contains("elided lifetimes"));
+
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl<T> Item<T> {
+                fn generic(&self, a: i32) {}
+            }
+        })
+        .unwrap();
+        let generated = item_impl(item, config.clone()).generated.to_string();
+        assert!(generated.contains("does not support generic parameters"));
+
+        // only methods of impl blocks have extension traits
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+        let generated = item_fn(item, config).generated.to_string();
+        assert!(generated.contains("only applies to methods"));
+    }
+
+    #[test]
+    fn test_associated_functions() {
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Item<u8> {
+                fn new(#[def] a: u8) -> Self {}
+            }
+        })
+        .unwrap();
+        let output = item_impl(item, MacroConfig::default());
+
+        assert!(output.modified.to_string().contains("fn new (a : u8)"));
+        assert!(output
+            .generated
+            .to_string()
+            .contains("Item :: < u8 > :: new ("));
+
+        // generic arguments of the impl are inferred
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl<T> Item<T> {
+                fn new(#[def] a: u8) -> Self {}
+            }
+        })
+        .unwrap();
+        let generated = item_impl(item, MacroConfig::default())
+            .generated
+            .to_string();
+
+        assert!(generated.contains("macro_rules ! __item_new_"));
+        assert!(generated.contains("Item :: new ("));
+    }
+
+    #[test]
+    fn test_awaited_defaults() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            async fn item(a: i32, #[def(fetch().await)] b: i32) {}
+        })
+        .unwrap();
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(generated.contains("async { item ($ a_val , fetch () . await) . await }"));
+
+        // other defaults are used as they are
+        let item: syn::ItemFn = syn::parse2(quote! {
+            async fn item(a: i32, #[def(1)] b: i32) {}
+        })
+        .unwrap();
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(!generated.contains("async {"));
+    }
+
+    #[test]
+    fn test_const_only_defaults() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item<T, const N: usize = 4>(a: T) {}
+        })
+        .unwrap();
+        let output = item_fn(item, MacroConfig::default());
+
+        assert!(output
+            .modified
+            .to_string()
+            .contains("fn item < T , const N : usize > (a : T)"));
+        assert!(output
+            .generated
+            .to_string()
+            .contains("item :: < _ , { 4 } > ($ a_val)"));
+
+        // const arguments are not known at runtime
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item<const N: usize = 4>(a: u8) {}
+        })
+        .unwrap();
+        let config = MacroConfig {
+            strategy: Some(Strategy::ArgsStruct),
+            ..Default::default()
+        };
+        let generated = item_fn(item, config).generated.to_string();
+
+        assert!(generated.contains("does not support const parameters"));
+    }
+
+    #[test]
+    fn test_foreign_functions() {
+        let item: syn::ItemForeignMod = syn::parse2(quote! {
+            extern "C" {
+                fn item(a: i32, #[def] b: i32, ...);
+                #[defamed::defamed(skip)]
+                fn skipped(#[def] a: i32);
+            }
+        })
+        .unwrap();
+        let output = item_foreign_mod(item, MacroConfig::default());
+        let modified = output.modified.to_string();
+        let generated = output.generated.to_string();
+
+        assert!(modified.contains("fn item (a : i32 , b : i32 , ...) ;"));
+        assert!(generated.contains("macro_rules ! __item_"));
+        assert!(!generated.contains("skipped"));
+        assert!(modified.contains("fn skipped (a : i32) ;"));
+        // variadic arguments are forwarded from their brackets
+        assert!(generated.contains("args = [$ ($ args_val : expr) , * $ (,) ?]"));
+        assert!(generated.contains("item ($ a_val , $ b_val , $ ($ args_val) , *)"));
+
+        // and cannot be passed on one at a time
+        let item: syn::ItemForeignMod = syn::parse2(quote! {
+            extern "C" {
+                fn item(a: i32, ...);
+            }
+        })
+        .unwrap();
+        let config = MacroConfig {
+            strategy: Some(Strategy::Muncher),
+            ..Default::default()
+        };
+        let generated = item_foreign_mod(item, config).generated.to_string();
+
+        assert!(generated.contains("only supported by the `permute` and `canonical` strategies"));
+    }
+
+    #[test]
+    fn test_const_defaults() {
+        let config = MacroConfig {
+            const_defaults: true,
+            ..Default::default()
+        };
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def(4)] b: i32) {}
+        })
+        .unwrap();
+        let generated = item_fn(item, config.clone()).generated.to_string();
+        assert!(generated.contains("item ($ a_val , const { 4 })"));
+
+        let item: syn::ItemStruct = syn::parse2(quote! {
+            struct Item(i32, #[def(4)] i32);
+        })
+        .unwrap();
+        let generated = item_struct(item, config.clone()).generated.to_string();
+        assert!(generated.contains("const { 4 }"));
+
+        // `Default::default()` is not a const expression
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+        let generated = item_fn(item, config).generated.to_string();
+        assert!(generated.contains("requires a const value for `b`"));
+    }
+
+    #[test]
+    fn test_require_docs() {
+        let config = MacroConfig {
+            require_docs: Some(RequireDocs::Deny),
+            ..Default::default()
+        };
+        let item: syn::ItemStruct = syn::parse2(quote! {
+            struct Item(
+                i32,
+                /// Doc
+                #[def] i32,
+                #[def(doc = "Doc")] i32,
+                #[def] i32,
+            );
+        })
+        .unwrap();
+        let generated = item_struct(item, config.clone()).generated.to_string();
+        assert!(generated.contains("the default of `d` is not documented"));
+        assert!(!generated.contains("`b`") && !generated.contains("`c`"));
+
+        // methods report their defaults once, not again for their extension trait
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Item {
+                fn method(&self, #[def] a: i32) {}
+            }
+        })
+        .unwrap();
+        let config = MacroConfig {
+            ext_trait: true,
+            test_matrix: false,
+            ..config
+        };
+        let generated = item_impl(item, config).generated.to_string();
+        assert_eq!(generated.matches("is not documented").count(), 1);
+    }
+
+    #[test]
+    fn test_strip_helpers() {
+        let helper = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, pm2::Span::call_site());
+        let mut item: syn::Item = syn::parse2(quote! {
+            #[test]
+            fn item(#[def(1, cases(2))] a: i32, #[defamed::def] b: i32) {}
+        })
+        .unwrap();
+        strip_helpers(&mut item, &helper);
+
+        assert_eq!(
+            item.to_token_stream().to_string(),
+            "# [test] fn item (a : i32 , b : i32) { }"
+        );
+    }
+
+    #[test]
+    fn test_test_matrix() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            #[test]
+            fn item(#[def(1, cases(2, 3))] a: i32, #[def] b: i32, #[def(cases(4))] c: i32) {}
+        })
+        .unwrap();
+        let config = MacroConfig {
+            test_matrix: true,
+            ..Default::default()
+        };
+        let output = item_fn(item.clone(), config.clone());
+        let modified = output.modified.to_string();
+        let generated = output.generated.to_string();
+
+        // the helper is compiled along with its tests
+        assert!(modified.contains("# [cfg (test)]"));
+        assert!(!modified.contains("# [test]"));
+        assert!(generated.contains("mod item { use super :: * ;"));
+        assert!(generated.contains("fn defaults () { item ! () }"));
+        assert!(generated.contains("fn a_1 () { item ! (3) }"));
+        assert!(generated
+            .contains("fn c_0 () { item ! (1 , core :: default :: Default :: default () , 4) }"));
+
+        // tests with parameters are not turned into helpers without the option
+        let output = item_fn(item, MacroConfig::default());
+        assert!(output
+            .generated
+            .to_string()
+            .contains("add the `test_matrix` option"));
+        assert!(!output.generated.to_string().contains("mod item"));
+
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+        assert!(item_fn(item, config.clone())
+            .generated
+            .to_string()
+            .contains("requires a default for every parameter"));
+
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Item {
+                fn method(&self, #[def] a: i32) {}
+            }
+        })
+        .unwrap();
+        assert!(item_impl(item, config)
+            .generated
+            .to_string()
+            .contains("only applies to free functions"));
+
+        // cases are unused without tests
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(#[def(1, cases(2))] a: i32) {}
+        })
+        .unwrap();
+        assert!(item_fn(item, MacroConfig::default())
+            .generated
+            .to_string()
+            .contains("only tested with the `test_matrix` option"));
+
+        // a test without parameters runs as it is
+        let item: syn::ItemFn = syn::parse2(quote! {
+            #[test]
+            fn item() {}
+        })
+        .unwrap();
+        let output = item_fn(item, MacroConfig::default());
+        assert!(output.modified.to_string().contains("# [test]"));
+        assert!(!output.generated.to_string().contains("mod item"));
+    }
+
+    #[test]
+    fn test_self_defaults() {
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Item<u8> {
+                /// Doc
+                fn new(#[def(Self::A)] a: u8) -> Self {}
+            }
+        })
+        .unwrap();
+        let generated = item_impl(item, MacroConfig::default())
+            .generated
+            .to_string();
+
+        assert!(generated.contains("Item :: < u8 > :: A"));
+        // docs keep the default as written
+        assert!(generated.contains("Self :: A"));
+
+        // `Self` can not be named for generic impls
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl<T> Item<T> {
+                fn new(#[def(Self::A)] a: u8) -> Self {}
+            }
+        })
+        .unwrap();
+        let generated = item_impl(item, MacroConfig::default())
+            .generated
+            .to_string();
+
+        assert!(generated.contains("compile_error"));
+        assert!(generated.contains("can only refer to `Self`"));
+    }
+
+    #[test]
+    fn test_collapse_debuginfo() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+        let output = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert_eq!(
+            output.contains("# [collapse_debuginfo (yes)]"),
+            cfg!(collapse_debuginfo)
+        );
+    }
+
+    #[test]
+    fn test_expansion_hash() {
+        let dunder = |path: syn::Path, item: pm2::TokenStream| {
+            let config = MacroConfig {
+                path: Some(path),
+                ..Default::default()
+            };
+            let generated = item_fn(syn::parse2(item).unwrap(), config)
+                .generated
+                .to_string();
+            let start = generated.find("macro_rules ! __").unwrap() + "macro_rules ! ".len();
+
+            generated[start..]
+                .split_whitespace()
+                .next()
+                .unwrap()
+                .to_string()
+        };
+
+        // paths are flattened into the same prefix
+        let nested = dunder(
+            syn::parse_quote!(a::b),
+            quote! { pub(crate) fn c(#[def] x: i32) {} },
+        );
+        let flat = dunder(
+            syn::parse_quote!(a),
+            quote! { pub(crate) fn b_c(#[def] x: i32) {} },
+        );
+        assert!(nested.starts_with("__a_b_c_") && flat.starts_with("__a_b_c_"));
+        assert_ne!(nested, flat);
+        // the suffix does not change between expansions of the same item
+        assert_eq!(
+            nested,
+            dunder(
+                syn::parse_quote!(a::b),
+                quote! { pub(crate) fn c(#[def] x: i32) {} }
+            )
+        );
+
+        // the args struct is named the same by its definition and the macro
+        let config = MacroConfig {
+            strategy: Some(Strategy::ArgsStruct),
+            ..Default::default()
+        };
+        let item: syn::ItemFn = syn::parse2(quote! { fn item(#[def] x: i32) {} }).unwrap();
+        let generated = item_fn(item, config).generated.to_string();
+        let start = generated.find("struct __defamed_args_item_").unwrap() + "struct ".len();
+        let args_ident = generated[start..].split_whitespace().next().unwrap();
+        assert!(generated.matches(args_ident).count() > 1);
+
+        // the module of parameters, the markers and the metavariables share the suffix
+        let item: syn::ItemFn = syn::parse2(quote! { fn item(#[def] x: i32) {} }).unwrap();
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+        let suffix = hash("item");
+        assert!(generated.contains(&format!("mod __defamed_params_item_{}", suffix)));
+        assert!(generated.contains(&format!(
+            "(@ params) => {{ __defamed_params_item_{} :: PARAMS }}",
+            suffix
+        )));
+        for internal in ["@ __defamed_call_", "$ __defamed_args_"] {
+            assert!(generated.contains(&format!("{}{}", internal, suffix)));
+        }
+        assert!(!generated.contains("__defamed_call "));
+        assert!(!generated.contains("__defamed_args :"));
+
+        // raw identifiers hash like their plain form, as they name the same macro
+        assert_eq!(
+            macro_gen::expansion_hash(
+                &MacroConfig::default(),
+                &syn::Ident::new_raw("item", pm2::Span::call_site())
+            ),
+            hash("item")
+        );
+    }
+
+    #[test]
+    fn test_feature_gate() {
+        let config = MacroConfig {
+            feature: Some(syn::parse_quote!("macros")),
+            self_test: true,
+            test_matrix: true,
+            ..Default::default()
+        };
+        let item: syn::ItemFn = syn::parse2(quote! {
+            #[cfg(unix)]
+            fn item(#[def(1)] a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+        let output = item_fn(item, config.clone());
+        let modified = output.modified.to_string();
+        let generated = output.generated.to_string();
+
+        // the function is always compiled, along with its documentation without the macro
+        assert!(
+            modified.starts_with("# [cfg (unix)] # [cfg_attr (feature = \"macros\" , doc = \"\")]")
+        );
+        assert!(!modified.contains("# [cfg (feature"));
+        // the macro, its module of parameters and the generated tests are gated
+        let gate = "# [cfg (unix)] # [cfg (feature = \"macros\")]";
+        assert!(generated.contains(&format!("{} # [doc (inline)]", gate)));
+        assert!(generated.contains(&format!(
+            "{} # [doc (hidden)] # [allow (unused_macros)]",
+            gate
+        )));
+        assert!(generated.contains(&format!("{} # [doc (hidden)] # [allow (dead_code", gate)));
+        assert_eq!(
+            generated
+                .matches(&format!("{} # [cfg (test)]", gate))
+                .count(),
+            2
+        );
+
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Counter {
+                fn step(&self, #[def] by: i32) {}
+            }
+        })
+        .unwrap();
+        let config = MacroConfig {
+            ext_trait: true,
+            test_matrix: false,
+            ..config
+        };
+        let generated = item_impl(item, config).generated.to_string();
+        // the extension trait and its struct are gated once, along with the macro of the struct
+        assert!(generated.contains("# [cfg (feature = \"macros\")] # [doc = \" Arguments of"));
+        assert!(generated.contains("# [cfg (feature = \"macros\")] # [doc = \" Calls"));
+        assert!(
+            !generated.contains("# [cfg (feature = \"macros\")] # [cfg (feature = \"macros\")]")
+        );
+    }
+
+    #[test]
+    fn test_pattern_syntax() {
+        let generated = |pattern: &str, item: pm2::TokenStream| {
+            let config: MacroConfig = syn::parse_str(&format!("pattern = {:?}", pattern)).unwrap();
+            item_fn(syn::parse2(item).unwrap(), config)
+                .generated
+                .to_string()
+        };
+        let item = quote! {fn item(a: i32, #[def] b: i32) {}};
+
+        let tt = generated("tt", item.clone());
+        assert!(tt.contains("b = $ b_val : tt"));
+        assert!(!tt.contains(": expr"));
+        let literal = generated("struct_literal", item.clone());
+        assert!(literal.contains("b : $ b_val : expr"));
+        assert!(literal.contains("example: `item!(<a>, b: <b>)`"));
+        assert!(generated("expr", item).contains("b = $ b_val : expr"));
+
+        // struct fields are named like in a struct literal unless configured
+        let item: syn::ItemStruct =
+            syn::parse2(quote! {struct S { a: i32, #[def] b: i32 }}).unwrap();
+        let fields = |config: MacroConfig| item_struct(item.clone(), config).generated.to_string();
+        assert!(fields(MacroConfig::default()).contains("b : $ b_val : expr"));
+        let config: MacroConfig = syn::parse2(quote! {pattern = "expr"}).unwrap();
+        assert!(fields(config).contains("b = $ b_val : expr"));
+    }
+
+    #[test]
+    fn test_auto_inline() {
+        let config = MacroConfig {
+            path: Some(syn::parse_quote!(crate)),
+            auto_inline: true,
+            ..Default::default()
+        };
+        let modified = |item: pm2::TokenStream| {
+            item_fn(syn::parse2(item).unwrap(), config.clone())
+                .modified
+                .to_string()
+        };
+
+        assert!(modified(quote! {
+            pub fn item(a: i32, #[def] b: i32) -> i32 { a + b }
+        })
+        .contains("# [inline] pub fn item"));
+        assert!(!modified(quote! {
+            pub fn item(a: i32, #[def] b: i32) -> i32 { let c = a; let d = b; let e = c + d; e }
+        })
+        .contains("inline"));
+        // private macros are not called from other crates
+        assert!(!modified(quote! {
+            pub(crate) fn item(a: i32, #[def] b: i32) -> i32 { a + b }
+        })
+        .contains("inline"));
+        assert!(!modified(quote! {
+            #[inline(never)] pub fn item(a: i32, #[def] b: i32) -> i32 { a + b }
+        })
+        .contains("# [inline]"));
+
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Item {
+                pub fn method(&self, #[def] a: i32) -> i32 { a }
+                fn private(&self, #[def] a: i32) -> i32 { a }
+            }
+        })
+        .unwrap();
+        let modified = item_impl(item, config).modified.to_string();
+        assert!(modified.contains("# [inline] pub fn method"));
+        assert!(!modified.contains("# [inline] fn private"));
+    }
+
+    #[test]
+    fn test_msrv() {
+        let item = || -> syn::ItemFn {
+            syn::parse2(quote! {
+                #[doc(alias = "i")]
+                pub fn item(a: i32, #[def] b: i32) {}
+            })
+            .unwrap()
+        };
+        let config = |msrv| MacroConfig {
+            path: Some(syn::parse_quote!(crate)),
+            self_test: true,
+            doctest: true,
+            msrv: Some(msrv),
+            krate: Some("lib".to_string()),
+            ..Default::default()
+        };
+
+        let output = item_fn(item(), config((1, 80))).generated.to_string();
+        assert!(output.contains("non_upper_case_globals , unexpected_cfgs)]"));
+        assert!(output.contains(":: core :: todo ! ()"));
+        assert!(output.contains("let _ = lib::item!(todo!());"));
+
+        let output = item_fn(item(), config((1, 39))).generated.to_string();
+        assert!(!output.contains("collapse_debuginfo"));
+        assert!(output.contains("non_upper_case_globals , unknown_lints , unexpected_cfgs)]"));
+        assert!(!output.contains("todo"));
+        assert!(output.contains(":: core :: unimplemented ! ()"));
+        assert!(output.contains("let _ = lib::item!(unimplemented!());"));
+        // aliases of the item are kept, as the item already requires them
+        assert!(output.contains("# [doc (alias = \"i\")]"));
+    }
+
+    #[test]
+    fn test_readable_arms() {
+        let item = || -> syn::ItemFn {
+            syn::parse2(quote! {
+                fn item(a: i32, #[def] b: i32) {}
+            })
+            .unwrap()
+        };
+        let config = MacroConfig {
+            readable: true,
+            ..Default::default()
+        };
+        let output = item_fn(item(), config.clone()).generated.to_string();
+
+        assert!(output.contains("# [doc = \" arm 1 of 5: positional: a; defaults used: b\"]"));
+        assert!(output.contains("# [doc = \" arm 4 of 5: positional: a; named: b\"]"));
+
+        let output = item_fn(
+            item(),
+            MacroConfig {
+                strategy: Some(Strategy::Canonical),
+                ..config
+            },
+        )
+        .generated
+        .to_string();
+        assert!(output.contains("# [doc = \" arm 1 of 2: supplied: a; defaults used: b\"]"));
+        assert!(output.contains("# [doc = \" arm 2 of 2: supplied: a, b\"]"));
+
+        let output = item_fn(item(), MacroConfig::default())
+            .generated
+            .to_string();
+        assert!(!output.contains(" arm 1 of "));
+    }
+
+    #[test]
+    fn test_shadow_params() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+        let output = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(output.contains(&format!("mod __defamed_params_item_{}", hash("item"))));
+        assert!(output.contains("pub const a : () = () ;"));
+        assert!(output.contains("pub const b : () = () ;"));
+        assert!(output.contains(&format!(
+            "let _ = __defamed_params_item_{0} :: $ __defamed_unknown_{0} ;",
+            hash("item")
+        )));
+
+        // tuple structs have no names to complete
+        let item: syn::ItemStruct = syn::parse2(quote! {
+            struct Item(i32, #[def] i32);
+        })
+        .unwrap();
+        let output = item_struct(item, MacroConfig::default())
+            .generated
+            .to_string();
+        assert!(output.contains(&format!("mod __defamed_params_Item_{}", hash("Item"))));
+        assert!(!output.contains("pub const 0"));
+    }
+
+    #[test]
+    fn test_params_metadata() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def(5)] b: Option<u8>) {}
+        })
+        .unwrap();
+        let output = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(output.contains(
+            "pub const PARAMS : & [(& str , & str , :: core :: option :: Option < & str >)] = & [\
+             (\"a\" , \"i32\" , :: core :: option :: Option :: None) , \
+             (\"b\" , \"Option < u8 >\" , :: core :: option :: Option :: Some (\"5\"))] ;"
+        ));
+    }
+
+    #[cfg(feature = "reflect")]
+    #[test]
+    fn test_reflection() {
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Counter {
+                fn increment(&mut self, #[def(1)] step: i32) {}
+            }
+        })
+        .unwrap();
+        let output = item_impl(item, MacroConfig::default())
+            .generated
+            .to_string();
+
+        assert!(output.contains(&format!(
+            "const COUNTER_INCREMENT_PARAMS : & [(& str , & str , :: core :: option :: Option < & str >)] = \
+             __defamed_params_counter_increment_{} :: PARAMS ;",
+            hash("counter_increment")
+        )));
+    }
+
+    #[test]
+    fn test_item_docs() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            /// Docs of the item
+            fn item(a: i32, #[def(3)] b: i32, #[def] c: i32) {}
+        })
+        .unwrap();
+        let modified = item_fn(item, MacroConfig::default()).modified.to_string();
+
+        // appended to the existing docs
+        let section = modified.find("# [doc = \"# Default values\"]").unwrap();
+        assert!(modified.find("Docs of the item").unwrap() < section);
+        assert!(modified.contains("# [doc = \"- `b`: `3`\"]"));
+        assert!(modified.contains("# [doc = \"- `c`: `Default::default()`\"]"));
+
+        // tuple fields are listed by index
+        let item: syn::ItemStruct = syn::parse2(quote! {
+            struct Item(i32, #[def(3)] i32);
+        })
+        .unwrap();
+        let modified = item_struct(item, MacroConfig::default())
+            .modified
+            .to_string();
+        assert!(modified.contains("# [doc = \"- `1`: `3`\"]"));
+
+        // items without defaults still link to their macro
+        let item: syn::ItemFn = syn::parse2(quote! { fn item(a: i32) {} }).unwrap();
+        let modified = item_fn(item, MacroConfig::default()).modified.to_string();
+        assert!(modified.contains("See [`item!`] for named and default arguments."));
+        assert!(!modified.contains("# Default values"));
+
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Counter {
+                pub fn step(&self, #[def] by: i32) {}
+            }
+        })
+        .unwrap();
+        let config = MacroConfig {
+            path: Some(syn::parse_quote!(inner)),
+            macro_use: true,
+            ..Default::default()
+        };
+        let output = item_impl(item, config);
+        assert!(output
+            .modified
+            .to_string()
+            .contains("See [`counter_step!`](crate::counter_step!)"));
+        assert!(output
+            .generated
+            .to_string()
+            .contains("wrapper for [`step`](method@crate::inner::Counter::step)"));
+    }
+
+    #[test]
+    fn test_doc_aliases() {
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Counter {
+                #[doc(alias = "advance")]
+                fn step(&self, #[def] by: i32) {}
+            }
+        })
+        .unwrap();
+        let config = MacroConfig {
+            aliases: vec![syn::parse_quote!("bump")],
+            ..Default::default()
+        };
+        let output = item_impl(item, config).generated.to_string();
+
+        assert!(output.contains("# [doc (alias = \"advance\")]"));
+        assert!(output.contains("# [doc (alias = \"bump\")]"));
+        assert!(output.contains("# [doc (alias = \"step\")]"));
+
+        // the macro of a function is already named after it
+        let item: syn::ItemFn = syn::parse2(quote! { fn item(a: i32) {} }).unwrap();
+        let output = item_fn(item, MacroConfig::default()).generated.to_string();
+        assert!(!output.contains("alias"));
+    }
+
+    #[test]
+    fn test_report_arms() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+        let config = MacroConfig {
+            report_arms: true,
+            ..Default::default()
+        };
+
+        let output = item_fn(item, config).generated.to_string();
+        assert!(output.contains("defamed: generated"));
+        assert!(output.contains("arms for `item`"));
+    }
+
+    #[test]
+    fn test_misplaced_default() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32, c: i32) {}
+        })
+        .unwrap();
+
+        let output = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(output.contains("default parameters must come after all required parameters"));
+        assert!(output.contains("required parameter `c` follows a default parameter"));
+    }
+
+    #[test]
+    fn test_duplicate_named_arm() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(generated.contains("argument `a` supplied twice to `item!`"));
+        assert!(generated.contains("argument `b` supplied twice to `item!`"));
+    }
+
+    #[test]
+    fn test_unknown_named_arm() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(depth: i32, #[def] width: i32) {}
+        })
+        .unwrap();
+
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(
+            generated.contains("unknown argument `dpeth` passed to `item!`, did you mean `depth`?")
+        );
+        assert!(
+            generated.contains("unknown argument `widht` passed to `item!`, did you mean `width`?")
+        );
+        assert!(generated.contains("` passed to `item!`, expected one of: `depth`, `width`"));
+    }
+
+    #[test]
+    fn test_accepted_forms_arm() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(depth: i32, #[def(3)] width: i32) {}
+        })
+        .unwrap();
+
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(generated.contains("no call form of `item!` accepts `"));
+        assert!(generated.contains("`depth`: `i32`, required"));
+        assert!(generated.contains("`width`: `i32`, defaults to `3`"));
+        assert!(generated.contains("example: `item!(<depth>, width = <width>)`"));
+    }
+
+    #[test]
+    fn test_no_params() {
+        let item: syn::ItemFn = syn::parse2(quote! { fn item() {} }).unwrap();
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(generated.contains(&format!(
+            "(@ __defamed_call_{0} [$ ($ __defamed_args_{0} : tt) *]) => {{ item () }}",
+            hash("item")
+        )));
+        assert!(generated.contains("`item!` does not take any parameters"));
+
+        let item: syn::ItemImpl = syn::parse2(quote! {
+            impl Counter {
+                fn is_zero(&self) -> bool { true }
+            }
+        })
+        .unwrap();
+        let generated = item_impl(item, MacroConfig::default())
+            .generated
+            .to_string();
+
+        assert!(generated.contains(&format!(
+            "(@ __defamed_call_{0} [$ ($ __defamed_args_{0} : tt) *] ($ self_val : expr)) => {{ $ self_val . is_zero () }}",
+            hash("counter_is_zero")
+        )));
+        assert!(generated.contains("`counter_is_zero!` only takes the receiver"));
+    }
+
+    #[test]
+    fn test_positional_after_named_arm() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        assert!(generated.contains(
+            "` passed to `item!` after a named argument, all positional arguments must come first"
+        ));
+    }
+
+    #[test]
+    fn test_arm_order() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, b: i32, #[def] c: i32) {}
+        })
+        .unwrap();
+
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+        let arm = |signature: &str| {
+            generated
+                .find(&format!(
+                    "(@ __defamed_call_{0} [$ ($ __defamed_args_{0} : tt) *] {1}) =>",
+                    hash("item"),
+                    signature
+                ))
+                .unwrap()
+        };
+
+        // positional arguments are tried first, fully named calls last
+        let positional = arm("($ a_val : expr) , ($ b_val : expr) ,");
+        let positional_default = arm("($ a_val : expr) , ($ b_val : expr) , ($ c_val : expr) ,");
+        let mixed = arm("($ a_val : expr) , b = $ b_val : expr ,");
+        let named = arm("b = $ b_val : expr , a = $ a_val : expr , c = $ c_val : expr ,");
+
+        assert!(positional < positional_default);
+        assert!(positional_default < mixed);
+        assert!(mixed < named);
+
+        // positional arguments are wrapped by the munch arms, so no call arm can match a named one
+        assert!(generated.contains(&format!(
+            "[$ ($ __defamed_out_{0}) * ($ __defamed_v_{0}) ,]",
+            hash("item")
+        )));
+    }
+
+    #[test]
+    fn test_arguments_passed_unchanged() {
+        let item: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32) {}
+        })
+        .unwrap();
+
+        let generated = item_fn(item, MacroConfig::default()).generated.to_string();
+
+        // captured arguments are forwarded as-is, keeping the spans of the caller's tokens
+        assert!(generated.contains("item ($ a_val , $ b_val)"));
+        assert!(generated.contains("item ($ a_val , core :: default :: Default :: default ())"));
+    }
+
+    #[test]
+    fn test_empty_struct() {
+        for item in [quote! { struct Empty {} }, quote! { struct Empty(); }] {
+            let item: syn::ItemStruct = syn::parse2(item.clone()).unwrap();
+            let output = item_struct(item.clone(), MacroConfig::default());
+
+            assert!(output
+                .modified
+                .to_string()
+                .starts_with(&item.to_token_stream().to_string()));
+        }
+    }
+
+    #[test]
+    fn test_recover_stripped_item() {
+        let func: syn::ItemFn = syn::parse2(quote! {
+            pub fn public(a: i32, #[def] b: i32) -> i32 { a + b }
+        })
+        .unwrap();
+        let output = item_fn(func, MacroConfig::default());
+
+        assert_eq!(
+            output.modified.to_string(),
+            quote! { pub fn public(a: i32, b: i32) -> i32 { a + b } }.to_string()
+        );
+        assert!(output.generated.to_string().contains("compile_error"));
+
+        let st: syn::ItemStruct = syn::parse2(quote! {
+            struct Misplaced { #[def] a: i32, b: i32 }
+        })
+        .unwrap();
+        let output = item_struct(st, MacroConfig::default());
+
+        assert_eq!(
+            output.modified.to_string(),
+            quote! { struct Misplaced { a: i32, b: i32 } }.to_string()
+        );
+        assert!(output.generated.to_string().contains("compile_error"));
+    }
+
+    /// Item and generated code of an expansion, rendered for snapshots.
+    ///
+    /// Changes to the snapshots in `src/snapshots` are reviewed with `cargo insta review`.
+    /// Features that change the generated code are not covered by the snapshots.
+    fn render_expansion(output: ProcOutput) -> String {
+        let ProcOutput {
+            modified,
+            generated,
+            ..
+        } = output;
+
+        crate::expand::debug_dir::render(&quote! {#modified #generated})
+    }
+
+    #[test]
+    #[cfg(not(any(
+        feature = "decl_macro",
+        feature = "proc_macro_diagnostic",
+        feature = "reflect"
+    )))]
+    fn test_snapshot_expansions() {
+        let func: syn::ItemFn = syn::parse2(quote! {
+            fn add(lhs: i32, #[def(1)] rhs: i32) -> i32 {
+                lhs + rhs
+            }
+        })
+        .unwrap();
+        insta::assert_snapshot!(
+            "function",
+            render_expansion(item_fn(func, MacroConfig::default()))
+        );
+
+        let func: syn::ItemFn = syn::parse2(quote! {
+            pub fn scale(value: u32, #[def(2)] factor: u32, #[def] offset: u32) -> u32 {
+                value * factor + offset
+            }
+        })
+        .unwrap();
+        let config: MacroConfig = syn::parse2(quote! {crate, strategy = "muncher"}).unwrap();
+        insta::assert_snapshot!("muncher", render_expansion(item_fn(func, config)));
+
+        let imp: syn::ItemImpl = syn::parse2(quote! {
+            impl Counter {
+                fn increment(&mut self, #[def(1)] step: i32) -> i32 {
+                    self.count += step;
+                    self.count
+                }
+            }
+        })
+        .unwrap();
+        insta::assert_snapshot!(
+            "method",
+            render_expansion(item_impl(imp, MacroConfig::default()))
+        );
+
+        let st: syn::ItemStruct = syn::parse2(quote! {
+            struct Point {
+                x: i32,
+                #[def]
+                y: i32,
+            }
+        })
+        .unwrap();
+        insta::assert_snapshot!(
+            "struct",
+            render_expansion(item_struct(st, MacroConfig::default()))
+        );
+
+        let st: syn::ItemStruct = syn::parse2(quote! {
+            struct Pair(i32, #[def('a')] char);
+        })
+        .unwrap();
+        insta::assert_snapshot!(
+            "tuple_struct",
+            render_expansion(item_struct(st, MacroConfig::default()))
+        );
+    }
+
+    #[test]
+    fn test_match_impl_block() {
+        let tokens = quote! {
+            impl SomeStruct {
+                pub fn new() -> Self {
+                    SomeStruct {}
+                }
+            }
+        };
+
+        let _: syn::ItemImpl = syn::parse2(tokens).unwrap();
+    }
+
+    #[test]
+    fn test_match_mod_block() {
+        let tokens = quote! {
+            mod some_module {
+                struct X{}
+            }
+        };
+
+        let _: syn::ItemMod = syn::parse2(tokens).unwrap();
+    }
+}
@@ -0,0 +1,1055 @@
+//! Options passed to the `#[defamed(...)]` attribute.
+
+use std::collections::BTreeMap;
+
+use quote::{quote, ToTokens};
+use syn::{parse::Parse, punctuated::Punctuated, spanned::Spanned, Token};
+
+use crate::{permute, traits::PatternSyntax};
+
+/// Options that only apply to a single item, which the crate and profiles do not accept.
+const ITEM_OPTIONS: [&str; 4] = ["skip", "alias", "test_matrix", "profile"];
+
+/// Options that are either on or off, turned on by their name alone or with `= true`,
+/// and off with `= false`.
+const FLAGS: [&str; 13] = [
+    "macro_use",
+    "skip",
+    "report_arms",
+    "readable",
+    "doctest",
+    "self_test",
+    "test_matrix",
+    "const_defaults",
+    "auto_inline",
+    "require_docs",
+    "ext_trait",
+    "ordered_named",
+    "no_named",
+];
+
+/// Options shared by the items of a crate, read from the `[package.metadata.defamed]` table of its manifest.
+///
+/// The manifest is read again for every item, so the options do not depend on the order items are expanded in.
+#[derive(Clone, Default)]
+pub struct CrateOptions {
+    /// Options inherited by every item
+    defaults: MacroConfig,
+    /// Options of the profiles in `[package.metadata.defamed.profiles]`, by name
+    profiles: BTreeMap<String, MacroConfig>,
+}
+
+impl CrateOptions {
+    /// Options of the package being compiled, from the manifest in `CARGO_MANIFEST_DIR`.
+    ///
+    /// Packages without a manifest, such as items expanded outside of Cargo, have no options.
+    pub fn load() -> syn::Result<Self> {
+        let manifest = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(|dir| std::path::Path::new(&dir).join("Cargo.toml"))
+            .and_then(|path| std::fs::read_to_string(path).ok());
+
+        match manifest {
+            Some(manifest) => Self::from_manifest(&manifest),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Options of the `[package.metadata.defamed]` table of a manifest,
+    /// and of the profiles in its `profiles` table.
+    pub fn from_manifest(manifest: &str) -> syn::Result<Self> {
+        let error = |table: &str, message: String| {
+            syn::Error::new(
+                proc_macro2::Span::call_site(),
+                format!("invalid `[{}]` options: {}", table, message),
+            )
+        };
+        let table = "package.metadata.defamed";
+
+        let manifest = manifest
+            .parse::<toml::Table>()
+            .map_err(|e| error(table, e.to_string()))?;
+        let mut options = match manifest
+            .get("package")
+            .and_then(|package| package.get("metadata"))
+            .and_then(|metadata| metadata.get(crate::CRATE_IDENT))
+        {
+            Some(toml::Value::Table(options)) => options.clone(),
+            Some(_) => return Err(error(table, "expected a table".to_string())),
+            None => return Ok(Self::default()),
+        };
+
+        let profiles = match options.remove("profiles") {
+            Some(toml::Value::Table(profiles)) => profiles,
+            Some(_) => return Err(error(table, "expected a table of profiles".to_string())),
+            None => toml::Table::new(),
+        };
+        let profiles = profiles
+            .iter()
+            .map(|(name, options)| {
+                let table = format!("{}.profiles.{}", table, name);
+                match options {
+                    toml::Value::Table(options) => shared_options(options)
+                        .map(|options| (name.clone(), options))
+                        .map_err(|message| error(&table, message)),
+                    _ => Err(error(&table, "expected a table".to_string())),
+                }
+            })
+            .collect::<syn::Result<_>>()?;
+
+        Ok(Self {
+            defaults: shared_options(&options).map_err(|message| error(table, message))?,
+            profiles,
+        })
+    }
+}
+
+/// Options of a table of the manifest shared by several items.
+fn shared_options(options: &toml::Table) -> Result<MacroConfig, String> {
+    let span = proc_macro2::Span::call_site();
+
+    let options = options
+        .iter()
+        .map(|(name, value)| {
+            let ident = syn::parse_str::<syn::Ident>(name)
+                .map_err(|_| format!("`{}` is not an option", name))?;
+            let value = match value {
+                toml::Value::String(s) => syn::Lit::Str(syn::LitStr::new(s, span)),
+                toml::Value::Integer(i) if *i >= 0 => {
+                    syn::Lit::Int(syn::LitInt::new(&i.to_string(), span))
+                }
+                toml::Value::Boolean(b) => syn::Lit::Bool(syn::LitBool::new(*b, span)),
+                other => {
+                    return Err(format!(
+                        "expected a string, a positive integer or a boolean for `{}`, found {}",
+                        name,
+                        other.type_str()
+                    ))
+                }
+            };
+
+            Ok(quote! {#ident = #value})
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let tokens = quote! {#(#options),*};
+
+    MacroConfig::check_shared(tokens.clone()).map_err(|e| e.to_string())?;
+    syn::parse2(tokens).map_err(|e| e.to_string())
+}
+
+/// Parsed attribute arguments.
+///
+/// The path to the item (if any) and all options are comma-separated:
+/// ```ignore
+/// #[defamed::defamed(crate::inner, macro_use)]
+/// ```
+#[derive(Clone, Default)]
+pub struct MacroConfig {
+    /// Path to the module containing the item, relative to the crate root
+    pub path: Option<syn::Path>,
+    /// Name the exported macro after the item itself, for `#[macro_use] extern crate` consumers
+    pub macro_use: bool,
+    /// Exclude the item from macro generation, used inside annotated `impl` blocks and modules
+    pub skip: bool,
+    /// Name of the helper attribute for default values, if not [crate::DEFAULT_HELPER_ATTR]
+    pub attr: Option<syn::Ident>,
+    /// How the macro arms are generated, chosen by the size of the item if not configured
+    pub strategy: Option<Strategy>,
+    /// Which named arguments the macro accepts
+    pub named: NamedArgs,
+    /// How the receiver is passed to the macros of methods, as the first positional argument if not configured
+    pub receiver: Option<Receiver>,
+    /// Syntax the arguments are matched with, named like struct fields for structs and with `=` otherwise
+    pub pattern: Option<PatternSyntax>,
+    /// Maximum number of call forms, if not [crate::expand::DEFAULT_MAX_ARMS]
+    pub max_arms: Option<usize>,
+    /// Report the number of generated macro arms at the item
+    pub report_arms: bool,
+    /// Annotate every call arm with the arguments it accepts, for reviewing the expanded macro
+    pub readable: bool,
+    /// Compile the examples in the macro documentation as doctests
+    pub doctest: bool,
+    /// Generate a test expanding every call form of the macro
+    pub self_test: bool,
+    /// Generate a test calling the function with all defaults, and one for every case of each default
+    pub test_matrix: bool,
+    /// Evaluate default values in inline `const` blocks, so only const expressions are accepted
+    pub const_defaults: bool,
+    /// Cargo feature the generated macro is gated behind, leaving the item itself available without it
+    pub feature: Option<syn::LitStr>,
+    /// Mark small functions `#[inline]` if their macro is exported, so calls from other crates can be inlined
+    pub auto_inline: bool,
+    /// Report defaults without a description
+    pub require_docs: Option<RequireDocs>,
+    /// Generate an extension trait for every method, taking its arguments collected in a struct
+    pub ext_trait: bool,
+    /// Additional names the macro can be searched for in rustdoc
+    pub aliases: Vec<syn::LitStr>,
+    /// Name of the profile in `[package.metadata.defamed.profiles]` of the manifest, whose options the item inherits
+    pub profile: Option<syn::LitStr>,
+    /// Oldest Rust version the generated code compiles with, if not the `rust-version` of the package
+    pub msrv: Option<(u32, u32)>,
+    /// Name of the crate the item is compiled in, if not `CARGO_CRATE_NAME`
+    pub krate: Option<String>,
+    /// Flags turned off with `= false`, which are not inherited from the crate or a profile
+    pub disabled: Vec<String>,
+}
+
+/// Named arguments accepted by the macro, restricted to reduce the number of arms.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NamedArgs {
+    /// Named arguments in any order
+    #[default]
+    Any,
+    /// Named arguments in declaration order only
+    Ordered,
+    /// Positional arguments only, omitting trailing defaults
+    Disabled,
+}
+
+impl NamedArgs {
+    /// All restrictions, with the options they are selected by
+    const OPTIONS: [(&'static str, Self); 2] = [
+        ("ordered_named", Self::Ordered),
+        ("no_named", Self::Disabled),
+    ];
+}
+
+/// How the receiver is passed to the macro of a method, chosen for all methods of an `impl` block.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Receiver {
+    /// The receiver is the first positional argument
+    #[default]
+    Positional,
+    /// The receiver is the first argument, passed as `self = <receiver>`
+    Named,
+}
+
+impl Receiver {
+    /// All conventions, with the names they are selected by
+    const NAMES: [(&'static str, Self); 2] =
+        [("positional", Self::Positional), ("named", Self::Named)];
+}
+
+/// How defaults without a description are reported, for `require_docs`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RequireDocs {
+    /// Every undocumented default is an error
+    Deny,
+    /// Every undocumented default is a warning
+    Warn,
+}
+
+impl RequireDocs {
+    /// All levels, with the names they are selected by
+    const NAMES: [(&'static str, Self); 2] = [("deny", Self::Deny), ("warn", Self::Warn)];
+}
+
+/// Syntaxes of the patterns matching the arguments, with the names they are selected by
+const PATTERN_NAMES: [(&str, PatternSyntax); 3] = [
+    ("expr", PatternSyntax::Expr),
+    ("tt", PatternSyntax::TokenTree),
+    ("struct_literal", PatternSyntax::StructLiteral),
+];
+
+/// Code generation strategy for the macro arms.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Strategy {
+    /// One arm for every accepted order of arguments
+    #[default]
+    Permute,
+    /// Arguments are parsed one at a time, so the number of arms grows linearly with the parameters
+    Muncher,
+    /// Arguments are sorted into declaration order before matching,
+    /// so only one arm is needed for every combination of supplied defaults
+    Canonical,
+    /// Arguments are collected in a hidden struct, which calls the function.
+    /// Only a few arms are needed regardless of the parameters, at the cost of a struct built on every call
+    ArgsStruct,
+}
+
+impl Strategy {
+    /// All strategies, with the names they are selected by
+    const NAMES: [(&'static str, Self); 4] = [
+        ("permute", Self::Permute),
+        ("muncher", Self::Muncher),
+        ("canonical", Self::Canonical),
+        ("args_struct", Self::ArgsStruct),
+    ];
+
+    /// Number of forms generated for the strategy and named arguments of an item,
+    /// without generating them.
+    ///
+    /// Saturates at [usize::MAX].
+    pub fn form_count(self, named: NamedArgs, required: usize, default: usize) -> usize {
+        match (self, named) {
+            (Self::Permute, NamedArgs::Any) => permute::permute_count(required, default),
+            (Self::Permute, NamedArgs::Ordered) => permute::ordered_count(required, default),
+            (Self::Permute, NamedArgs::Disabled) => permute::tuple_struct_count(default),
+            (Self::Canonical, _) => permute::canonical_count(default),
+            (Self::Muncher | Self::ArgsStruct, _) => 1,
+        }
+    }
+}
+
+impl MacroConfig {
+    /// Name of the helper attribute used to mark default values.
+    pub fn helper_attr(&self) -> syn::Ident {
+        self.attr.clone().unwrap_or_else(|| {
+            syn::Ident::new(crate::DEFAULT_HELPER_ATTR, proc_macro2::Span::call_site())
+        })
+    }
+
+    /// Strategy the macro arms are generated with.
+    pub fn strategy(&self) -> Strategy {
+        self.strategy.unwrap_or_default()
+    }
+
+    /// Maximum number of call forms the macro can be generated with.
+    pub fn arm_limit(&self) -> usize {
+        self.max_arms.unwrap_or(crate::expand::DEFAULT_MAX_ARMS)
+    }
+
+    /// How the receiver is passed to the macros of methods.
+    pub fn receiver(&self) -> Receiver {
+        self.receiver.unwrap_or_default()
+    }
+
+    /// Oldest Rust version the generated code compiles with,
+    /// falling back to the `rust-version` of the package being compiled.
+    pub fn msrv(&self) -> Option<(u32, u32)> {
+        self.msrv.or_else(|| {
+            std::env::var("CARGO_PKG_RUST_VERSION")
+                .ok()
+                .and_then(|version| parse_version(&version))
+        })
+    }
+
+    /// Name of the crate the item is compiled in,
+    /// falling back to the crate Cargo is compiling.
+    pub fn crate_name(&self) -> Option<String> {
+        self.krate
+            .clone()
+            .or_else(|| std::env::var("CARGO_CRATE_NAME").ok())
+    }
+
+    /// Checks if the generated code can use syntax stabilized in `version`.
+    ///
+    /// Without a minimum supported Rust version, the newest syntax is used.
+    pub fn supports(&self, version: (u32, u32)) -> bool {
+        self.msrv().is_none_or(|msrv| msrv >= version)
+    }
+
+    /// Reject an option whose generated code needs syntax stabilized in `version`.
+    pub fn require_version(
+        &self,
+        version: (u32, u32),
+        option: &str,
+        span: proc_macro2::Span,
+    ) -> syn::Result<()> {
+        match self.msrv() {
+            Some(msrv) if msrv < version => Err(syn::Error::new(
+                span,
+                format!(
+                    "`{}` requires Rust {}.{}, but the minimum supported Rust version is {}.{}",
+                    option, version.0, version.1, msrv.0, msrv.1
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check options shared by several items:
+    /// the path to the item and options that only apply to a single item are rejected.
+    fn check_shared(tokens: proc_macro2::TokenStream) -> syn::Result<()> {
+        let metas = syn::parse::Parser::parse2(
+            Punctuated::<syn::Meta, Token![,]>::parse_terminated,
+            tokens.clone(),
+        )?;
+        crate::collect_results(metas.iter().map(|meta| {
+            let mut option = Self::default();
+            option.apply_option(meta)?;
+
+            match meta.path().get_ident() {
+                _ if option.path.is_some() => Err(syn::Error::new(
+                    meta.span(),
+                    "the path to an item cannot be shared by other items",
+                )),
+                Some(ident) if ITEM_OPTIONS.iter().any(|name| ident == name) => {
+                    Err(syn::Error::new(
+                        meta.span(),
+                        format!("`{}` only applies to a single item", ident),
+                    ))
+                }
+                _ => Ok(()),
+            }
+        }))?;
+
+        // combinations of options are checked like the options of an item
+        syn::parse2::<Self>(tokens).map(|_| ())
+    }
+
+    /// Fill in the options not given to the item from the options of its profile,
+    /// followed by the options of the crate being compiled.
+    ///
+    /// Options of methods are only inherited by items with `methods`, as other items reject them.
+    pub fn with_crate_defaults(self, methods: bool) -> syn::Result<Self> {
+        self.with_options(&CrateOptions::load()?, methods)
+    }
+
+    /// Fill in the options not given to the item from the options of its profile,
+    /// followed by the options of the crate.
+    pub fn with_options(self, options: &CrateOptions, methods: bool) -> syn::Result<Self> {
+        let profile = match &self.profile {
+            Some(name) => Some(options.profiles.get(&name.value()).ok_or_else(|| {
+                syn::Error::new(
+                    name.span(),
+                    format!(
+                        "unknown profile `{}`, declare it in `[package.metadata.defamed.profiles]` of the manifest",
+                        name.value()
+                    ),
+                )
+            })?),
+            None => None,
+        };
+
+        let inherited = profile
+            .into_iter()
+            .chain(Some(&options.defaults))
+            .cloned()
+            .map(|options| match methods {
+                true => options,
+                false => Self {
+                    receiver: None,
+                    ext_trait: false,
+                    ..options
+                },
+            });
+
+        let config = inherited.fold(self, Self::inherit);
+        config.check_msrv()?;
+
+        Ok(config)
+    }
+
+    /// Reject options generating syntax newer than the minimum supported Rust version.
+    fn check_msrv(&self) -> syn::Result<()> {
+        let span = proc_macro2::Span::call_site();
+
+        crate::collect_results(
+            self.aliases
+                .iter()
+                .map(|alias| {
+                    self.require_version(crate::expand::DOC_ALIAS_MSRV, "alias", alias.span())
+                })
+                .chain(self.const_defaults.then(|| {
+                    self.require_version(crate::expand::INLINE_CONST_MSRV, "const_defaults", span)
+                })),
+        )
+        .map(|_| ())
+    }
+
+    /// Fill in the options not given to `self` from `defaults`.
+    ///
+    /// Flags turned off by `self` are not inherited.
+    fn inherit(self, defaults: Self) -> Self {
+        let disabled = self
+            .disabled
+            .iter()
+            .chain(&defaults.disabled)
+            .cloned()
+            .collect::<Vec<_>>();
+        let inherits = |flag: &str| !self.disabled.iter().any(|name| name == flag);
+
+        // restricting named arguments selects the permute strategy
+        let (strategy, named) = match self.strategy.is_none()
+            && self.named == NamedArgs::Any
+            && NamedArgs::OPTIONS.iter().all(|(name, _)| inherits(name))
+        {
+            true => (defaults.strategy, defaults.named),
+            false => (self.strategy, self.named),
+        };
+
+        Self {
+            macro_use: self.macro_use || (defaults.macro_use && inherits("macro_use")),
+            attr: self.attr.or(defaults.attr),
+            strategy,
+            named,
+            receiver: self.receiver.or(defaults.receiver),
+            pattern: self.pattern.or(defaults.pattern),
+            max_arms: self.max_arms.or(defaults.max_arms),
+            report_arms: self.report_arms || (defaults.report_arms && inherits("report_arms")),
+            readable: self.readable || (defaults.readable && inherits("readable")),
+            doctest: self.doctest || (defaults.doctest && inherits("doctest")),
+            self_test: self.self_test || (defaults.self_test && inherits("self_test")),
+            ext_trait: self.ext_trait || (defaults.ext_trait && inherits("ext_trait")),
+            const_defaults: self.const_defaults
+                || (defaults.const_defaults && inherits("const_defaults")),
+            auto_inline: self.auto_inline || (defaults.auto_inline && inherits("auto_inline")),
+            feature: self.feature.or(defaults.feature),
+            require_docs: self
+                .require_docs
+                .or(defaults.require_docs.filter(|_| inherits("require_docs"))),
+            msrv: self.msrv.or(defaults.msrv),
+            disabled,
+            ..self
+        }
+    }
+}
+
+impl Parse for MacroConfig {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut config = Self::default();
+        let metas = Punctuated::<syn::Meta, Token![,]>::parse_terminated(input)?;
+
+        // all invalid options are reported at once
+        crate::collect_results(metas.iter().map(|meta| config.apply_option(meta)))?;
+
+        // other strategies accept named arguments in any order without the extra arms
+        if config.named != NamedArgs::Any && config.strategy() != Strategy::Permute {
+            let (option, meta) = NamedArgs::OPTIONS
+                .iter()
+                .find_map(|(name, _)| {
+                    metas
+                        .iter()
+                        .find(|meta| meta.path().is_ident(name))
+                        .map(|meta| (name, meta))
+                })
+                .expect("option is set");
+
+            return Err(syn::Error::new(
+                meta.span(),
+                format!("`{}` only applies to the `permute` strategy", option),
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+impl MacroConfig {
+    /// Apply a single comma-separated option.
+    fn apply_option(&mut self, meta: &syn::Meta) -> syn::Result<()> {
+        match meta {
+            syn::Meta::Path(p) if FLAGS.iter().any(|flag| p.is_ident(flag)) => {
+                self.set_flag(p, true)?
+            }
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Bool(value),
+                        ..
+                    }),
+                ..
+            }) if FLAGS.iter().any(|flag| path.is_ident(flag)) => {
+                self.set_flag(path, value.value)?
+            }
+            syn::Meta::Path(p) => match self.path {
+                Some(_) => {
+                    return Err(syn::Error::new(
+                        p.span(),
+                        "Item path provided more than once",
+                    ))
+                }
+                None => self.path = Some(p.clone()),
+            },
+            syn::Meta::NameValue(nv) if nv.path.is_ident("attr") => {
+                self.attr = Some(str_value(nv, "attr = \"def\"")?.parse()?);
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("alias") => {
+                self.aliases
+                    .push(str_value(nv, "alias = \"name\"")?.clone());
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("feature") => {
+                self.feature = Some(str_value(nv, "feature = \"macros\"")?.clone());
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("profile") => {
+                self.profile = Some(str_value(nv, "profile = \"minimal\"")?.clone());
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("max_arms") => {
+                self.max_arms = Some(match &nv.value {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Int(i),
+                        ..
+                    }) => i.base10_parse()?,
+                    other => {
+                        return Err(syn::Error::new(
+                            other.span(),
+                            "Expected an integer literal, e.g. `max_arms = 1000`",
+                        ))
+                    }
+                });
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("strategy") => {
+                let name = str_value(nv, "strategy = \"muncher\"")?;
+
+                self.strategy = Strategy::NAMES
+                    .iter()
+                    .find(|(n, _)| name.value() == *n)
+                    .map(|(_, strategy)| Some(*strategy))
+                    .ok_or_else(|| {
+                        let expected = Strategy::NAMES
+                            .iter()
+                            .map(|(n, _)| format!("`{}`", n))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        syn::Error::new(
+                            name.span(),
+                            format!("Unknown strategy, expected one of: {}", expected),
+                        )
+                    })?;
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("require_docs") => {
+                let name = str_value(nv, "require_docs = \"warn\"")?;
+
+                self.require_docs = RequireDocs::NAMES
+                    .iter()
+                    .find(|(n, _)| name.value() == *n)
+                    .map(|(_, level)| Some(*level))
+                    .ok_or_else(|| {
+                        syn::Error::new(name.span(), "Unknown level, expected `deny` or `warn`")
+                    })?;
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("msrv") => {
+                let version = str_value(nv, "msrv = \"1.70\"")?;
+
+                self.msrv = match parse_version(&version.value()) {
+                    Some(msrv) if msrv < crate::expand::OLDEST_MSRV => {
+                        return Err(syn::Error::new(
+                            version.span(),
+                            format!(
+                                "The generated code requires at least Rust {}.{}",
+                                crate::expand::OLDEST_MSRV.0,
+                                crate::expand::OLDEST_MSRV.1
+                            ),
+                        ))
+                    }
+                    Some(msrv) => Some(msrv),
+                    None => {
+                        return Err(syn::Error::new(
+                            version.span(),
+                            "Expected a Rust version, e.g. `msrv = \"1.70\"`",
+                        ))
+                    }
+                };
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("pattern") => {
+                let name = str_value(nv, "pattern = \"tt\"")?;
+
+                self.pattern = PATTERN_NAMES
+                    .iter()
+                    .find(|(n, _)| name.value() == *n)
+                    .map(|(_, syntax)| Some(*syntax))
+                    .ok_or_else(|| {
+                        let expected = PATTERN_NAMES
+                            .iter()
+                            .map(|(n, _)| format!("`{}`", n))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        syn::Error::new(
+                            name.span(),
+                            format!("Unknown pattern syntax, expected one of: {}", expected),
+                        )
+                    })?;
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("receiver") => {
+                let name = str_value(nv, "receiver = \"named\"")?;
+
+                self.receiver = Receiver::NAMES
+                    .iter()
+                    .find(|(n, _)| name.value() == *n)
+                    .map(|(_, receiver)| Some(*receiver))
+                    .ok_or_else(|| {
+                        let expected = Receiver::NAMES
+                            .iter()
+                            .map(|(n, _)| format!("`{}`", n))
+                            .collect::<Vec<_>>()
+                            .join(", ");
+
+                        syn::Error::new(
+                            name.span(),
+                            format!("Unknown receiver convention, expected one of: {}", expected),
+                        )
+                    })?;
+            }
+            syn::Meta::NameValue(nv) if FLAGS.iter().any(|flag| nv.path.is_ident(flag)) => {
+                return Err(syn::Error::new(
+                    nv.value.span(),
+                    format!(
+                        "Expected a boolean literal, e.g. `{} = false`",
+                        nv.path.to_token_stream()
+                    ),
+                ))
+            }
+            syn::Meta::List(_) | syn::Meta::NameValue(_) => {
+                return Err(syn::Error::new(meta.span(), "Unrecognized option"))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Turn a flag on or off, remembering flags turned off so they are not inherited.
+    fn set_flag(&mut self, path: &syn::Path, value: bool) -> syn::Result<()> {
+        let name = path.to_token_stream().to_string();
+
+        match name.as_str() {
+            "macro_use" => self.macro_use = value,
+            "skip" => self.skip = value,
+            "report_arms" => self.report_arms = value,
+            "readable" => self.readable = value,
+            "doctest" => self.doctest = value,
+            "self_test" => self.self_test = value,
+            "test_matrix" => self.test_matrix = value,
+            "const_defaults" => self.const_defaults = value,
+            "auto_inline" => self.auto_inline = value,
+            "require_docs" => self.require_docs = value.then_some(RequireDocs::Deny),
+            "ext_trait" => self.ext_trait = value,
+            _ => {
+                let (_, named) = NamedArgs::OPTIONS
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .expect("option is known");
+
+                match value {
+                    true if self.named != NamedArgs::Any && self.named != *named => {
+                        return Err(syn::Error::new(
+                            path.span(),
+                            "`ordered_named` and `no_named` cannot be combined",
+                        ));
+                    }
+                    true => self.named = *named,
+                    false if self.named == *named => self.named = NamedArgs::Any,
+                    false => (),
+                }
+            }
+        }
+        if !value {
+            self.disabled.push(name);
+        }
+
+        Ok(())
+    }
+}
+
+/// Major and minor version of a Rust version, such as `1.70` or `1.70.0`.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let parts = version
+        .split('.')
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    match parts.as_slice() {
+        [major, minor] | [major, minor, _] => Some((*major, *minor)),
+        _ => None,
+    }
+}
+
+/// The string literal value of a name-value option.
+fn str_value<'a>(nv: &'a syn::MetaNameValue, example: &str) -> syn::Result<&'a syn::LitStr> {
+    match &nv.value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(s),
+            ..
+        }) => Ok(s),
+        other => Err(syn::Error::new(
+            other.span(),
+            format!("Expected a string literal, e.g. `{}`", example),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quote::quote;
+
+    #[test]
+    fn test_form_count() {
+        assert_eq!(Strategy::Permute.form_count(NamedArgs::Any, 1, 2), 13);
+        assert_eq!(Strategy::Permute.form_count(NamedArgs::Ordered, 1, 2), 11);
+        assert_eq!(Strategy::Permute.form_count(NamedArgs::Disabled, 1, 2), 3);
+        assert_eq!(Strategy::Canonical.form_count(NamedArgs::Any, 1, 2), 4);
+        assert_eq!(Strategy::Muncher.form_count(NamedArgs::Any, 30, 30), 1);
+        assert_eq!(
+            Strategy::Permute.form_count(NamedArgs::Any, 30, 30),
+            usize::MAX
+        );
+    }
+
+    #[test]
+    fn test_parse_path_and_options() {
+        let config: MacroConfig = syn::parse2(quote! {}).unwrap();
+        assert!(config.path.is_none());
+        assert!(!config.macro_use);
+
+        let config: MacroConfig = syn::parse2(quote! {crate}).unwrap();
+        assert!(config.path.unwrap().is_ident("crate"));
+
+        let config: MacroConfig = syn::parse2(quote! {inner::nested, macro_use}).unwrap();
+        assert_eq!(config.path.unwrap().segments.len(), 2);
+        assert!(config.macro_use);
+
+        let config: MacroConfig = syn::parse2(quote! {ordered_named}).unwrap();
+        assert_eq!(config.named, NamedArgs::Ordered);
+        let config: MacroConfig = syn::parse2(quote! {no_named}).unwrap();
+        assert_eq!(config.named, NamedArgs::Disabled);
+        assert!(syn::parse2::<MacroConfig>(quote! {ordered_named, strategy = "muncher"}).is_err());
+        assert!(syn::parse2::<MacroConfig>(quote! {ordered_named, no_named}).is_err());
+
+        let config: MacroConfig = syn::parse2(quote! {report_arms}).unwrap();
+        assert!(config.report_arms);
+
+        let config: MacroConfig = syn::parse2(quote! {readable}).unwrap();
+        assert!(config.readable);
+
+        let config: MacroConfig = syn::parse2(quote! {crate, doctest}).unwrap();
+        assert!(config.doctest);
+
+        let config: MacroConfig = syn::parse2(quote! {self_test}).unwrap();
+        assert!(config.self_test);
+
+        let config: MacroConfig = syn::parse2(quote! {test_matrix}).unwrap();
+        assert!(config.test_matrix);
+
+        let config: MacroConfig = syn::parse2(quote! {const_defaults}).unwrap();
+        assert!(config.const_defaults);
+
+        let config: MacroConfig = syn::parse2(quote! {crate, auto_inline}).unwrap();
+        assert!(config.auto_inline);
+
+        let config: MacroConfig = syn::parse2(quote! {feature = "macros"}).unwrap();
+        assert_eq!(config.feature.unwrap().value(), "macros");
+        assert!(syn::parse2::<MacroConfig>(quote! {feature = macros}).is_err());
+
+        let config: MacroConfig = syn::parse2(quote! {require_docs}).unwrap();
+        assert_eq!(config.require_docs, Some(RequireDocs::Deny));
+        let config: MacroConfig = syn::parse2(quote! {require_docs = "warn"}).unwrap();
+        assert_eq!(config.require_docs, Some(RequireDocs::Warn));
+        assert!(syn::parse2::<MacroConfig>(quote! {require_docs = "allow"}).is_err());
+
+        let config: MacroConfig = syn::parse2(quote! {ext_trait}).unwrap();
+        assert!(config.ext_trait);
+
+        // flags take a boolean, and remember being turned off
+        let config: MacroConfig = syn::parse2(quote! {doctest = true, no_named = true}).unwrap();
+        assert!(config.doctest);
+        assert_eq!(config.named, NamedArgs::Disabled);
+        let config: MacroConfig = syn::parse2(quote! {doctest = false}).unwrap();
+        assert!(!config.doctest);
+        assert_eq!(config.disabled, ["doctest"]);
+        assert!(syn::parse2::<MacroConfig>(quote! {doctest = 1}).is_err());
+
+        let config: MacroConfig = syn::parse2(quote! {alias = "a", alias = "b"}).unwrap();
+        assert_eq!(config.aliases.len(), 2);
+        assert!(syn::parse2::<MacroConfig>(quote! {alias = b}).is_err());
+
+        let config: MacroConfig = syn::parse2(quote! {max_arms = 500}).unwrap();
+        assert_eq!(config.arm_limit(), 500);
+        assert_eq!(
+            MacroConfig::default().arm_limit(),
+            crate::expand::DEFAULT_MAX_ARMS
+        );
+        assert!(syn::parse2::<MacroConfig>(quote! {max_arms = "500"}).is_err());
+
+        let config: MacroConfig = syn::parse2(quote! {skip}).unwrap();
+        assert!(config.path.is_none());
+        assert!(config.skip);
+
+        let config: MacroConfig = syn::parse2(quote! {crate, attr = "opt"}).unwrap();
+        assert_eq!(config.helper_attr(), "opt");
+        assert_eq!(
+            MacroConfig::default().helper_attr(),
+            crate::DEFAULT_HELPER_ATTR
+        );
+
+        let config: MacroConfig = syn::parse2(quote! {strategy = "muncher"}).unwrap();
+        assert_eq!(config.strategy, Some(Strategy::Muncher));
+        let config: MacroConfig = syn::parse2(quote! {strategy = "canonical"}).unwrap();
+        assert_eq!(config.strategy, Some(Strategy::Canonical));
+        let config: MacroConfig = syn::parse2(quote! {strategy = "args_struct"}).unwrap();
+        assert_eq!(config.strategy, Some(Strategy::ArgsStruct));
+        let config: MacroConfig = syn::parse2(quote! {strategy = "permute"}).unwrap();
+        assert_eq!(config.strategy, Some(Strategy::Permute));
+        assert_eq!(MacroConfig::default().strategy, None);
+        assert_eq!(MacroConfig::default().strategy(), Strategy::Permute);
+
+        let config: MacroConfig = syn::parse2(quote! {pattern = "tt"}).unwrap();
+        assert_eq!(config.pattern, Some(PatternSyntax::TokenTree));
+        let config: MacroConfig = syn::parse2(quote! {pattern = "struct_literal"}).unwrap();
+        assert_eq!(config.pattern, Some(PatternSyntax::StructLiteral));
+        assert!(syn::parse2::<MacroConfig>(quote! {pattern = "ident"}).is_err());
+
+        let config: MacroConfig = syn::parse2(quote! {receiver = "named"}).unwrap();
+        assert_eq!(config.receiver(), Receiver::Named);
+        let config: MacroConfig = syn::parse2(quote! {receiver = "positional"}).unwrap();
+        assert_eq!(config.receiver(), Receiver::Positional);
+        assert_eq!(MacroConfig::default().receiver(), Receiver::Positional);
+        assert!(syn::parse2::<MacroConfig>(quote! {receiver = "first"}).is_err());
+
+        assert!(syn::parse2::<MacroConfig>(quote! {strategy = "unknown"}).is_err());
+        assert!(syn::parse2::<MacroConfig>(quote! {crate, inner}).is_err());
+        assert!(syn::parse2::<MacroConfig>(quote! {attr = opt}).is_err());
+        assert!(syn::parse2::<MacroConfig>(quote! {attr = "not an ident"}).is_err());
+
+        // every invalid option is reported
+        let err = syn::parse2::<MacroConfig>(quote! {unknown(1), crate, inner, attr = 1}).err();
+        assert_eq!(err.map(|e| e.into_iter().count()), Some(3));
+        assert!(syn::parse2::<MacroConfig>(quote! {crate, unknown = 1}).is_err());
+    }
+
+    #[test]
+    fn test_msrv() {
+        let config: MacroConfig = syn::parse2(quote! {msrv = "1.70"}).unwrap();
+        assert_eq!(config.msrv, Some((1, 70)));
+        assert!(config.supports((1, 70)));
+        assert!(!config.supports((1, 79)));
+
+        let config: MacroConfig = syn::parse2(quote! {msrv = "1.56.1"}).unwrap();
+        assert_eq!(config.msrv, Some((1, 56)));
+
+        assert!(syn::parse2::<MacroConfig>(quote! {msrv = "1"}).is_err());
+        assert!(syn::parse2::<MacroConfig>(quote! {msrv = "1.x"}).is_err());
+        assert!(syn::parse2::<MacroConfig>(quote! {msrv = 1.70}).is_err());
+        // the generated code matches optional arguments with `$(...)?`
+        assert!(syn::parse2::<MacroConfig>(quote! {msrv = "1.31"}).is_err());
+
+        let config: MacroConfig =
+            syn::parse2(quote! {msrv = "1.40", const_defaults, alias = "a"}).unwrap();
+        let err = config.check_msrv().unwrap_err();
+        assert_eq!(err.into_iter().count(), 2);
+        let config: MacroConfig = syn::parse2(quote! {msrv = "1.79", const_defaults}).unwrap();
+        assert!(config.check_msrv().is_ok());
+    }
+
+    #[test]
+    fn test_crate_defaults() {
+        let defaults: MacroConfig =
+            syn::parse2(quote! {strategy = "muncher", attr = "opt", receiver = "named"}).unwrap();
+
+        let config = MacroConfig::default().inherit(defaults.clone());
+        assert_eq!(config.strategy(), Strategy::Muncher);
+        assert_eq!(config.helper_attr(), "opt");
+        assert_eq!(config.receiver(), Receiver::Named);
+
+        // options of the item take precedence
+        let config: MacroConfig =
+            syn::parse2(quote! {crate, attr = "arg", receiver = "positional"}).unwrap();
+        let config = config.inherit(defaults.clone());
+        assert!(config.path.as_ref().unwrap().is_ident("crate"));
+        assert_eq!(config.helper_attr(), "arg");
+        assert_eq!(config.receiver(), Receiver::Positional);
+
+        // restricting named arguments keeps the permute strategy
+        let config: MacroConfig = syn::parse2(quote! {no_named}).unwrap();
+        let config = config.inherit(defaults);
+        assert_eq!(config.strategy(), Strategy::Permute);
+        assert_eq!(config.named, NamedArgs::Disabled);
+
+        // flags turned off by the item are not inherited
+        let defaults: MacroConfig = syn::parse2(quote! {doctest, no_named, require_docs}).unwrap();
+        let config: MacroConfig =
+            syn::parse2(quote! {doctest = false, no_named = false, require_docs = false}).unwrap();
+        let config = config.inherit(defaults.clone());
+        assert!(!config.doctest);
+        assert_eq!(config.named, NamedArgs::Any);
+        assert_eq!(config.require_docs, None);
+        let config = MacroConfig::default().inherit(defaults);
+        assert!(config.doctest);
+        assert_eq!(config.named, NamedArgs::Disabled);
+        assert_eq!(config.require_docs, Some(RequireDocs::Deny));
+    }
+
+    #[test]
+    fn test_manifest_options() {
+        let options = CrateOptions::from_manifest(
+            r#"
+            [package]
+            name = "crate"
+
+            [package.metadata.defamed]
+            strategy = "muncher"
+            attr = "opt"
+            max_arms = 10
+            doctest = true
+            "#,
+        )
+        .unwrap();
+        let config = MacroConfig::default()
+            .with_options(&options, false)
+            .unwrap();
+        assert_eq!(config.strategy(), Strategy::Muncher);
+        assert_eq!(config.helper_attr(), "opt");
+        assert_eq!(config.arm_limit(), 10);
+        assert!(config.doctest);
+
+        let config: MacroConfig = syn::parse2(quote! {doctest = false}).unwrap();
+        assert!(!config.with_options(&options, false).unwrap().doctest);
+
+        // packages without options inherit nothing
+        let options = CrateOptions::from_manifest("[package]\nname = \"crate\"").unwrap();
+        let config = MacroConfig::default()
+            .with_options(&options, false)
+            .unwrap();
+        assert_eq!(config.strategy, None);
+
+        for invalid in [
+            "strategy = \"unknown\"",
+            "skip = true",
+            "alias = \"a\"",
+            "unknown = 1",
+            "max_arms = -1",
+            "max_arms = [1]",
+            "doctest = \"yes\"",
+        ] {
+            let manifest = format!("[package.metadata.defamed]\n{}", invalid);
+            let err = CrateOptions::from_manifest(&manifest).err().unwrap();
+            assert!(err.to_string().contains("[package.metadata.defamed]"));
+        }
+        assert!(CrateOptions::from_manifest("[package.metadata]\ndefamed = 1").is_err());
+    }
+
+    #[test]
+    fn test_profiles() {
+        let options = CrateOptions::from_manifest(
+            r#"
+            [package.metadata.defamed]
+            attr = "opt"
+            doctest = true
+
+            [package.metadata.defamed.profiles.compact]
+            strategy = "muncher"
+            doctest = false
+            "#,
+        )
+        .unwrap();
+
+        // options of the profile take precedence over those of the crate
+        let config: MacroConfig = syn::parse2(quote! {profile = "compact"}).unwrap();
+        let config = config.with_options(&options, false).unwrap();
+        assert_eq!(config.strategy(), Strategy::Muncher);
+        assert_eq!(config.helper_attr(), "opt");
+        assert!(!config.doctest);
+
+        let config: MacroConfig = syn::parse2(quote! {profile = "compact", attr = "arg"}).unwrap();
+        let config = config.with_options(&options, false).unwrap();
+        assert_eq!(config.helper_attr(), "arg");
+
+        let config: MacroConfig = syn::parse2(quote! {profile = "unknown"}).unwrap();
+        let err = config.with_options(&options, false).err().unwrap();
+        assert!(err.to_string().contains("unknown profile `unknown`"));
+
+        for invalid in [
+            "profiles = 1",
+            "profiles.compact = 1",
+            "profiles.compact.profile = \"other\"",
+            "profiles.compact.skip = true",
+        ] {
+            let manifest = format!("[package.metadata.defamed]\n{}", invalid);
+            assert!(CrateOptions::from_manifest(&manifest).is_err());
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Generated code written to files, by setting `DEFAMED_DEBUG_DIR` for the build.
+//!
+//! The code generated for every annotated item is written to a file of its own in the directory,
+//! so it can be inspected without expanding the whole crate.
+
+use std::{fmt::Write, path::PathBuf};
+
+use proc_macro2 as pm2;
+
+/// Environment variable with the directory the generated code is written to
+const DEBUG_DIR_VAR: &str = "DEFAMED_DEBUG_DIR";
+
+/// Write the code generated for the macro to `{file_stem}.rs`, if enabled.
+///
+/// Failures are reported on stderr, without failing the build.
+pub fn write(file_stem: &str, macro_ident: &syn::Ident, generated: &pm2::TokenStream) {
+    let dir = match std::env::var_os(DEBUG_DIR_VAR).filter(|var| !var.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => return,
+    };
+    let file = dir.join(format!("{}.rs", file_stem));
+
+    let res = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&file, render(generated)));
+    if let Err(e) = res {
+        eprintln!(
+            "defamed: failed to write the expansion of `{}!` to {}: {}",
+            macro_ident,
+            file.display(),
+            e
+        );
+    }
+}
+
+/// Render tokens with a line for every statement, attribute and macro arm,
+/// and an indented block for every brace.
+///
+/// Token streams have no formatting of their own, and `rustfmt` leaves macro bodies untouched.
+pub fn render(tokens: &pm2::TokenStream) -> String {
+    let mut out = String::new();
+    render_block(tokens.clone(), 0, &mut out);
+
+    out.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+/// Render the tokens of a block at `depth`, starting on a new line.
+fn render_block(tokens: pm2::TokenStream, depth: usize, out: &mut String) {
+    let indent = "    ".repeat(depth);
+    let _ = write!(out, "\n{}", indent);
+
+    let tokens = tokens.into_iter().collect::<Vec<_>>();
+    for (idx, token) in tokens.iter().enumerate() {
+        let prev = |n: usize| idx.checked_sub(n).map(|i| &tokens[i]);
+
+        match token {
+            pm2::TokenTree::Group(group) => {
+                // the arms of `macro_rules! name ( ... )` are a block of their own
+                let is_macro_body = matches!(prev(2), Some(pm2::TokenTree::Punct(p)) if p.as_char() == '!')
+                    && matches!(prev(3), Some(pm2::TokenTree::Ident(i)) if i == "macro_rules");
+                let is_attr =
+                    matches!(prev(1), Some(pm2::TokenTree::Punct(p)) if p.as_char() == '#');
+
+                match (group.delimiter(), is_macro_body) {
+                    (pm2::Delimiter::Brace, _) | (_, true) => {
+                        let (open, close) = match group.delimiter() {
+                            pm2::Delimiter::Brace => ("{", "}"),
+                            pm2::Delimiter::Parenthesis => ("(", ")"),
+                            pm2::Delimiter::Bracket => ("[", "]"),
+                            pm2::Delimiter::None => ("", ""),
+                        };
+
+                        out.push_str(open);
+                        render_block(group.stream(), depth + 1, out);
+                        let _ = write!(out, "\n{}{} ", indent, close);
+
+                        // a block ends its item or statement, unless it is continued
+                        let continued = match tokens.get(idx + 1) {
+                            Some(pm2::TokenTree::Punct(p)) => p.as_char() != '#',
+                            Some(pm2::TokenTree::Ident(i)) => i == "else",
+                            Some(_) => false,
+                            None => true,
+                        };
+                        if !continued {
+                            let _ = write!(out, "\n{}", indent);
+                        }
+                    }
+                    _ => render_inline(group, out),
+                }
+
+                if is_attr {
+                    let _ = write!(out, "\n{}", indent);
+                }
+            }
+            pm2::TokenTree::Punct(punct) if punct.as_char() == ';' => {
+                let _ = write!(out, ";\n{}", indent);
+            }
+            // the next arm or statement of a block
+            pm2::TokenTree::Punct(punct)
+                if punct.as_char() == ','
+                    && matches!(prev(1), Some(pm2::TokenTree::Group(g)) if g.delimiter() == pm2::Delimiter::Brace) =>
+            {
+                let _ = write!(out, ",\n{}", indent);
+            }
+            other => render_token(other, out),
+        }
+    }
+}
+
+/// Render a group on the current line.
+///
+/// The compiler wraps long groups on its own, so they are rendered token by token.
+pub fn render_inline(group: &pm2::Group, out: &mut String) {
+    let (open, close) = match group.delimiter() {
+        pm2::Delimiter::Brace => ("{ ", "}"),
+        pm2::Delimiter::Parenthesis => ("(", ")"),
+        pm2::Delimiter::Bracket => ("[", "]"),
+        pm2::Delimiter::None => ("", ""),
+    };
+
+    out.push_str(open);
+    for token in group.stream() {
+        match &token {
+            pm2::TokenTree::Group(inner) => render_inline(inner, out),
+            other => render_token(other, out),
+        }
+    }
+
+    if out.ends_with(' ') && group.delimiter() != pm2::Delimiter::Brace {
+        out.pop();
+    }
+    out.push_str(close);
+    out.push(' ');
+}
+
+/// Render a token other than a group, followed by a space if it is not joined to the next one.
+fn render_token(token: &pm2::TokenTree, out: &mut String) {
+    match token {
+        // multi-character operators such as `=>` are split into joint punctuation,
+        // and metavariables are written without a space after `$`
+        pm2::TokenTree::Punct(punct)
+            if punct.spacing() == pm2::Spacing::Joint || punct.as_char() == '$' =>
+        {
+            out.push(punct.as_char());
+        }
+        other => {
+            let _ = write!(out, "{} ", other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use quote::quote;
+
+    #[test]
+    fn test_render() {
+        let tokens = quote! {
+            #[doc(hidden)]
+            macro_rules! item (
+                (@call $a:expr) => { item($a) };
+                () => { item(1) }
+            );
+            pub use item as other;
+        };
+
+        assert_eq!(
+            render(&tokens),
+            "# [doc (hidden)]
+macro_rules ! item (
+    (@ call $a : expr) => {
+        item ($a)
+    } ;
+    () => {
+        item (1)
+    }
+) ;
+pub use item as other ;
+"
+        );
+    }
+}
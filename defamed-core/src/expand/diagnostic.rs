@@ -0,0 +1,199 @@
+//! Warnings and errors reported by the macro.
+//!
+//! With the `proc_macro_diagnostic` feature, diagnostics are emitted through the unstable
+//! [`proc_macro::Diagnostic`] API, which supports warnings and notes attached to related spans.
+//! Otherwise, warnings are emitted as uses of deprecated items and errors as `compile_error!`,
+//! with any notes folded into the message or reported as separate errors.
+
+use proc_macro2 as pm2;
+use quote::quote;
+
+/// Severity of a diagnostic
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A diagnostic with a primary span and any number of children on related spans.
+pub struct Diagnostic {
+    level: Level,
+    /// Name of the deprecated item used to emit warnings on stable
+    name: &'static str,
+    span: pm2::Span,
+    message: String,
+    children: Vec<(Level, pm2::Span, String)>,
+    /// Error this diagnostic was converted from, which keeps the full spans of its tokens on stable
+    source: Option<syn::Error>,
+}
+
+impl Diagnostic {
+    /// Create a new error.
+    pub fn error<M: Into<String>>(span: pm2::Span, message: M) -> Self {
+        Self {
+            level: Level::Error,
+            name: "",
+            span,
+            message: message.into(),
+            children: vec![],
+            source: None,
+        }
+    }
+
+    /// Create a new warning.
+    ///
+    /// `name` identifies the warning on stable, where it shows up as a deprecated item.
+    pub fn warning<M: Into<String>>(name: &'static str, span: pm2::Span, message: M) -> Self {
+        Self {
+            level: Level::Warning,
+            name,
+            span,
+            message: message.into(),
+            children: vec![],
+            source: None,
+        }
+    }
+
+    /// Create a new note, for information that does not need to be acted on.
+    ///
+    /// `name` identifies the note on stable, where it shows up as a deprecated item.
+    pub fn note<M: Into<String>>(name: &'static str, span: pm2::Span, message: M) -> Self {
+        Self {
+            level: Level::Note,
+            ..Self::warning(name, span, message)
+        }
+    }
+
+    /// Attach a note to a related span.
+    pub fn span_note<M: Into<String>>(mut self, span: pm2::Span, note: M) -> Self {
+        self.children.push((Level::Note, span, note.into()));
+        self
+    }
+
+    /// Emit the diagnostic.
+    ///
+    /// Returns the tokens that need to be added to the macro output for the diagnostic
+    /// to be reported, which are empty if it was emitted directly to the compiler.
+    pub fn emit(self) -> pm2::TokenStream {
+        #[cfg(feature = "proc_macro_diagnostic")]
+        if proc_macro::is_available() {
+            if let Some(source) = self.source {
+                let mut errors = source.into_iter();
+                let first = errors
+                    .next()
+                    .expect("syn::Error always contains at least one error");
+
+                let children = errors
+                    .map(|err| (Level::Error, err.span(), err.to_string()))
+                    .chain(self.children)
+                    .collect();
+
+                return Self {
+                    span: first.span(),
+                    message: first.to_string(),
+                    children,
+                    source: None,
+                    ..self
+                }
+                .emit();
+            }
+
+            let level = |level| match level {
+                Level::Error => proc_macro::Level::Error,
+                Level::Warning => proc_macro::Level::Warning,
+                Level::Note => proc_macro::Level::Note,
+            };
+
+            self.children
+                .into_iter()
+                .fold(
+                    proc_macro::Diagnostic::spanned(
+                        self.span.unwrap(),
+                        level(self.level),
+                        self.message,
+                    ),
+                    |diag, (child, span, message)| match child {
+                        Level::Error => diag.span_error(span.unwrap(), message),
+                        Level::Warning => diag.span_warning(span.unwrap(), message),
+                        Level::Note => diag.span_note(span.unwrap(), message),
+                    },
+                )
+                .emit();
+
+            return pm2::TokenStream::new();
+        }
+
+        self.fallback()
+    }
+
+    /// Tokens reporting the diagnostic on a stable compiler.
+    fn fallback(self) -> pm2::TokenStream {
+        match self.level {
+            Level::Error => {
+                let mut err = self
+                    .source
+                    .unwrap_or_else(|| syn::Error::new(self.span, self.message));
+                for (_, span, message) in self.children {
+                    err.combine(syn::Error::new(span, message));
+                }
+
+                err.to_compile_error()
+            }
+            Level::Warning | Level::Note => {
+                let message =
+                    self.children
+                        .into_iter()
+                        .fold(self.message, |msg, (child, _, message)| match child {
+                            Level::Note => format!("{}\nnote: {}", msg, message),
+                            _ => format!("{}\n{}", msg, message),
+                        });
+
+                let warning = proc_macro_warning::FormattedWarning::new_deprecated(
+                    self.name, message, self.span,
+                );
+
+                // scoped, as the same warning may be emitted more than once in a module
+                quote! {
+                    const _: () = {
+                        #warning
+                    };
+                }
+            }
+        }
+    }
+}
+
+/// Every error combined in a [`syn::Error`] becomes part of the same diagnostic.
+impl From<syn::Error> for Diagnostic {
+    fn from(value: syn::Error) -> Self {
+        Self {
+            source: Some(value),
+            ..Self::error(pm2::Span::call_site(), "")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback() {
+        let span = pm2::Span::call_site();
+
+        let warning = Diagnostic::warning("SomeWarning", span, "first")
+            .span_note(span, "second")
+            .emit()
+            .to_string();
+        assert!(warning.contains("SomeWarning"));
+        assert!(warning.contains("first"));
+        assert!(warning.contains("second"));
+
+        let error = Diagnostic::error(span, "first")
+            .span_note(span, "second")
+            .emit()
+            .to_string();
+        assert_eq!(error.matches("compile_error").count(), 2);
+    }
+}
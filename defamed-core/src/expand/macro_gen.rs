@@ -0,0 +1,2661 @@
+//! Function macro generators
+
+use std::{
+    collections::HashSet,
+    fmt::{Debug, Display},
+};
+
+use proc_macro2::{self as pm2, Span};
+use quote::{quote, ToTokens};
+use syn::{ext::IdentExt, punctuated::Punctuated, spanned::Spanned, token::Comma, Visibility};
+
+use super::{
+    config::{MacroConfig, NamedArgs, Receiver, Strategy},
+    debug_dir,
+    diagnostic::Diagnostic,
+    signatures::{self, Signature},
+    timings::Timings,
+};
+use crate::{
+    backend::{Backend, MacroArms},
+    permute::{self, params::FunctionParam, PermutedItem},
+    traits::{DocInfo, PatternSyntax, ToDocInfo, ToMacroPattern},
+};
+
+#[derive(Clone, Debug)]
+pub enum MacroType {
+    Function,
+    /// Method with a receiver, implemented on the type with the given identifier,
+    /// along with the type as written in the `impl` block and how the receiver is passed
+    Method(syn::Ident, Box<syn::Type>, Receiver),
+    /// Associated function without a receiver, called through the last segment of the implementing type,
+    /// with its generic arguments as a turbofish: `Type::<Args>::function`
+    Associated(Box<syn::PathSegment>),
+    /// Struct with named fields
+    Struct,
+    /// Tuple struct with unnamed fields
+    StructTuple,
+    // Enum,
+}
+
+/// Converts `self` to doc item disambiguation prefix
+impl Display for MacroType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MacroType::Function => write!(f, "fn@"),
+            MacroType::Method(self_ident, ..) => write!(f, "method@{}::", self_ident),
+            MacroType::Associated(self_ty) => write!(f, "method@{}::", self_ty.ident),
+            MacroType::Struct => write!(f, "struct@"),
+            MacroType::StructTuple => write!(f, "struct@"),
+        }
+    }
+}
+
+impl MacroType {
+    /// Name of the macro generated for `item_ident`.
+    ///
+    /// Methods are not in the module namespace, so their macro is named after the type as well.
+    pub fn macro_ident(&self, item_ident: &syn::Ident) -> syn::Ident {
+        match self {
+            MacroType::Method(self_ident, ..) => syn::Ident::new(
+                &format!("{}_{}", to_snake_case(&self_ident.to_string()), item_ident),
+                Span::call_site(),
+            ),
+            MacroType::Associated(self_ty) => syn::Ident::new(
+                &format!(
+                    "{}_{}",
+                    to_snake_case(&self_ty.ident.to_string()),
+                    item_ident
+                ),
+                Span::call_site(),
+            ),
+            _ => item_ident.clone(),
+        }
+    }
+
+    /// Kind of the item, as written in machine-readable signatures
+    fn kind(&self) -> &'static str {
+        match self {
+            MacroType::Function => "function",
+            MacroType::Method(..) => "method",
+            MacroType::Associated(..) => "associated_function",
+            MacroType::Struct => "struct",
+            MacroType::StructTuple => "tuple_struct",
+        }
+    }
+
+    /// Number of arguments before the parameters, which can only be passed positionally
+    fn leading_args(&self) -> usize {
+        match self {
+            MacroType::Method(..) => 1,
+            _ => 0,
+        }
+    }
+
+    /// The receiver argument of a method macro, with `value` as the receiver
+    fn receiver_arg(&self, value: &str) -> Option<String> {
+        match self {
+            MacroType::Method(_, _, Receiver::Positional) => Some(value.to_string()),
+            MacroType::Method(_, _, Receiver::Named) => Some(format!("self = {}", value)),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) use crate::permute::params::RECEIVER_METAVAR;
+
+/// Syntax the arguments of the macro are matched with, named like struct fields for structs if not configured.
+fn pattern_syntax(config: &MacroConfig, output: &MacroType) -> PatternSyntax {
+    config.pattern.unwrap_or(match output {
+        MacroType::Struct => PatternSyntax::StructLiteral,
+        _ => PatternSyntax::Expr,
+    })
+}
+
+/// Marker for internal macro arms that validate arguments one at a time
+const MUNCH_MARKER: &str = "__defamed_munch";
+
+/// Marker for internal macro arms that call the underlying item, once arguments are validated
+const CALL_MARKER: &str = "__defamed_call";
+
+/// Marker for internal macro arms that expand a collected argument, or its default value
+const ARG_MARKER: &str = "__defamed_arg";
+
+/// Generate a macro with all permutations of positional, named and default parameters.
+/// The macro inherits all doc comments from the original function.
+/// Any `#[cfg]` attributes on the original item are applied to all generated items.
+///
+/// This macro generates code that calls the actual function,
+/// while reorderng and substituting parameters as needed.
+///
+/// The permute strategies buffer every permutation to sort it and prune the shadowed ones,
+/// then write each arm into the body of the macro once it is rendered.
+///
+/// Calls of `async` items awaiting a default value are wrapped in an `async` block,
+/// where the default is evaluated by the returned future.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_func_macro<T, I>(
+    vis: Visibility,
+    item_attrs: &[syn::Attribute],
+    // package_name: &str,
+    config: &MacroConfig,
+    item_ident: syn::Ident,
+    params: I,
+    output: MacroType,
+    asyncness: Option<syn::Token![async]>,
+    mut timings: Timings,
+) -> pm2::TokenStream
+where
+    T: Clone + PartialEq + Debug,
+    PermutedItem<T>: ToMacroPattern + ToDocInfo,
+    I: IntoIterator<Item = Vec<PermutedItem<T>>>,
+{
+    let mut params = params.into_iter();
+
+    // first pattern contains the correct order of parameteres to call
+    let first_ref = match params.next() {
+        Some(f) => f,
+        None => {
+            return syn::Error::new(item_ident.span(), "at least one match pattern expected")
+                .to_compile_error()
+        }
+    };
+
+    if config.receiver() != Receiver::Positional && !matches!(output, MacroType::Method(..)) {
+        return syn::Error::new(
+            item_ident.span(),
+            "`receiver` only applies to methods, in annotated `impl` blocks and traits",
+        )
+        .to_compile_error();
+    }
+
+    if config.ext_trait && !matches!(output, MacroType::Method(..)) {
+        return syn::Error::new(
+            item_ident.span(),
+            "`ext_trait` only applies to methods, in annotated `impl` blocks",
+        )
+        .to_compile_error();
+    }
+
+    let item_path = config.path.as_ref();
+
+    let macro_ident = output.macro_ident(&item_ident);
+    let hash = expansion_hash(config, &macro_ident);
+
+    // defaults awaited outside of an async context would not compile
+    let awaited = asyncness.is_some()
+        && first_ref
+            .iter()
+            .any(|p| p.to_default_value().is_some_and(awaits));
+
+    let func_path_root = path_root(item_path);
+
+    // let package_ident = syn::Ident::new(&package_name.replace("-", "_"), Span::call_site());
+
+    // macros 2.0 and `#[macro_use]` macros are referred to by their own name,
+    // everything else through its re-export
+    let macro_self_path = match cfg!(feature = "decl_macro") || config.macro_use {
+        true => quote! {#macro_ident},
+        false => quote! {#func_path_root #macro_ident},
+    };
+
+    // rendered once, and reused for names, errors and docs
+    let param_infos = first_ref
+        .iter()
+        .map(|p| p.to_doc_info())
+        .collect::<Vec<_>>();
+
+    // tuple structs do not have named parameters
+    let param_names = match &output {
+        MacroType::StructTuple => vec![None; first_ref.len()],
+        _ => param_infos
+            .iter()
+            .map(|info| syn::parse_str::<syn::Ident>(&info.ident).ok())
+            .collect(),
+    };
+    Signature {
+        kind: output.kind(),
+        item_ident: &item_ident,
+        macro_ident: &macro_ident,
+        path: item_path,
+        params: &param_infos,
+    }
+    .write();
+
+    let accepted_forms = describe_accepted_forms(
+        &macro_ident,
+        &output,
+        config.named,
+        pattern_syntax(config, &output),
+        &param_infos,
+    );
+
+    // unknown names are resolved in the hidden module of parameters, for completions
+    let params_mod_ident = params_mod_ident(&macro_ident, &hash);
+    let params_mod_path = quote! {#func_path_root #params_mod_ident};
+
+    // arms are separated by commas in macros 2.0, and by semicolons in `macro_rules!`,
+    // and their internal identifiers are suffixed as they are written
+    let mut macro_matches = MacroArms::new(match cfg!(feature = "decl_macro") {
+        true => quote! {,},
+        false => quote! {;},
+    })
+    .with_rewrite({
+        let hash = hash.clone();
+        move |arm| suffix_internal_idents(arm, &hash)
+    });
+
+    // the module of parameters is named with a hash, so its list is reached through the macro instead
+    macro_matches.push(quote! {(@params) => { #params_mod_path::PARAMS }});
+
+    let target = ArmTarget {
+        config,
+        output: &output,
+        item_ident: &item_ident,
+        macro_ident: &macro_ident,
+        macro_self_path: &macro_self_path,
+        func_path_root: &func_path_root,
+        param_names: &param_names,
+        awaited,
+        accepted_forms: &accepted_forms,
+    };
+    let mut forms = std::iter::once(first_ref.clone()).chain(params);
+
+    // arguments of the invocations of the self-test, one for every call arm generated per form
+    let (arms, self_test_calls, collect) = match config.strategy() {
+        Strategy::Permute | Strategy::Canonical => {
+            let mut backend = PermuteBackend {
+                target: &target,
+                canonical: config.strategy() == Strategy::Canonical,
+                timings: &mut timings,
+                self_test_calls: vec![],
+                collect: None,
+            };
+            let arms = backend.emit_arms(&mut forms, &mut macro_matches);
+
+            (arms, backend.self_test_calls, backend.collect)
+        }
+        Strategy::Muncher => {
+            let mut backend = MuncherBackend {
+                target: &target,
+                collect: None,
+            };
+            let arms = backend.emit_arms(&mut forms, &mut macro_matches);
+
+            (
+                arms,
+                representative_test_args(&param_infos, config.named),
+                backend.collect,
+            )
+        }
+        Strategy::ArgsStruct => (
+            ArgsStructBackend { target: &target }.emit_arms(&mut forms, &mut macro_matches),
+            representative_test_args(&param_infos, config.named),
+            None,
+        ),
+    };
+    if let Err(e) = arms {
+        return e.to_compile_error();
+    }
+
+    // the args struct reports invalid arguments itself
+    if config.strategy() != Strategy::ArgsStruct {
+        macro_matches.extend(create_munch_arms(
+            config,
+            &macro_self_path,
+            &macro_ident,
+            &params_mod_path,
+            &param_names,
+            &output,
+            &accepted_forms,
+            first_ref.last().is_some_and(ToMacroPattern::is_variadic),
+            collect,
+        ));
+    }
+
+    // besides the `@params` arm
+    let num_arms = macro_matches.len() - 1;
+    timings.report(&macro_ident, num_arms);
+
+    // shows which items are responsible for compile times
+    let arm_report = config.report_arms.then(|| {
+        Diagnostic::note(
+            "GeneratedArms",
+            item_ident.span(),
+            format!(
+                "defamed: generated {} arms for `{}`",
+                num_arms,
+                item_ident.unraw()
+            ),
+        )
+        .emit()
+    });
+
+    let _macro_mod = syn::Ident::new(
+        &format!("{}_macros", item_ident.to_token_stream()),
+        Span::call_site(),
+    );
+
+    // the macro is re-exported with the visibility of the item: public items export it from the crate,
+    // restricted items keep it within their scope and private items within their module
+    let macro_def_attr = match &vis {
+        Visibility::Public(_) => quote! {#[macro_export]},
+        Visibility::Restricted(_) | Visibility::Inherited => quote! {},
+    };
+
+    let func_dunder_ident = syn::Ident::new(
+        &format!(
+            "__{}{}_{}__",
+            match &item_path {
+                Some(p) => p
+                    .segments
+                    .iter()
+                    .map(|seg| format!("{}_", seg.ident))
+                    .collect::<String>(),
+                None => "".to_string(),
+            },
+            macro_ident.to_token_stream(),
+            hash
+        ),
+        Span::call_site(),
+    );
+
+    // let full_func_path = match func_path {
+    //     Some(p) => quote! {crate::#p::#func_ident},
+    //     None => quote! {crate::#func_ident},
+    // };
+    let item_prefix = output.to_string();
+
+    let cfg_attrs = cfg_attrs(config, item_attrs);
+
+    let macro_doc = create_macro_doc(
+        config,
+        &item_prefix,
+        &item_ident,
+        &macro_ident,
+        &output,
+        &param_infos,
+        doctest_path(config, &vis, &output),
+    );
+
+    let doc_aliases = create_doc_aliases(config, item_attrs, &item_ident, &macro_ident);
+    // names are only completed by the arm of unknown names
+    let params_mod = create_params_mod(
+        config,
+        &vis,
+        &cfg_attrs,
+        &params_mod_ident,
+        &param_names,
+        &param_infos,
+        config.strategy() != Strategy::ArgsStruct && config.named != NamedArgs::Disabled,
+    );
+    let reflection = cfg!(feature = "reflect").then(|| {
+        create_reflection(
+            &vis,
+            &cfg_attrs,
+            &item_prefix,
+            &item_ident,
+            &macro_ident,
+            &params_mod_ident,
+        )
+    });
+    let self_test = config.self_test.then(|| {
+        create_self_test(
+            config,
+            &cfg_attrs,
+            &macro_ident,
+            &output,
+            &param_names,
+            &self_test_calls,
+        )
+    });
+    let macro_doc = quote! {
+        #macro_doc
+        #doc_aliases
+    };
+
+    // debuggers and profilers attribute the generated code to the call site
+    let collapse_debuginfo = (cfg!(collapse_debuginfo)
+        && config.supports(crate::expand::COLLAPSE_DEBUGINFO_MSRV))
+    .then(|| quote! {#[collapse_debuginfo(yes)]});
+
+    let macro_matches = macro_matches.into_body();
+
+    // macros 2.0 are scoped like any other item, so no re-export is needed
+    let expanded = if cfg!(feature = "decl_macro") {
+        if config.macro_use {
+            return syn::Error::new(
+                item_ident.span(),
+                "`macro_use` is not supported by macros 2.0",
+            )
+            .to_compile_error();
+        }
+
+        quote! {
+            #(#cfg_attrs)*
+            #macro_doc
+            #[allow(unused_macros)]
+            #collapse_debuginfo
+            #vis macro #macro_ident {
+                #macro_matches
+            }
+
+            #params_mod
+            #self_test
+            #reflection
+            #arm_report
+        }
+    }
+    // `#[macro_use] extern crate` only imports exported macros by their definition name
+    else if config.macro_use {
+        if !matches!(vis, Visibility::Public(_)) {
+            return syn::Error::new(
+                item_ident.span(),
+                "`macro_use` requires a public item, as the macro must be exported",
+            )
+            .to_compile_error();
+        }
+
+        quote! {
+            #(#cfg_attrs)*
+            #macro_doc
+            #[allow(unused_macros)]
+            #collapse_debuginfo
+            #[macro_export]
+            macro_rules! #macro_ident (
+                #macro_matches
+            );
+
+            #params_mod
+            #self_test
+            #reflection
+            #arm_report
+        }
+    } else {
+        quote! {
+            // #vis mod #macro_mod {
+
+                #(#cfg_attrs)*
+                #[doc(hidden)]
+                #[allow(unused_macros)]
+                #collapse_debuginfo
+                #macro_def_attr
+                macro_rules! #func_dunder_ident (
+                    #macro_matches
+                );
+
+                #(#cfg_attrs)*
+                #[doc(inline)]
+                #macro_doc
+                #vis use #func_dunder_ident as #macro_ident;
+
+                #params_mod
+                #self_test
+                #reflection
+                #arm_report
+
+            // }
+            // #vis use #macro_mod::*;
+            // #vis use #func_ident!;
+        }
+    };
+
+    debug_dir::write(
+        &signatures::file_stem(
+            &config.crate_name().unwrap_or_default(),
+            item_path,
+            &macro_ident,
+        ),
+        &macro_ident,
+        &expanded,
+    );
+
+    expanded
+}
+
+// /// Struct with named fields
+// pub fn generate_item_struct_struct_macro(
+//     ident: syn::Ident,
+//     named_fields: syn::FieldsNamed,
+// ) -> pm2::TokenStream {
+//     //asldkdm
+//     quote! {}
+// }
+
+// /// Tuple struct
+// pub fn generate_item_struct_tuple_macro(
+//     ident: syn::Ident,
+//     unnamed_fields: syn::FieldsUnnamed,
+// ) -> pm2::TokenStream {
+//     quote! {}
+// }
+
+/// Convert a `snake_case` identifier to `UpperCamelCase`.
+fn to_upper_camel_case(ident: &str) -> String {
+    ident
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Convert an upper camel case identifier to snake case.
+fn to_snake_case(ident: &str) -> String {
+    let chars = ident.chars().collect::<Vec<_>>();
+    let mut res = String::with_capacity(ident.len() + 4);
+
+    for (idx, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && idx != 0 {
+            let prev_lower = chars[idx - 1].is_lowercase() || chars[idx - 1].is_numeric();
+            let next_lower = chars
+                .get(idx + 1)
+                .map(|n| n.is_lowercase())
+                .unwrap_or(false);
+
+            // word boundaries: `someWord`, `HTTPServer`
+            if prev_lower || (chars[idx - 1].is_uppercase() && next_lower) {
+                res.push('_');
+            }
+        }
+
+        res.extend(c.to_lowercase());
+    }
+
+    res
+}
+
+/// Item a macro is generated for, shared by the built-in backends.
+struct ArmTarget<'a> {
+    config: &'a MacroConfig,
+    output: &'a MacroType,
+    item_ident: &'a syn::Ident,
+    macro_ident: &'a syn::Ident,
+    /// Path the macro calls itself through
+    macro_self_path: &'a pm2::TokenStream,
+    func_path_root: &'a pm2::TokenStream,
+    param_names: &'a [Option<syn::Ident>],
+    /// Whether the call awaits a default value, and is wrapped in an `async` block
+    awaited: bool,
+    /// Description of the accepted call forms, for errors
+    accepted_forms: &'a str,
+}
+
+/// Backend of the permute and canonical strategies, with one call arm for every form.
+///
+/// Arms of the canonical strategy match the arguments sorted into declaration order,
+/// which are collected by the munch arms.
+struct PermuteBackend<'a> {
+    target: &'a ArmTarget<'a>,
+    canonical: bool,
+    timings: &'a mut Timings,
+    /// Arguments of the self-test, one for every arm
+    self_test_calls: Vec<Vec<TestArg>>,
+    /// Set by the canonical strategy, once the arms are emitted
+    collect: Option<Collect>,
+}
+
+impl<T> Backend<PermutedItem<T>> for PermuteBackend<'_>
+where
+    T: Clone + PartialEq + Debug,
+    PermutedItem<T>: ToMacroPattern + ToDocInfo,
+{
+    fn pattern_syntax(&self) -> Option<PatternSyntax> {
+        Some(pattern_syntax(self.target.config, self.target.output))
+    }
+
+    fn render_pattern(&self, form: &[PermutedItem<T>]) -> pm2::TokenStream {
+        match self.canonical {
+            true => create_slot_signature(self, form),
+            false => create_macro_signature(self, form),
+        }
+    }
+
+    fn emit_arms(
+        &mut self,
+        forms: &mut dyn Iterator<Item = Vec<PermutedItem<T>>>,
+        arms: &mut MacroArms,
+    ) -> syn::Result<()> {
+        let ArmTarget {
+            config,
+            output,
+            item_ident,
+            func_path_root,
+            ..
+        } = self.target;
+        let canonical = self.canonical;
+        let call = syn::Ident::new(CALL_MARKER, Span::call_site());
+        // validated arguments are preceded by the original ones, for errors
+        let args = quote! {[$($__defamed_args:tt)*]};
+
+        let mut forms = forms.peekable();
+        let first_ref = match forms.peek() {
+            Some(first) => first.clone(),
+            None => return Ok(()),
+        };
+
+        // every form is buffered to sort it and prune the shadowed ones,
+        // while each arm is written into the macro once it is rendered
+        let mut forms = forms
+            .map(|p| (permute::arm_sort_key(&p, &first_ref), p))
+            .collect::<Vec<_>>();
+        forms.sort_by(|(a, _), (b, _)| a.cmp(b));
+        permute::prune_shadowed(&mut forms);
+        self.timings.phase("permute");
+        let num_forms = forms.len();
+
+        for (idx, (_, p)) in forms.into_iter().enumerate() {
+            let macro_signature = self.render_pattern(&p);
+            let (turbofish, func_signature) = create_func_call_signature(first_ref.as_slice(), &p)
+                .map_err(|e| syn::Error::new(item_ident.span(), e))?;
+
+            let self_val = syn::Ident::new(RECEIVER_METAVAR, Span::call_site());
+            let (receiver, body) = match output {
+                MacroType::Function | MacroType::StructTuple => (
+                    None,
+                    quote! {#func_path_root #item_ident #turbofish(#func_signature)},
+                ),
+                MacroType::Struct => (None, quote! {#func_path_root #item_ident{#func_signature}}),
+                MacroType::Associated(self_ty) => (
+                    None,
+                    quote! {#func_path_root #self_ty::#item_ident #turbofish(#func_signature)},
+                ),
+                // the receiver is always the first positional argument
+                MacroType::Method(..) => (
+                    Some(match canonical {
+                        true => quote! {($#self_val: expr)},
+                        false => quote! {($#self_val: expr),},
+                    }),
+                    quote! {$#self_val.#item_ident #turbofish(#func_signature)},
+                ),
+            };
+            let body = async_shim(self.target.awaited, body);
+
+            // shows up as a doc comment in the expansion of both the item and the invocation
+            let body = match config.readable {
+                true => {
+                    let comment = format!(
+                        " arm {} of {}: {}",
+                        idx + 1,
+                        num_forms,
+                        describe_form(&p, canonical)
+                    );
+                    quote! {{
+                        #[doc = #comment]
+                        const _: () = ();
+                        #body
+                    }}
+                }
+                false => body,
+            };
+
+            arms.push(quote! {
+                (@#call #args #receiver #macro_signature) => {
+                    #body
+                }
+            });
+
+            if config.self_test {
+                self.self_test_calls
+                    .push(form_test_args(&p, &first_ref, canonical));
+            }
+        }
+
+        // sorted arguments are passed on to the call arms in their slots
+        if canonical {
+            let macro_self_path = self.target.macro_self_path;
+            let leading = output.leading_args();
+            let slots = (0..leading + first_ref.len()).map(slot_var);
+
+            self.collect = Some(Collect {
+                leading,
+                call: quote! {#macro_self_path!(@#call [$($__defamed_args)*] #(#slots)*)},
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Backend of the muncher strategy, which collects the arguments in declaration order
+/// and expands each of them, or its default value, in a single call.
+struct MuncherBackend<'a> {
+    target: &'a ArmTarget<'a>,
+    /// Set once the arms are emitted
+    collect: Option<Collect>,
+}
+
+impl<P: ToMacroPattern + ToDocInfo> Backend<P> for MuncherBackend<'_> {
+    fn pattern_syntax(&self) -> Option<PatternSyntax> {
+        Some(pattern_syntax(self.target.config, self.target.output))
+    }
+
+    fn render_pattern(&self, form: &[P]) -> pm2::TokenStream {
+        create_slot_signature(self, form)
+    }
+
+    fn emit_arms(
+        &mut self,
+        forms: &mut dyn Iterator<Item = Vec<P>>,
+        arms: &mut MacroArms,
+    ) -> syn::Result<()> {
+        let target = self.target;
+        let declared = forms.next().unwrap_or_default();
+        let (slot_arms, mut collect) = create_slot_call(
+            target.macro_self_path,
+            target.func_path_root,
+            target.item_ident,
+            target.macro_ident,
+            target.output,
+            target.param_names,
+            &declared,
+        );
+        collect.call = async_shim(target.awaited, collect.call);
+        self.collect = Some(collect);
+        arms.extend(slot_arms);
+
+        Ok(())
+    }
+}
+
+/// Backend of the args struct strategy, which sets the arguments on the hidden args struct of the function.
+struct ArgsStructBackend<'a> {
+    target: &'a ArmTarget<'a>,
+}
+
+impl<P: ToMacroPattern> Backend<P> for ArgsStructBackend<'_> {
+    fn pattern_syntax(&self) -> Option<PatternSyntax> {
+        Some(pattern_syntax(self.target.config, self.target.output))
+    }
+
+    /// Arguments are collected as the fields of the args struct.
+    fn render_pattern(&self, form: &[P]) -> pm2::TokenStream {
+        self.target
+            .param_names
+            .iter()
+            .zip(form)
+            .filter_map(|(name, p)| {
+                let pattern = self.item_pattern(p)?;
+                Some(quote! {#name: #pattern,})
+            })
+            .collect()
+    }
+
+    fn emit_arms(
+        &mut self,
+        forms: &mut dyn Iterator<Item = Vec<P>>,
+        arms: &mut MacroArms,
+    ) -> syn::Result<()> {
+        let target = self.target;
+        let declared = forms.next().unwrap_or_default();
+
+        arms.extend(create_args_struct_arms(
+            pattern_syntax(target.config, target.output),
+            target.macro_self_path,
+            target.func_path_root,
+            &args_struct_ident(target.config, target.item_ident),
+            target.macro_ident,
+            target.param_names,
+            &declared,
+            target.accepted_forms,
+        ));
+
+        Ok(())
+    }
+}
+
+/// Create the arms of the muncher strategy that expand each collected argument,
+/// along with the call made once all arguments are munched.
+///
+/// Unset slots expand to the default value of their parameter, or to an error if it is required.
+fn create_slot_call<P: ToMacroPattern + ToDocInfo>(
+    self_path: &pm2::TokenStream,
+    func_path_root: &pm2::TokenStream,
+    item_ident: &syn::Ident,
+    macro_ident: &syn::Ident,
+    output: &MacroType,
+    param_names: &[Option<syn::Ident>],
+    params: &[P],
+) -> (Vec<pm2::TokenStream>, Collect) {
+    let arg = syn::Ident::new(ARG_MARKER, Span::call_site());
+    let leading = output.leading_args();
+
+    let mut arms = vec![quote! {
+        (@#arg $__defamed_i:tt ($($__defamed_v:tt)*)) => {
+            $($__defamed_v)*
+        }
+    }];
+
+    if leading > 0 {
+        let missing = format!("missing receiver for `{}!`", macro_ident);
+        arms.push(quote! {
+            (@#arg 0 _) => {
+                ::core::compile_error!(#missing)
+            }
+        });
+    }
+
+    for (idx, param) in params.iter().enumerate() {
+        let slot = pm2::Literal::usize_unsuffixed(leading + idx);
+        let value = param.to_default_value().unwrap_or_else(|| {
+            let missing = format!(
+                "missing required argument `{}` for `{}!`",
+                param.to_doc_info().ident,
+                macro_ident
+            );
+            quote! {::core::compile_error!(#missing)}
+        });
+
+        arms.push(quote! {
+            (@#arg #slot _) => {
+                #value
+            }
+        });
+    }
+
+    let args = (0..leading + params.len())
+        .map(|idx| {
+            let slot = pm2::Literal::usize_unsuffixed(idx);
+            let var = slot_var(idx);
+            quote! {#self_path!(@#arg #slot #var)}
+        })
+        .collect::<Vec<_>>();
+    let (turbofish, args) = split_generic_args(
+        std::iter::repeat_n(None, leading)
+            .chain(params.iter().map(ToMacroPattern::generic_position))
+            .zip(args),
+    );
+
+    let call = match output {
+        MacroType::Function | MacroType::StructTuple => quote! {
+            #func_path_root #item_ident #turbofish(#(#args),*)
+        },
+        MacroType::Struct => {
+            let fields = param_names.iter().flatten();
+            quote! {
+                #func_path_root #item_ident{#(#fields: #args),*}
+            }
+        }
+        MacroType::Associated(self_ty) => quote! {
+            #func_path_root #self_ty::#item_ident #turbofish(#(#args),*)
+        },
+        MacroType::Method(..) => {
+            let (receiver, args) = args.split_first().expect("methods always have a receiver");
+            quote! {
+                #receiver.#item_ident #turbofish(#(#args),*)
+            }
+        }
+    };
+
+    (arms, Collect { leading, call })
+}
+
+/// Whether `tokens` await a future.
+fn awaits(tokens: pm2::TokenStream) -> bool {
+    let mut after_dot = false;
+
+    tokens.into_iter().any(|token| {
+        let found = match &token {
+            pm2::TokenTree::Ident(ident) => after_dot && ident == "await",
+            pm2::TokenTree::Group(group) => awaits(group.stream()),
+            pm2::TokenTree::Punct(_) | pm2::TokenTree::Literal(_) => false,
+        };
+        after_dot = matches!(&token, pm2::TokenTree::Punct(p) if p.as_char() == '.');
+
+        found
+    })
+}
+
+/// Call an async item from an `async` block if `awaited`,
+/// so the defaults it awaits are evaluated by the returned future.
+/// The arguments are then evaluated once the future is first polled.
+fn async_shim(awaited: bool, call: pm2::TokenStream) -> pm2::TokenStream {
+    match awaited {
+        true => quote! {async { #call.await }},
+        false => call,
+    }
+}
+
+/// Path to the module of an item from within the generated macro, followed by `::`.
+///
+/// Items without a path are resolved where the macro is called.
+pub(crate) fn path_root(item_path: Option<&syn::Path>) -> pm2::TokenStream {
+    item_path
+        .map(|g| {
+            if g.is_ident(crate::expand::ROOT_VISIBILITY_IDENT) {
+                quote! {$#g ::}
+            } else {
+                quote! {$crate :: #g ::}
+            }
+        })
+        .unwrap_or_default()
+}
+
+/// Identifier of the hidden struct that collects the arguments of a function,
+/// for the args struct strategy.
+pub(crate) fn args_struct_ident(config: &MacroConfig, item_ident: &syn::Ident) -> syn::Ident {
+    syn::Ident::new(
+        &format!(
+            "__defamed_args_{}_{}",
+            item_ident.unraw(),
+            expansion_hash(config, item_ident)
+        ),
+        item_ident.span(),
+    )
+}
+
+/// Hash of the path and name of an item, suffixed to the identifiers of the hidden items generated for it
+/// and to the internal identifiers of its macro, so those of different items never collide.
+///
+/// Two items expanded into the same scope, such as through `include!`, have different names,
+/// as their macros would collide otherwise. Only these inputs are hashed, so the suffix
+/// does not depend on where or how often the item is expanded.
+/// FNV-1a is used as its output does not change between compilers, keeping expansions reproducible.
+pub(crate) fn expansion_hash(config: &MacroConfig, item_ident: &syn::Ident) -> String {
+    let key = format!("{}::{}", config.path.to_token_stream(), item_ident.unraw());
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    format!("{:08x}", (hash ^ (hash >> 32)) as u32)
+}
+
+/// Identifier of the hidden module describing the parameters of a macro, suffixed with its [expansion_hash].
+fn params_mod_ident(macro_ident: &syn::Ident, hash: &str) -> syn::Ident {
+    syn::Ident::new(
+        &format!("__defamed_params_{}_{}", macro_ident.unraw(), hash),
+        Span::call_site(),
+    )
+}
+
+/// Suffix the internal metavariables (`$__defamed_rest`) and arm markers (`@__defamed_call`)
+/// of a macro with its [expansion_hash].
+fn suffix_internal_idents(tokens: pm2::TokenStream, hash: &str) -> pm2::TokenStream {
+    let mut after_sigil = false;
+
+    tokens
+        .into_iter()
+        .map(|tree| {
+            let suffixed = match tree {
+                pm2::TokenTree::Group(group) => {
+                    let mut suffixed = pm2::Group::new(
+                        group.delimiter(),
+                        suffix_internal_idents(group.stream(), hash),
+                    );
+                    suffixed.set_span(group.span());
+                    pm2::TokenTree::Group(suffixed)
+                }
+                pm2::TokenTree::Ident(ident)
+                    if after_sigil && ident.to_string().starts_with("__defamed_") =>
+                {
+                    pm2::TokenTree::Ident(syn::Ident::new(
+                        &format!("{}_{}", ident, hash),
+                        ident.span(),
+                    ))
+                }
+                other => other,
+            };
+            after_sigil =
+                matches!(&suffixed, pm2::TokenTree::Punct(p) if matches!(p.as_char(), '$' | '@'));
+
+            suffixed
+        })
+        .collect()
+}
+
+/// Generate a hidden module describing the parameters of a macro.
+///
+/// `PARAMS` lists the name, type and default value (if any) of every parameter in declaration order,
+/// as written in the item, for other macros and tools to introspect.
+///
+/// With `complete`, there is also a constant for every named parameter,
+/// documented like the parameter in the macro docs.
+/// These are only compiled by rust-analyzer, which resolves unknown argument names in the module,
+/// and then offers the parameters as completions inside invocations.
+/// Lints are checked for the whole module, as `cfg` attributes are evaluated before the item they are on.
+fn create_params_mod(
+    config: &MacroConfig,
+    vis: &Visibility,
+    cfg_attrs: &[syn::Attribute],
+    mod_ident: &syn::Ident,
+    param_names: &[Option<syn::Ident>],
+    param_infos: &[DocInfo],
+    complete: bool,
+) -> pm2::TokenStream {
+    let metadata = param_infos.iter().map(|info| {
+        let DocInfo {
+            ident,
+            ty,
+            default_value,
+            ..
+        } = info;
+        let default_value = match default_value {
+            Some(val) => quote! {::core::option::Option::Some(#val)},
+            None => quote! {::core::option::Option::None},
+        };
+
+        quote! {(#ident, #ty, #default_value)}
+    });
+
+    let (names, docs): (Vec<_>, Vec<_>) = param_names
+        .iter()
+        .zip(param_infos)
+        .filter(|_| complete)
+        .filter_map(|(name, info)| Some((name.as_ref()?, info.to_string())))
+        .unzip();
+    let unexpected_cfgs = unexpected_cfgs_lints(config);
+
+    quote! {
+        #(#cfg_attrs)*
+        #[doc(hidden)]
+        #[allow(dead_code, non_upper_case_globals, #unexpected_cfgs)]
+        #vis mod #mod_ident {
+            /// Name, type and default value of every parameter
+            pub const PARAMS: &[(&str, &str, ::core::option::Option<&str>)] = &[#(#metadata),*];
+
+            #(
+                #[cfg(rust_analyzer)]
+                #[doc = #docs]
+                pub const #names: () = ();
+            )*
+        }
+    }
+}
+
+/// Argument of a self-test invocation, by the index of its parameter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TestArg {
+    Positional(usize),
+    Named(usize),
+}
+
+/// Arguments of the self-test invocation accepted by the call arm of `form`.
+///
+/// Canonical forms list their items in declaration order, so the items supplied after
+/// an omitted default are passed by name.
+fn form_test_args<T: Clone + PartialEq>(
+    form: &[PermutedItem<T>],
+    reference: &[PermutedItem<T>],
+    canonical: bool,
+) -> Vec<TestArg> {
+    let mut omitted = false;
+
+    form.iter()
+        .filter_map(|item| {
+            // the struct update syntax is not a parameter
+            let idx = reference.iter().position(|r| r == item)?;
+
+            match item {
+                PermutedItem::Positional(_) if canonical && omitted => Some(TestArg::Named(idx)),
+                PermutedItem::Positional(_) => Some(TestArg::Positional(idx)),
+                PermutedItem::Named(_) => Some(TestArg::Named(idx)),
+                PermutedItem::Default(_) => {
+                    omitted = true;
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Arguments of the self-test invocations of strategies with a single call arm,
+/// covering each way an argument can be munched: required arguments positionally,
+/// every argument positionally, each default by name, and every argument by name in reverse order.
+fn representative_test_args(params: &[DocInfo], named: NamedArgs) -> Vec<Vec<TestArg>> {
+    let (required, defaults): (Vec<_>, Vec<_>) =
+        (0..params.len()).partition(|idx| params[*idx].default_value.is_none());
+    let positional = required
+        .iter()
+        .map(|idx| TestArg::Positional(*idx))
+        .collect::<Vec<_>>();
+
+    let mut calls = vec![
+        positional.clone(),
+        (0..params.len()).map(TestArg::Positional).collect(),
+    ];
+    if named != NamedArgs::Disabled {
+        calls.extend(defaults.iter().map(|idx| {
+            let mut args = positional.clone();
+            args.push(TestArg::Named(*idx));
+            args
+        }));
+    }
+    if named == NamedArgs::Any {
+        calls.push((0..params.len()).rev().map(TestArg::Named).collect());
+    }
+    calls.dedup();
+
+    calls
+}
+
+/// Generate a check invoking the macro once for every call in `calls`, compiled with the tests,
+/// so a malformed arm fails the build of the tests.
+///
+/// Every argument is `todo!()`, or `unimplemented!()` before Rust 1.40, and the invocations are only type-checked:
+/// they are written in a closure that is never called.
+fn create_self_test(
+    config: &MacroConfig,
+    cfg_attrs: &[syn::Attribute],
+    macro_ident: &syn::Ident,
+    output: &MacroType,
+    param_names: &[Option<syn::Ident>],
+    calls: &[Vec<TestArg>],
+) -> pm2::TokenStream {
+    let syntax = pattern_syntax(config, output);
+    let placeholder = placeholder(config).parse::<pm2::TokenStream>().unwrap();
+    // the `tt` fragment only matches a single token tree
+    let placeholder = match syntax {
+        PatternSyntax::TokenTree => quote! {(::core::#placeholder)},
+        PatternSyntax::Expr | PatternSyntax::StructLiteral => quote! {::core::#placeholder},
+    };
+    let named_separator = syntax.named_separator();
+    let receiver = match output {
+        MacroType::Method(_, self_ty, convention) => {
+            let value = quote! {{ let receiver: #self_ty = #placeholder; receiver }};
+            Some(match convention {
+                Receiver::Positional => value,
+                Receiver::Named => quote! {self = #value},
+            })
+        }
+        _ => None,
+    };
+
+    // tuple structs cannot be called with named arguments
+    let invocations = calls.iter().filter_map(|call| {
+        let args = call
+            .iter()
+            .map(|arg| match arg {
+                TestArg::Positional(_) => Some(placeholder.clone()),
+                TestArg::Named(idx) => {
+                    let name = param_names[*idx].as_ref()?;
+                    Some(quote! {#name #named_separator #placeholder})
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let args = receiver.iter().cloned().chain(args);
+
+        Some(match output {
+            // omitted fields are filled in with struct update syntax
+            MacroType::Struct => {
+                let update = (call.len() < param_names.len()).then(|| quote! {..});
+                let args = args.chain(update);
+                quote! {#macro_ident! { #(#args),* }}
+            }
+            _ => quote! {#macro_ident!(#(#args),*)},
+        })
+    });
+    let doc = format!(
+        " Expands every call form of `{}!`, which is type-checked but never run",
+        macro_ident.unraw()
+    );
+
+    // an anonymous constant resolves the item in function bodies as well, where tests cannot be declared
+    quote! {
+        #(#cfg_attrs)*
+        #[cfg(test)]
+        #[doc = #doc]
+        #[allow(
+            unreachable_code,
+            unused_unsafe,
+            unused_variables,
+            clippy::let_unit_value,
+            clippy::diverging_sub_expression
+        )]
+        const _: () = {
+            // calls of unsafe items expand to unsafe calls
+            let _ = || unsafe {
+                #(let _ = #invocations;)*
+            };
+        };
+    }
+}
+
+/// Generate a module of tests named after the function,
+/// calling its macro with all defaults and once for every case of each default.
+///
+/// Defaults before an overridden one are passed positionally, so every strategy accepts the call.
+pub(crate) fn create_test_matrix(
+    item_attrs: &[syn::Attribute],
+    config: &MacroConfig,
+    sig: &syn::Signature,
+    params: &[FunctionParam],
+) -> pm2::TokenStream {
+    let cfg_attrs = cfg_attrs(config, item_attrs);
+    let item_ident = &sig.ident;
+    let output = &sig.output;
+
+    let defaults = params
+        .iter()
+        .map(|p| {
+            p.default_value
+                .to_value_tokens()
+                .expect("all parameters have defaults")
+        })
+        .collect::<Vec<_>>();
+    let cases = params.iter().enumerate().flat_map(|(idx, param)| {
+        let leading = &defaults[..idx];
+        param
+            .cases()
+            .iter()
+            .enumerate()
+            .map(move |(case_idx, case)| {
+                let test_ident = syn::Ident::new(
+                    &format!("{}_{}", param.name().trim_start_matches("r#"), case_idx),
+                    Span::call_site(),
+                );
+                quote! {
+                    #[test]
+                    fn #test_ident() #output {
+                        #item_ident!(#(#leading,)* #case)
+                    }
+                }
+            })
+    });
+    let doc = format!(
+        " Tests of `{}` with all defaults, and with every case of each default",
+        item_ident.unraw()
+    );
+
+    // modules and functions are in separate namespaces, so tests are listed as `<function>::<case>`
+    quote! {
+        #(#cfg_attrs)*
+        #[cfg(test)]
+        #[doc = #doc]
+        mod #item_ident {
+            use super::*;
+
+            #[test]
+            fn defaults() #output {
+                #item_ident!()
+            }
+
+            #(#cases)*
+        }
+    }
+}
+
+/// Generate a public constant listing the parameters of an item, for runtime reflection.
+///
+/// The constant is named after the macro in upper snake case, such as `COUNTER_INCREMENT_PARAMS`,
+/// and refers to the list of parameters in the module of parameters.
+/// Proc-macro crates cannot export types, so every entry is a tuple of name, type and default value.
+fn create_reflection(
+    vis: &Visibility,
+    cfg_attrs: &[syn::Attribute],
+    item_prefix: &str,
+    item_ident: &syn::Ident,
+    macro_ident: &syn::Ident,
+    params_mod_ident: &syn::Ident,
+) -> pm2::TokenStream {
+    let const_ident = syn::Ident::new(
+        &format!(
+            "{}_PARAMS",
+            to_snake_case(&macro_ident.unraw().to_string()).to_uppercase()
+        ),
+        Span::call_site(),
+    );
+    let doc = format!(
+        "Name, type and default value of every parameter of [`{}{}`], in declaration order",
+        item_prefix,
+        item_ident.unraw()
+    );
+
+    quote! {
+        #(#cfg_attrs)*
+        #[doc = #doc]
+        #[allow(dead_code)]
+        #vis const #const_ident: &[(&str, &str, ::core::option::Option<&str>)] =
+            #params_mod_ident::PARAMS;
+    }
+}
+
+/// Generate the hidden args struct of a function, along with the shim that calls it.
+///
+/// Every field is optional, so defaults are only evaluated for omitted arguments.
+pub fn generate_args_struct(
+    vis: &Visibility,
+    item_attrs: &[syn::Attribute],
+    config: &MacroConfig,
+    sig: &syn::Signature,
+    params: &[FunctionParam],
+) -> Result<pm2::TokenStream, syn::Error> {
+    if let Some(param) = sig.generics.params.first() {
+        return Err(syn::Error::new(
+            param.span(),
+            "the `args_struct` strategy does not support generic parameters",
+        ));
+    }
+
+    let output = &sig.output;
+    // bounds without generic parameters still apply to the call
+    let where_clause = &sig.generics.where_clause;
+    let unnameable = params
+        .iter()
+        .map(|p| p.ty().to_token_stream())
+        .chain([output.to_token_stream()])
+        .find_map(unnameable_type);
+    if let Some(span) = unnameable {
+        return Err(syn::Error::new(
+            span,
+            "the `args_struct` strategy requires types that can be named outside the function, \
+            without `impl Trait` or elided lifetimes",
+        ));
+    }
+
+    let names = params
+        .iter()
+        .map(|p| {
+            syn::parse_str::<syn::Ident>(p.name()).map_err(|_| {
+                syn::Error::new(
+                    p.inner_span(),
+                    "the `args_struct` strategy requires parameters to be plain identifiers",
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let tys = params.iter().map(|p| p.ty());
+    // every value is an argument of the call, so defaults coerce to the type of their parameter,
+    // such as a boxed value to a boxed trait object
+    let values = names.iter().zip(params).map(|(name, p)| {
+        let omitted = p
+            .default_tokens()
+            .unwrap_or_else(|| quote! {::core::unreachable!()});
+
+        quote! {
+            match self.#name {
+                ::core::option::Option::Some(value) => value,
+                ::core::option::Option::None => #omitted,
+            }
+        }
+    });
+
+    let item_ident = &sig.ident;
+    let args_ident = args_struct_ident(config, item_ident);
+    let asyncness = &sig.asyncness;
+    let unsafety = &sig.unsafety;
+    let awaited = asyncness.map(|_| quote! {.await});
+    let call = quote! {#item_ident(#(#values),*) #awaited};
+    let call = match unsafety {
+        Some(_) => quote! {unsafe { #call }},
+        None => call,
+    };
+
+    let cfg_attrs = cfg_attrs(config, item_attrs);
+    // the call is made from `call`, which must be compiled with the same features,
+    // and report panics at the location of its own caller
+    let call_attrs = item_attrs
+        .iter()
+        .filter(|a| a.path().is_ident("target_feature") || a.path().is_ident("track_caller"));
+
+    Ok(quote! {
+        #(#cfg_attrs)*
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #[derive(Default)]
+        #vis struct #args_ident {
+            #(#vis #names: ::core::option::Option<#tys>,)*
+        }
+
+        #(#cfg_attrs)*
+        impl #args_ident {
+            #[doc(hidden)]
+            #(#call_attrs)*
+            #vis #asyncness #unsafety fn call(self) #output #where_clause {
+                #call
+            }
+        }
+    })
+}
+
+/// Identifiers of the extension trait of a method, and of the struct collecting its arguments.
+pub(crate) fn ext_trait_idents(
+    self_ident: &syn::Ident,
+    item_ident: &syn::Ident,
+) -> (syn::Ident, syn::Ident) {
+    let prefix = format!(
+        "{}{}",
+        self_ident.unraw(),
+        to_upper_camel_case(&item_ident.unraw().to_string())
+    );
+
+    (
+        syn::Ident::new(&format!("{}Ext", prefix), item_ident.span()),
+        syn::Ident::new(&format!("{}Args", prefix), item_ident.span()),
+    )
+}
+
+/// `cfg` attributes of the item, which the items generated from it are compiled with,
+/// along with the cargo feature they are gated behind by the `feature` option.
+pub(crate) fn cfg_attrs(
+    config: &MacroConfig,
+    item_attrs: &[syn::Attribute],
+) -> Vec<syn::Attribute> {
+    item_attrs
+        .iter()
+        .filter(|a| a.path().is_ident("cfg"))
+        .cloned()
+        .chain(
+            config
+                .feature
+                .as_ref()
+                .map(|feature| syn::parse_quote! {#[cfg(feature = #feature)]}),
+        )
+        .collect()
+}
+
+/// Generate the extension trait of a method, and its implementation for `self_ty`.
+///
+/// The trait method is named after the method with a `_with` suffix,
+/// and takes the arguments of the method collected in the struct `args_ident`, with fields `names`.
+pub fn generate_ext_trait(
+    vis: &Visibility,
+    item_attrs: &[syn::Attribute],
+    config: &MacroConfig,
+    self_ident: &syn::Ident,
+    self_ty: &syn::Type,
+    sig: &syn::Signature,
+    names: &[syn::Ident],
+) -> pm2::TokenStream {
+    let item_ident = &sig.ident;
+    let (trait_ident, args_ident) = ext_trait_idents(self_ident, item_ident);
+    let with_ident = syn::Ident::new(&format!("{}_with", item_ident.unraw()), item_ident.span());
+
+    // `mut self` is a pattern, which trait declarations do not allow, and the receiver is only passed on
+    let mut receiver = sig
+        .receiver()
+        .expect("methods always have a receiver")
+        .clone();
+    if receiver.reference.is_none() {
+        receiver.mutability = None;
+    }
+    let unsafety = &sig.unsafety;
+    let output = &sig.output;
+
+    let call = quote! {<#self_ty>::#item_ident(self, #(args.#names),*)};
+    let call = match unsafety {
+        Some(_) => quote! {unsafe { #call }},
+        None => call,
+    };
+
+    let cfg_attrs = cfg_attrs(config, item_attrs);
+    let track_caller = item_attrs
+        .iter()
+        .filter(|a| a.path().is_ident("track_caller"));
+    let trait_doc = format!(
+        " Calls [`{}::{}`] as a method, with its arguments collected in [`{}`]",
+        self_ident,
+        item_ident.unraw(),
+        args_ident
+    );
+    let method_doc = format!(
+        " Calls [`{}::{}`], use the [`{}!`] macro to fill in the default arguments",
+        self_ident,
+        item_ident.unraw(),
+        args_ident
+    );
+
+    quote! {
+        #(#cfg_attrs)*
+        #[doc = #trait_doc]
+        #vis trait #trait_ident {
+            #[doc = #method_doc]
+            #unsafety fn #with_ident(#receiver, args: #args_ident) #output;
+        }
+
+        #(#cfg_attrs)*
+        impl #trait_ident for #self_ty {
+            #[inline]
+            #(#track_caller)*
+            #unsafety fn #with_ident(#receiver, args: #args_ident) #output {
+                #call
+            }
+        }
+    }
+}
+
+/// Find a type that cannot be named outside the signature of its function,
+/// such as `impl Trait` or a reference with an elided lifetime.
+pub(crate) fn unnameable_type(tokens: pm2::TokenStream) -> Option<Span> {
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match &token {
+            pm2::TokenTree::Ident(ident) if ident == "impl" => return Some(ident.span()),
+            // `&&` is two tokens, the lifetime belongs to the second one
+            pm2::TokenTree::Punct(punct) if punct.as_char() == '&' => match tokens.peek() {
+                Some(pm2::TokenTree::Punct(next)) if matches!(next.as_char(), '\'' | '&') => (),
+                _ => return Some(punct.span()),
+            },
+            pm2::TokenTree::Group(group) => {
+                if let Some(span) = unnameable_type(group.stream()) {
+                    return Some(span);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Create the arms of the args struct strategy.
+///
+/// Positional arguments are named after the parameters in declaration order,
+/// then all arguments are set on the hidden args struct `args_ident`, whose shim calls the function.
+/// The number of arms does not depend on the number of parameters.
+#[allow(clippy::too_many_arguments)]
+fn create_args_struct_arms<P: ToMacroPattern>(
+    syntax: PatternSyntax,
+    self_path: &pm2::TokenStream,
+    func_path_root: &pm2::TokenStream,
+    args_ident: &syn::Ident,
+    macro_ident: &syn::Ident,
+    param_names: &[Option<syn::Ident>],
+    params: &[P],
+    accepted_forms: &str,
+) -> Vec<pm2::TokenStream> {
+    let munch = syn::Ident::new(MUNCH_MARKER, Span::call_site());
+    let unseen = quote! {_};
+    let named_flag = quote! {named};
+    let names = param_names.iter().flatten();
+    let named_separator = syntax.named_separator();
+    let fragment = syntax.fragment();
+
+    // required arguments are checked at compile time, as associated items of the supplied arguments
+    let required = param_names
+        .iter()
+        .zip(params)
+        .filter(|(_, p)| p.to_default_value().is_none())
+        .filter_map(|(name, _)| name.as_ref());
+
+    let positional_prefix = "positional argument `";
+    let positional_suffix = format!(
+        "` passed to `{}!` after a named argument, all positional arguments must come first",
+        macro_ident
+    );
+    let too_many = format!(
+        "too many positional arguments passed to `{}!`\n\n{}",
+        macro_ident, accepted_forms
+    );
+
+    vec![
+        quote! {
+            (@#munch $__defamed_f:tt [$($__defamed_n:ident)*] [$($__defamed_set:tt)*] $__defamed_name:ident #named_separator $__defamed_v:#fragment $(, $($__defamed_rest:tt)*)?) => {
+                #self_path!(@#munch #named_flag [$($__defamed_n)*] [$($__defamed_set)* $__defamed_name: $__defamed_v,] $($($__defamed_rest)*)?)
+            }
+        },
+        quote! {
+            (@#munch #unseen [$__defamed_next:ident $($__defamed_n:ident)*] [$($__defamed_set:tt)*] $__defamed_v:#fragment $(, $($__defamed_rest:tt)*)?) => {
+                #self_path!(@#munch #unseen [$($__defamed_n)*] [$($__defamed_set)* $__defamed_next: $__defamed_v,] $($($__defamed_rest)*)?)
+            }
+        },
+        quote! {
+            (@#munch $__defamed_f:tt [$($__defamed_n:ident)*] [$($__defamed_name:ident: $__defamed_v:expr,)*]) => {{
+                #[allow(dead_code, non_upper_case_globals)]
+                const _: () = {
+                    struct SuppliedArguments;
+                    impl SuppliedArguments {
+                        $(const $__defamed_name: () = ();)*
+                    }
+                    #(let _ = SuppliedArguments::#required;)*
+                };
+
+                // every argument may have been supplied
+                #[allow(clippy::needless_update)]
+                let args = #func_path_root #args_ident {
+                    $($__defamed_name: ::core::option::Option::Some($__defamed_v),)*
+                    ..::core::default::Default::default()
+                };
+                args.call()
+            }}
+        },
+        quote! {
+            (@#munch #named_flag [$($__defamed_n:ident)*] [$($__defamed_set:tt)*] $__defamed_v:#fragment $(, $($__defamed_rest:tt)*)?) => {
+                ::core::compile_error!(::core::concat!(
+                    #positional_prefix,
+                    ::core::stringify!($__defamed_v),
+                    #positional_suffix
+                ))
+            }
+        },
+        quote! {
+            (@#munch #unseen [] [$($__defamed_set:tt)*] $($__defamed_rest:tt)+) => {
+                ::core::compile_error!(#too_many)
+            }
+        },
+        // entry point for all invocations
+        quote! {
+            ($($__defamed_args:tt)*) => {
+                #self_path!(@#munch #unseen [#(#names)*] [] $($__defamed_args)*)
+            }
+        },
+    ]
+}
+
+/// Metavariable of the slot at `idx` in the munched state.
+fn slot_var(idx: usize) -> pm2::TokenStream {
+    let var = syn::Ident::new(&format!("__defamed_s{}", idx), Span::call_site());
+    quote! {$#var}
+}
+
+/// Arguments collected in declaration order and the call made from them.
+struct Collect {
+    /// Number of slots before the first parameter, filled only by positional arguments
+    leading: usize,
+    /// Body of the arm reached once every argument is munched
+    call: pm2::TokenStream,
+}
+
+/// Create the arms that validate the arguments of every invocation
+/// before passing them on to the call arms.
+///
+/// Since `$x:expr` also matches `name = value`, positional call arms would otherwise
+/// swallow misused named arguments.
+/// The arguments are munched one at a time, recording the named parameters seen so far
+/// in a list of slots (one per parameter, `_` if unseen).
+///
+/// With `collect`, the slots hold the values of the arguments instead (`(value)` once set),
+/// which are used by the call made once every argument is munched.
+///
+/// Named arguments are restricted by `named`: with [NamedArgs::Ordered], they are rejected
+/// once a later parameter has been seen, and with [NamedArgs::Disabled], they are always rejected.
+/// Unknown names are also resolved in the module of parameters at `params_mod_path` by rust-analyzer.
+///
+/// If the last parameter is `variadic`, bracketed arguments are passed on as tokens,
+/// so the call arms can match the variadic arguments inside them.
+#[allow(clippy::too_many_arguments)]
+fn create_munch_arms(
+    config: &MacroConfig,
+    self_path: &pm2::TokenStream,
+    macro_ident: &syn::Ident,
+    params_mod_path: &pm2::TokenStream,
+    param_names: &[Option<syn::Ident>],
+    output: &MacroType,
+    accepted_forms: &str,
+    variadic: bool,
+    collect: Option<Collect>,
+) -> Vec<pm2::TokenStream> {
+    let named = config.named;
+    let syntax = pattern_syntax(config, output);
+    let named_separator = syntax.named_separator();
+    let fragment = syntax.fragment();
+    let munch = syn::Ident::new(MUNCH_MARKER, Span::call_site());
+    let call = syn::Ident::new(CALL_MARKER, Span::call_site());
+    let unseen = quote! {_};
+    // set once a named argument is seen
+    let named_flag = quote! {named};
+
+    let leading = collect.as_ref().map(|c| c.leading).unwrap_or_default();
+    let slot_vars = (0..leading + param_names.len())
+        .map(slot_var)
+        .collect::<Vec<_>>();
+
+    // without collecting, the arguments are rewritten for the call arms, each followed by a comma.
+    // positional arguments are wrapped in parentheses, so no call arm can match a named one
+    let out = collect.is_none().then(|| quote! {[$($__defamed_out:tt)*]});
+    let out_with = |arg: pm2::TokenStream| {
+        collect
+            .is_none()
+            .then(|| quote! {[$($__defamed_out)* #arg,]})
+    };
+
+    // all arguments are valid, pass them on
+    let (end_slots, end_body, end_body_dot_dot) = match &collect {
+        Some(c) => (quote! {#(#slot_vars:tt)*}, c.call.clone(), c.call.clone()),
+        None => (
+            quote! {$($__defamed_s:tt)*},
+            quote! {#self_path!(@#call [$($__defamed_args)*] $($__defamed_out)*)},
+            quote! {#self_path!(@#call [$($__defamed_args)*] $($__defamed_out)* ..,)},
+        ),
+    };
+    let mut arms = vec![quote! {
+        (@#munch $__defamed_f:tt [#end_slots] [$($__defamed_args:tt)*] #out) => {
+            #end_body
+        }
+    }];
+
+    // the struct update syntax without a value ends the arguments
+    if matches!(output, MacroType::Struct) {
+        arms.push(quote! {
+            (@#munch $__defamed_f:tt [#end_slots] [$($__defamed_args:tt)*] #out ..) => {
+                #end_body_dot_dot
+            }
+        });
+    }
+
+    // named arguments are matched by the parameters they are accepted for
+    let num_params = param_names.len();
+    let param_names = match named {
+        NamedArgs::Disabled => vec![],
+        NamedArgs::Any | NamedArgs::Ordered => param_names.to_vec(),
+    };
+    // variadic arguments are matched and passed on as tokens
+    let bracketed = quote! {[$($__defamed_va:tt)*]};
+    let bracketed_value = quote! {[$($__defamed_va)*]};
+
+    for (idx, name) in param_names.iter().enumerate() {
+        let name = match name {
+            Some(n) => n,
+            None => continue,
+        };
+        let (value, value_out) = match variadic && idx + 1 == num_params {
+            true => (bracketed.clone(), bracketed_value.clone()),
+            false => (quote! {$__defamed_v:#fragment}, quote! {$__defamed_v}),
+        };
+        let idx = leading + idx;
+        let (seen, set) = match collect {
+            Some(_) => (quote! {($($__defamed_prev:tt)*)}, quote! {(#value_out)}),
+            None => (name.to_token_stream(), name.to_token_stream()),
+        };
+
+        // slots matching the current state, with this parameter's slot replaced
+        let slots_with = |slot: pm2::TokenStream| {
+            slot_vars
+                .iter()
+                .enumerate()
+                .map(|(i, var)| match i == idx {
+                    true => slot.clone(),
+                    false => quote! {#var:tt},
+                })
+                .collect::<Vec<_>>()
+        };
+        let slots_seen = slots_with(seen);
+        let slots_unseen = slots_with(unseen.clone());
+        // later parameters must not have been seen yet
+        let later_unseen = |i: usize| named == NamedArgs::Ordered && i > idx;
+        let slots_in_order = slot_vars
+            .iter()
+            .enumerate()
+            .map(|(i, var)| match i {
+                _ if i == idx || later_unseen(i) => unseen.clone(),
+                _ => quote! {#var:tt},
+            })
+            .collect::<Vec<_>>();
+        let slots_next = slot_vars.iter().enumerate().map(|(i, var)| match i {
+            _ if i == idx => set.clone(),
+            _ if later_unseen(i) => unseen.clone(),
+            _ => var.clone(),
+        });
+
+        let duplicate = format!("argument `{}` supplied twice to `{}!`", name, macro_ident);
+        let out_next = out_with(quote! {#name #named_separator #value_out});
+
+        arms.push(quote! {
+            (@#munch $__defamed_f:tt [#(#slots_seen)*] [$($__defamed_args:tt)*] #out #name #named_separator $($__defamed_rest:tt)*) => {
+                ::core::compile_error!(#duplicate)
+            }
+        });
+        arms.push(quote! {
+            (@#munch $__defamed_f:tt [#(#slots_in_order)*] [$($__defamed_args:tt)*] #out #name #named_separator #value $(, $($__defamed_rest:tt)*)?) => {
+                #self_path!(@#munch #named_flag [#(#slots_next)*] [$($__defamed_args)*] #out_next $($($__defamed_rest)*)?)
+            }
+        });
+
+        // otherwise reported as an unknown name
+        if syntax == PatternSyntax::TokenTree {
+            let not_tt = format!(
+                "argument `{}` passed to `{}!` is not a single token tree, wrap it in parentheses",
+                name, macro_ident
+            );
+
+            arms.push(quote! {
+                (@#munch $__defamed_f:tt [#(#slots_in_order)*] [$($__defamed_args:tt)*] #out #name #named_separator $($__defamed_rest:tt)*) => {
+                    ::core::compile_error!(#not_tt)
+                }
+            });
+        }
+
+        if named == NamedArgs::Ordered {
+            let out_of_order = format!(
+                "argument `{}` passed to `{}!` after a later parameter, named arguments must be passed in declaration order",
+                name, macro_ident
+            );
+
+            arms.push(quote! {
+                (@#munch $__defamed_f:tt [#(#slots_unseen)*] [$($__defamed_args:tt)*] #out #name #named_separator $($__defamed_rest:tt)*) => {
+                    ::core::compile_error!(#out_of_order)
+                }
+            });
+        }
+    }
+
+    let known_names = param_names.iter().flatten().collect::<Vec<_>>();
+
+    if !known_names.is_empty() {
+        let unexpected_cfgs = unexpected_cfgs_lints(config);
+        let unknown_arm = |pattern: pm2::TokenStream,
+                           unknown: pm2::TokenStream,
+                           message: pm2::TokenStream| {
+            quote! {
+                (@#munch $__defamed_f:tt [$($__defamed_s:tt)*] [$($__defamed_args:tt)*] #out #pattern #named_separator $($__defamed_rest:tt)*) => {{
+                    // rust-analyzer completes the parameters from the items of the module
+                    #[allow(#unexpected_cfgs)]
+                    const _: () = {
+                        #[cfg(rust_analyzer)]
+                        let _ = #params_mod_path::#unknown;
+                    };
+
+                    ::core::compile_error!(#message)
+                }}
+            }
+        };
+
+        // misspelled names are matched literally, as the macro cannot compare them with the known ones
+        for (misspelled, name) in suggest_close_names(&known_names) {
+            let message = format!(
+                "unknown argument `{}` passed to `{}!`, did you mean `{}`?",
+                misspelled, macro_ident, name
+            );
+
+            arms.push(unknown_arm(
+                quote! {#misspelled},
+                quote! {#misspelled},
+                quote! {#message},
+            ));
+        }
+
+        let prefix = "unknown argument `";
+        let expected = format!(
+            "` passed to `{}!`, expected one of: {}",
+            macro_ident,
+            known_names
+                .iter()
+                .map(|n| format!("`{}`", n))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        arms.push(unknown_arm(
+            quote! {$__defamed_unknown:ident},
+            quote! {$__defamed_unknown},
+            quote! {::core::concat!(#prefix, ::core::stringify!($__defamed_unknown), #expected)},
+        ));
+    }
+
+    if named == NamedArgs::Disabled {
+        let disabled_prefix = "named argument `";
+        let disabled_suffix = format!(
+            "` passed to `{}!`, which only accepts positional arguments",
+            macro_ident
+        );
+
+        arms.push(quote! {
+            (@#munch $__defamed_f:tt [$($__defamed_s:tt)*] [$($__defamed_args:tt)*] #out $__defamed_name:ident #named_separator $($__defamed_rest:tt)*) => {
+                ::core::compile_error!(::core::concat!(
+                    #disabled_prefix,
+                    ::core::stringify!($__defamed_name),
+                    #disabled_suffix
+                ))
+            }
+        });
+    }
+
+    let initial_slots = vec![unseen.clone(); slot_vars.len()];
+    let initial_out = collect.is_none().then(|| quote! {[]});
+    let positional_prefix = "positional argument `";
+    let positional_suffix = format!(
+        "` passed to `{}!` after a named argument, all positional arguments must come first",
+        macro_ident
+    );
+    let no_match = format!("no call form of `{}!` accepts `", macro_ident);
+    let accepted_forms = format!("`\n\n{}", accepted_forms);
+
+    let out_positional = out_with(quote! {($__defamed_v)});
+
+    // bracketed positional argument, which may be the variadic arguments
+    if variadic {
+        let out_bracketed = out_with(quote! {(#bracketed_value)});
+
+        arms.push(match collect {
+            Some(_) => quote! {
+                (@#munch #unseen [$(($($__defamed_set:tt)*))* #unseen $($__defamed_s:tt)*] [$($__defamed_args:tt)*] #bracketed $(, $($__defamed_rest:tt)*)?) => {
+                    #self_path!(@#munch #unseen [$(($($__defamed_set)*))* (#bracketed_value) $($__defamed_s)*] [$($__defamed_args)*] $($($__defamed_rest)*)?)
+                }
+            },
+            None => quote! {
+                (@#munch #unseen [$($__defamed_s:tt)*] [$($__defamed_args:tt)*] #out #bracketed $(, $($__defamed_rest:tt)*)?) => {
+                    #self_path!(@#munch #unseen [$($__defamed_s)*] [$($__defamed_args)*] #out_bracketed $($($__defamed_rest)*)?)
+                }
+            },
+        });
+    }
+
+    // positional argument, filling the first unset slot when collecting
+    arms.push(match collect {
+        Some(_) => quote! {
+            (@#munch #unseen [$(($($__defamed_set:tt)*))* #unseen $($__defamed_s:tt)*] [$($__defamed_args:tt)*] $__defamed_v:#fragment $(, $($__defamed_rest:tt)*)?) => {
+                #self_path!(@#munch #unseen [$(($($__defamed_set)*))* ($__defamed_v) $($__defamed_s)*] [$($__defamed_args)*] $($($__defamed_rest)*)?)
+            }
+        },
+        None => quote! {
+            (@#munch #unseen [$($__defamed_s:tt)*] [$($__defamed_args:tt)*] #out $__defamed_v:#fragment $(, $($__defamed_rest:tt)*)?) => {
+                #self_path!(@#munch #unseen [$($__defamed_s)*] [$($__defamed_args)*] #out_positional $($($__defamed_rest)*)?)
+            }
+        },
+    });
+
+    arms.extend([
+        quote! {
+            (@#munch #named_flag [$($__defamed_s:tt)*] [$($__defamed_args:tt)*] #out $__defamed_v:#fragment $(, $($__defamed_rest:tt)*)?) => {
+                ::core::compile_error!(::core::concat!(
+                    #positional_prefix,
+                    ::core::stringify!($__defamed_v),
+                    #positional_suffix
+                ))
+            }
+        },
+        // valid arguments that do not match any call form,
+        // internal invocations must not reach the entry point
+        quote! {
+            (@#munch $__defamed_f:tt [$($__defamed_s:tt)*] [$($__defamed_args:tt)*] #out $($__defamed_rest:tt)*) => {
+                ::core::compile_error!(::core::concat!(
+                    #no_match,
+                    ::core::stringify!($($__defamed_args)*),
+                    #accepted_forms
+                ))
+            }
+        },
+        quote! {
+            (@#call [$($__defamed_args:tt)*] $($__defamed_s:tt)*) => {
+                ::core::compile_error!(::core::concat!(
+                    #no_match,
+                    ::core::stringify!($($__defamed_args)*),
+                    #accepted_forms
+                ))
+            }
+        },
+    ]);
+
+    // entry point for all invocations, a named receiver is passed on as the first positional argument
+    match output {
+        MacroType::Method(_, _, Receiver::Named) => {
+            let missing = format!(
+                "missing receiver for `{}!`, pass it first as `self = <receiver>`",
+                macro_ident
+            );
+
+            arms.extend([
+                quote! {
+                    (self = $__defamed_recv:expr $(, $($__defamed_rest:tt)*)?) => {
+                        #self_path!(@#munch #unseen [#(#initial_slots)*] [self = $__defamed_recv $(, $($__defamed_rest)*)?] #initial_out $__defamed_recv $(, $($__defamed_rest)*)?)
+                    }
+                },
+                quote! {
+                    ($($__defamed_args:tt)*) => {
+                        ::core::compile_error!(#missing)
+                    }
+                },
+            ]);
+        }
+        _ => arms.push(quote! {
+            ($($__defamed_args:tt)*) => {
+                #self_path!(@#munch #unseen [#(#initial_slots)*] [$($__defamed_args)*] #initial_out $($__defamed_args)*)
+            }
+        }),
+    }
+
+    arms
+}
+
+/// Describe the parameters of an item and give an example of a valid invocation.
+fn describe_accepted_forms(
+    macro_ident: &syn::Ident,
+    output: &MacroType,
+    named: NamedArgs,
+    syntax: PatternSyntax,
+    params: &[DocInfo],
+) -> String {
+    let param_list = params
+        .iter()
+        .map(|p| format!("  {}", p))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let example = example_call(macro_ident, output, named, syntax, params);
+
+    match (param_list.is_empty(), output) {
+        (true, MacroType::Method(..)) => format!(
+            "`{}!` only takes the receiver\n\nexample: `{}`",
+            macro_ident, example
+        ),
+        (true, _) => format!("`{}!` does not take any parameters", macro_ident),
+        (false, _) => {
+            let restriction = match named {
+                NamedArgs::Any => "",
+                NamedArgs::Ordered => "\n\nnamed arguments must be passed in declaration order",
+                NamedArgs::Disabled => "\n\nonly positional arguments are accepted",
+            };
+
+            format!(
+                "parameters:\n{}\n\nexample: `{}`{}",
+                param_list, example, restriction
+            )
+        }
+    }
+}
+
+/// Documentation of the generated macro, with a table of how each parameter is passed and example calls.
+///
+/// The examples are compiled as doctests if the macro can be called through `doctest_path`.
+fn create_macro_doc(
+    config: &MacroConfig,
+    item_prefix: &str,
+    item_ident: &syn::Ident,
+    macro_ident: &syn::Ident,
+    output: &MacroType,
+    params: &[DocInfo],
+    doctest_path: Option<(Option<String>, String)>,
+) -> pm2::TokenStream {
+    let mut lines = vec![
+        format!(
+            "[`defamed`] wrapper for {}",
+            item_link(config, item_prefix, item_ident)
+        ),
+        String::new(),
+    ];
+
+    let syntax = pattern_syntax(config, output);
+    // tuple structs do not have named parameters
+    let named = match output {
+        MacroType::StructTuple => NamedArgs::Disabled,
+        _ => config.named,
+    };
+
+    if !params.is_empty() {
+        let passed = match named {
+            NamedArgs::Any => "positionally or by name",
+            NamedArgs::Ordered => "positionally, or by name in declaration order",
+            NamedArgs::Disabled => "positionally",
+        };
+        // pipes in closures would end the table cell
+        let escape = |text: &str| text.replace('|', "\\|");
+        let cell = |text: &str| format!("`{}`", escape(text));
+        // descriptions are only shown if any parameter has one
+        let described = params.iter().any(|p| p.doc.is_some());
+
+        lines.push("# Parameters".to_string());
+        lines.push(match described {
+            true => "| Parameter | Type | Passed | If omitted | Description |".to_string(),
+            false => "| Parameter | Type | Passed | If omitted |".to_string(),
+        });
+        lines.push("|---|---|---|---|".to_string() + if described { "---|" } else { "" });
+        lines.extend(params.iter().map(|p| {
+            let row = format!(
+                "| {} | {} | {} | {} |",
+                cell(&p.ident),
+                cell(&p.ty),
+                passed,
+                match &p.default_value {
+                    Some(val) => cell(val),
+                    None => "required".to_string(),
+                }
+            );
+
+            match described {
+                true => format!("{} {} |", row, escape(p.doc.as_deref().unwrap_or_default())),
+                false => row,
+            }
+        }));
+        lines.push(String::new());
+
+        if named != NamedArgs::Disabled {
+            lines.push(
+                "Positional arguments come first, in declaration order, followed by named arguments.\n"
+                    .to_string(),
+            );
+        }
+    }
+
+    // compiled examples pass placeholders that coerce to any type
+    let examples = match doctest_path {
+        Some((setup, path)) => {
+            let calls = doc_examples(
+                &format!("{}{}", path, macro_ident),
+                output,
+                named,
+                syntax,
+                params,
+                |_| match syntax {
+                    // the `tt` fragment only matches a single token tree
+                    PatternSyntax::TokenTree => format!("({})", placeholder(config)),
+                    PatternSyntax::Expr | PatternSyntax::StructLiteral => {
+                        placeholder(config).to_string()
+                    }
+                },
+            );
+
+            std::iter::once("```no_run".to_string())
+                .chain(setup)
+                .chain(calls.into_iter().map(|call| format!("let _ = {};", call)))
+                .collect::<Vec<_>>()
+        }
+        None => std::iter::once("```text".to_string())
+            .chain(doc_examples(
+                &macro_ident.to_string(),
+                output,
+                named,
+                syntax,
+                params,
+                |p| format!("<{}>", p.ident.trim_start_matches("r#")),
+            ))
+            .collect(),
+    };
+
+    lines.push("# Examples".to_string());
+    lines.extend(examples);
+    lines.push("```".to_string());
+
+    lines.iter().map(|line| quote! {#[doc = #line]}).collect()
+}
+
+/// Doc aliases of the macro: those of the item, the configured ones,
+/// and the name of the item if the macro is named differently.
+fn create_doc_aliases(
+    config: &MacroConfig,
+    item_attrs: &[syn::Attribute],
+    item_ident: &syn::Ident,
+    macro_ident: &syn::Ident,
+) -> pm2::TokenStream {
+    let item_aliases = item_attrs.iter().filter(|attr| match &attr.meta {
+        syn::Meta::List(list) => {
+            list.path.is_ident("doc")
+                && list
+                    .tokens
+                    .clone()
+                    .into_iter()
+                    .next()
+                    .is_some_and(|t| t.to_string() == "alias")
+        }
+        _ => false,
+    });
+
+    let item_name = item_ident.unraw().to_string();
+    let item_name = (item_ident != macro_ident && config.supports(crate::expand::DOC_ALIAS_MSRV))
+        .then_some(item_name);
+    let aliases = config
+        .aliases
+        .iter()
+        .map(|alias| alias.value())
+        .chain(item_name);
+
+    quote! {
+        #(#item_aliases)*
+        #(#[doc(alias = #aliases)])*
+    }
+}
+
+/// Lints allowed for the `rust_analyzer` cfg, which compilers predating the `unexpected_cfgs` lint report as unknown.
+fn unexpected_cfgs_lints(config: &MacroConfig) -> pm2::TokenStream {
+    match config.supports(crate::expand::UNEXPECTED_CFGS_MSRV) {
+        true => quote! {unexpected_cfgs},
+        false => quote! {unknown_lints, unexpected_cfgs},
+    }
+}
+
+/// Argument coercing to any type, for calls that are compiled but never run.
+fn placeholder(config: &MacroConfig) -> &'static str {
+    match config.supports(crate::expand::TODO_MSRV) {
+        true => "todo!()",
+        false => "unimplemented!()",
+    }
+}
+
+/// Intra-doc link from the macro to its item, with the disambiguating `item_prefix`.
+///
+/// `#[macro_use]` macros are exported at the crate root, so the item is linked by its full path.
+fn item_link(config: &MacroConfig, item_prefix: &str, item_ident: &syn::Ident) -> String {
+    let module = config
+        .path
+        .as_ref()
+        .filter(|p| config.macro_use && !p.is_ident(crate::expand::ROOT_VISIBILITY_IDENT));
+
+    match module {
+        Some(path) => {
+            let module = format!(
+                "@crate::{}::",
+                path.to_token_stream().to_string().replace(' ', "")
+            );
+
+            format!(
+                "[`{}`]({}{})",
+                item_ident.unraw(),
+                item_prefix.replacen('@', &module, 1),
+                item_ident.unraw()
+            )
+        }
+        None => format!("[`{}{}`]", item_prefix, item_ident.unraw()),
+    }
+}
+
+/// Intra-doc link to the macro generated for an item, from the module of the item.
+///
+/// `#[macro_use]` macros are only exported at the crate root.
+pub fn macro_link(config: &MacroConfig, macro_ident: &syn::Ident) -> String {
+    match config.macro_use {
+        true => format!("[`{0}!`](crate::{0}!)", macro_ident),
+        false => format!("[`{}!`]", macro_ident),
+    }
+}
+
+/// Path to the macro from doctests of the crate being built, if its examples can be compiled,
+/// along with any hidden lines the examples need to reach it.
+///
+/// Only public macros are reachable from doctests, and methods cannot be called without a receiver.
+fn doctest_path(
+    config: &MacroConfig,
+    vis: &Visibility,
+    output: &MacroType,
+) -> Option<(Option<String>, String)> {
+    if !config.doctest
+        || !matches!(vis, Visibility::Public(_))
+        || matches!(output, MacroType::Method(..))
+    {
+        return None;
+    }
+
+    let krate = config.crate_name()?;
+
+    // `#[macro_use]` macros call themselves by their bare name
+    if config.macro_use {
+        return Some((
+            Some(format!("# #[macro_use] extern crate {};", krate)),
+            String::new(),
+        ));
+    }
+
+    let module = config
+        .path
+        .as_ref()
+        .filter(|p| !p.is_ident(crate::expand::ROOT_VISIBILITY_IDENT))
+        .map(|p| format!("::{}", p.to_token_stream().to_string().replace(' ', "")))
+        .unwrap_or_default();
+
+    Some((None, format!("{}{}::", krate, module)))
+}
+
+/// A named argument in the documentation, written with `syntax`.
+fn named_arg(syntax: PatternSyntax, name: &str, value: &str) -> String {
+    match syntax {
+        PatternSyntax::StructLiteral => format!("{}: {}", name, value),
+        PatternSyntax::Expr | PatternSyntax::TokenTree => format!("{} = {}", name, value),
+    }
+}
+
+/// Example invocations for the macro documentation, with each argument rendered by `arg`.
+///
+/// Required arguments are passed positionally, then every argument is named except the first default.
+fn doc_examples(
+    macro_path: &str,
+    output: &MacroType,
+    named: NamedArgs,
+    syntax: PatternSyntax,
+    params: &[DocInfo],
+    arg: impl Fn(&DocInfo) -> String,
+) -> Vec<String> {
+    let receiver = output.receiver_arg("<self>");
+    let first_default = params.iter().position(|p| p.default_value.is_some());
+
+    let positional = receiver
+        .iter()
+        .cloned()
+        .chain(
+            params
+                .iter()
+                .filter(|p| p.default_value.is_none())
+                .map(&arg),
+        )
+        .collect::<Vec<_>>();
+
+    let named_args = match (named, output) {
+        (NamedArgs::Disabled, _) | (_, MacroType::StructTuple) => None,
+        _ => Some(
+            receiver
+                .into_iter()
+                .chain(
+                    params
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| Some(*idx) != first_default)
+                        .map(|(_, p)| named_arg(syntax, &p.ident, &arg(p))),
+                )
+                .collect::<Vec<_>>(),
+        ),
+    };
+
+    let mut examples = std::iter::once(positional)
+        .chain(named_args)
+        .map(|args| match output {
+            // omitted fields are filled in with struct update syntax
+            MacroType::Struct => {
+                let update = first_default.map(|_| "..".to_string());
+                let args = args.into_iter().chain(update).collect::<Vec<_>>();
+
+                format!("{}! {{ {} }}", macro_path, args.join(", "))
+            }
+            _ => format!("{}!({})", macro_path, args.join(", ")),
+        })
+        .collect::<Vec<_>>();
+    examples.dedup();
+
+    examples
+}
+
+/// An example of a valid invocation, passing every required parameter and the first default one.
+fn example_call(
+    macro_ident: &syn::Ident,
+    output: &MacroType,
+    named: NamedArgs,
+    syntax: PatternSyntax,
+    params: &[DocInfo],
+) -> String {
+    let (required, defaults): (Vec<_>, Vec<_>) =
+        params.iter().partition(|p| p.default_value.is_none());
+    let placeholder = |p: &DocInfo| format!("<{}>", p.ident.trim_start_matches("r#"));
+
+    // required params as usual, overriding the first default
+    match output {
+        MacroType::Function | MacroType::Method(..) | MacroType::Associated(..) => {
+            let args = output
+                .receiver_arg("<self>")
+                .into_iter()
+                .chain(required.iter().map(|p| placeholder(p)))
+                .chain(defaults.first().map(|p| match named {
+                    NamedArgs::Disabled => placeholder(p),
+                    NamedArgs::Any | NamedArgs::Ordered => {
+                        named_arg(syntax, &p.ident, &placeholder(p))
+                    }
+                }))
+                .collect::<Vec<_>>();
+
+            format!("{}!({})", macro_ident, args.join(", "))
+        }
+        MacroType::Struct => {
+            let args = required
+                .iter()
+                .chain(defaults.first())
+                .map(|p| match named {
+                    NamedArgs::Disabled => placeholder(p),
+                    NamedArgs::Any | NamedArgs::Ordered => {
+                        named_arg(syntax, &p.ident, &placeholder(p))
+                    }
+                })
+                .chain((defaults.len() > 1).then(|| "..".to_string()))
+                .collect::<Vec<_>>();
+
+            format!("{}! {{ {} }}", macro_ident, args.join(", "))
+        }
+        MacroType::StructTuple => {
+            let args = required
+                .iter()
+                .chain(defaults.first())
+                .map(|p| placeholder(p))
+                .collect::<Vec<_>>();
+
+            format!("{}!({})", macro_ident, args.join(", "))
+        }
+    }
+}
+
+/// Misspellings of the known names that are a single edit away from one of them,
+/// such as a missing, doubled or swapped character, along with the name they are closest to.
+///
+/// The names are compared without their `r#` prefix. A misspelling close to several names
+/// suggests the first one, and misspellings that are known names themselves are left out.
+fn suggest_close_names(known_names: &[&syn::Ident]) -> Vec<(syn::Ident, syn::Ident)> {
+    let unraw = known_names
+        .iter()
+        .map(|n| n.unraw().to_string())
+        .collect::<Vec<_>>();
+    let mut seen = unraw.iter().cloned().collect::<HashSet<_>>();
+    let mut suggestions = vec![];
+
+    for (name, known) in unraw.iter().zip(known_names) {
+        let chars = name.chars().collect::<Vec<_>>();
+        let edit = |f: &dyn Fn(&mut Vec<char>)| {
+            let mut edited = chars.clone();
+            f(&mut edited);
+            edited.into_iter().collect::<String>()
+        };
+
+        let missing = (0..chars.len()).map(|idx| {
+            edit(&|c| {
+                c.remove(idx);
+            })
+        });
+        let doubled = (0..chars.len()).map(|idx| edit(&|c| c.insert(idx, c[idx])));
+        let swapped = (1..chars.len()).map(|idx| edit(&|c| c.swap(idx - 1, idx)));
+
+        for misspelled in missing.chain(doubled).chain(swapped) {
+            let is_ident = syn::parse_str::<syn::Ident>(&misspelled).is_ok();
+
+            if is_ident && seen.insert(misspelled.clone()) {
+                suggestions.push((
+                    syn::Ident::new(&misspelled, Span::call_site()),
+                    (*known).clone(),
+                ));
+            }
+        }
+    }
+
+    suggestions
+}
+
+/// Describe the arguments accepted by a call arm, such as `named: b, a; defaults used: c`.
+///
+/// Arms of the canonical strategy are matched in declaration order,
+/// so their arguments are only described as supplied.
+fn describe_form<T>(form: &[PermutedItem<T>], canonical: bool) -> String
+where
+    T: Clone,
+    PermutedItem<T>: ToDocInfo,
+{
+    let names = |kind: fn(&PermutedItem<T>) -> bool| {
+        form.iter()
+            .filter(|p| kind(p))
+            .map(|p| p.to_doc_info().ident)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let kinds = [
+        (
+            match canonical {
+                true => "supplied",
+                false => "positional",
+            },
+            names(|p| matches!(p, PermutedItem::Positional(_))),
+        ),
+        ("named", names(|p| matches!(p, PermutedItem::Named(_)))),
+        (
+            "defaults used",
+            names(|p| matches!(p, PermutedItem::Default(_))),
+        ),
+    ];
+
+    let description = kinds
+        .iter()
+        .filter(|(_, names)| !names.is_empty())
+        .map(|(kind, names)| format!("{}: {}", kind, names))
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    match description.is_empty() {
+        true => "no arguments".to_string(),
+        false => description,
+    }
+}
+
+/// Create the macro pattern signature for a given vector of parameters,
+/// matching the arguments as rewritten by the munch arms.
+///
+/// Every argument is followed by a comma, and positional arguments are wrapped in parentheses
+/// so that they cannot match a named argument.
+/// Arms are then mutually exclusive, and can be ordered freely.
+fn create_macro_signature<T>(
+    backend: &impl Backend<PermutedItem<T>>,
+    params: &[PermutedItem<T>],
+) -> pm2::TokenStream
+where
+    T: Clone,
+    PermutedItem<T>: ToMacroPattern,
+{
+    params
+        .iter()
+        .filter_map(|p| {
+            let pattern = backend.item_pattern(p)?;
+            Some(match p {
+                PermutedItem::Positional(_) => quote! {(#pattern),},
+                _ => quote! {#pattern,},
+            })
+        })
+        .collect()
+}
+
+/// Create the pattern matching sorted arguments, one slot per parameter in declaration order.
+///
+/// Supplied arguments are matched in parentheses and omitted defaults as `_`.
+fn create_slot_signature<P: ToMacroPattern>(
+    backend: &impl Backend<P>,
+    params: &[P],
+) -> pm2::TokenStream {
+    params
+        .iter()
+        .map(|p| match backend.item_pattern(p) {
+            Some(pattern) => quote! {(#pattern)},
+            None => quote! {_},
+        })
+        .collect()
+}
+
+/// Uses the reference pattern to order the parameters in the function call.
+///
+/// All elements in `reference` must have an equal (by [PartialEq]) in `params`.
+///
+/// If there are more elements in `params` than in `reference`, the extra elements are appended to the end.
+///
+/// Returns the turbofish of the call along with its arguments,
+/// or a description of the mismatch if `params` does not cover `reference`.
+fn create_func_call_signature<P>(
+    reference: &[P],
+    params: &[P],
+) -> Result<(pm2::TokenStream, pm2::TokenStream), String>
+where
+    P: ToMacroPattern + PartialEq + Debug,
+{
+    if reference.len() > params.len() {
+        return Err(format!(
+            "reference must have at least as many elements as params\nref: {:?}\nparams: {:?}",
+            reference, params
+        ));
+    }
+
+    let ordered = reference
+        .iter()
+        .map(|r| {
+            params
+                .iter()
+                .find(|item| *item == r)
+                .ok_or_else(|| format!("parameter must exist: {:?}", r))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // extra elements are appended in order
+    let (turbofish, args) = split_generic_args(
+        ordered
+            .into_iter()
+            .chain(&params[reference.len()..])
+            .map(|p| (p.generic_position(), p.to_func_call_pattern())),
+    );
+    let seq: Punctuated<pm2::TokenStream, Comma> = args.into_iter().collect();
+
+    Ok((turbofish, seq.to_token_stream()))
+}
+
+/// Split the arguments of a call, along with their position among its generic arguments (if any),
+/// into the turbofish of the call and its argument list.
+///
+/// Generic arguments are wrapped in braces, and the generic parameters without one are inferred.
+fn split_generic_args(
+    args: impl IntoIterator<Item = (Option<usize>, pm2::TokenStream)>,
+) -> (pm2::TokenStream, Vec<pm2::TokenStream>) {
+    let mut generics = vec![];
+    let mut values = vec![];
+
+    for (position, arg) in args {
+        match position {
+            Some(position) => {
+                if generics.len() <= position {
+                    generics.resize(position + 1, quote! {_});
+                }
+                generics[position] = quote! {{#arg}};
+            }
+            None => values.push(arg),
+        }
+    }
+
+    let turbofish = match generics.is_empty() {
+        true => quote! {},
+        false => quote! {::<#(#generics),*>},
+    };
+
+    (turbofish, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("Widget"), "widget");
+        assert_eq!(to_snake_case("DefaultStruct"), "default_struct");
+        assert_eq!(to_snake_case("HTTPServer"), "http_server");
+        assert_eq!(to_snake_case("Vec3Builder"), "vec3_builder");
+        assert_eq!(to_snake_case("already_snake"), "already_snake");
+    }
+
+    #[test]
+    fn test_to_upper_camel_case() {
+        assert_eq!(to_upper_camel_case("resize"), "Resize");
+        assert_eq!(to_upper_camel_case("resize_with_scale"), "ResizeWithScale");
+        assert_eq!(to_upper_camel_case("_private"), "Private");
+        assert_eq!(to_upper_camel_case("vec3_add"), "Vec3Add");
+    }
+
+    #[test]
+    fn test_awaits() {
+        assert!(awaits(quote! {fetch().await}));
+        assert!(awaits(quote! {Some(fetch().await?)}));
+        assert!(!awaits(quote! {fetch()}));
+        assert!(!awaits(quote! {r#await}));
+    }
+
+    #[test]
+    fn test_suggest_close_names() {
+        let names = [
+            syn::Ident::new("lhs", Span::call_site()),
+            syn::Ident::new("rhs", Span::call_site()),
+            syn::Ident::new_raw("type", Span::call_site()),
+        ];
+        let suggestions = suggest_close_names(&names.iter().collect::<Vec<_>>())
+            .into_iter()
+            .map(|(misspelled, name)| (misspelled.to_string(), name.to_string()))
+            .collect::<HashMap<_, _>>();
+
+        assert_eq!(suggestions["rsh"], "rhs");
+        assert_eq!(suggestions["lhhs"], "lhs");
+        assert_eq!(suggestions["hs"], "lhs");
+        assert_eq!(suggestions["tpye"], "r#type");
+        assert_eq!(suggestions["typee"], "r#type");
+        // known names and keywords are never misspellings
+        assert!(!suggestions.contains_key("rhs"));
+        assert!(suggestions
+            .keys()
+            .all(|m| syn::parse_str::<syn::Ident>(m).is_ok()));
+    }
+}
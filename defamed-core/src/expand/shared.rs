@@ -0,0 +1,31 @@
+//! Constants shared by every module of the expansion.
+
+/// Identifier for public macros defined in the root module
+pub const ROOT_VISIBILITY_IDENT: &str = "crate";
+
+/// Maximum number of call forms generated for a single item, unless configured otherwise
+pub const DEFAULT_MAX_ARMS: usize = 100_000;
+
+/// Number of call forms above which items without a configured strategy use the muncher strategy
+pub const AUTO_MUNCHER_FORMS: usize = 1_000;
+
+/// Number of statements up to which the body of a function is inlined with `auto_inline`
+pub const AUTO_INLINE_STMTS: usize = 3;
+
+/// Oldest Rust version the generated macros compile with, which match optional arguments with `$(...)?`
+pub const OLDEST_MSRV: (u32, u32) = (1, 32);
+
+/// Rust version stabilizing `todo!()`, the placeholder argument of self-tests and documentation examples
+pub const TODO_MSRV: (u32, u32) = (1, 40);
+
+/// Rust version stabilizing `#[doc(alias)]`, used to find macros by the name of their item
+pub const DOC_ALIAS_MSRV: (u32, u32) = (1, 48);
+
+/// Rust version stabilizing inline `const` blocks, which evaluate defaults with `const_defaults`
+pub const INLINE_CONST_MSRV: (u32, u32) = (1, 79);
+
+/// Rust version stabilizing `#[collapse_debuginfo]` on macros
+pub const COLLAPSE_DEBUGINFO_MSRV: (u32, u32) = (1, 79);
+
+/// Rust version introducing the `unexpected_cfgs` lint, which older compilers report as unknown
+pub const UNEXPECTED_CFGS_MSRV: (u32, u32) = (1, 80);
@@ -0,0 +1,202 @@
+//! Machine-readable signatures, written by setting `DEFAMED_SIGNATURES` for the build.
+//!
+//! Every annotated item is described in a JSON file of its own, for doc generators and binding tools.
+//! The variable is the directory the files are written to, or `1` for the `defamed` directory
+//! in `OUT_DIR`, which is only set for crates with a build script.
+
+use std::path::PathBuf;
+
+use quote::ToTokens;
+use syn::ext::IdentExt;
+
+use crate::traits::DocInfo;
+
+/// Environment variable enabling the signatures
+const SIGNATURES_VAR: &str = "DEFAMED_SIGNATURES";
+
+/// Description of an annotated item and its macro.
+pub struct Signature<'a> {
+    /// `function`, `method`, `associated_function`, `struct` or `tuple_struct`
+    pub kind: &'static str,
+    pub item_ident: &'a syn::Ident,
+    pub macro_ident: &'a syn::Ident,
+    /// Path to the module containing the item, as given in the attribute
+    pub path: Option<&'a syn::Path>,
+    pub params: &'a [DocInfo],
+}
+
+impl Signature<'_> {
+    /// Write the signature to its own file, if signatures are enabled.
+    ///
+    /// Failures are reported on stderr, without failing the build.
+    pub fn write(&self) {
+        let dir = match signatures_dir() {
+            Some(dir) => dir,
+            None => return,
+        };
+        let krate = std::env::var("CARGO_CRATE_NAME").unwrap_or_default();
+        let file = dir.join(self.file_name(&krate));
+
+        let res =
+            std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&file, self.to_json(&krate)));
+        if let Err(e) = res {
+            eprintln!(
+                "defamed: failed to write the signature of `{}!` to {}: {}",
+                self.macro_ident,
+                file.display(),
+                e
+            );
+        }
+    }
+
+    /// Name of the file, see [file_stem].
+    fn file_name(&self, krate: &str) -> String {
+        format!("{}.json", file_stem(krate, self.path, self.macro_ident))
+    }
+
+    fn to_json(&self, krate: &str) -> String {
+        let path = self
+            .path
+            .map(|p| json_str(&p.to_token_stream().to_string().replace(' ', "")))
+            .unwrap_or_else(|| "null".to_string());
+        let params = self
+            .params
+            .iter()
+            .map(|p| {
+                let optional = |value: &Option<String>| {
+                    value
+                        .as_deref()
+                        .map(json_str)
+                        .unwrap_or_else(|| "null".to_string())
+                };
+
+                format!(
+                    "    {{\"name\": {}, \"type\": {}, \"default\": {}, \"doc\": {}}}",
+                    json_str(&p.ident),
+                    json_str(&p.ty),
+                    optional(&p.default_value),
+                    optional(&p.doc)
+                )
+            })
+            .collect::<Vec<_>>();
+        let params = match params.is_empty() {
+            true => "[]".to_string(),
+            false => format!("[\n{}\n  ]", params.join(",\n")),
+        };
+
+        format!(
+            "{{\n  \"crate\": {},\n  \"path\": {},\n  \"kind\": {},\n  \"item\": {},\n  \"macro\": {},\n  \"params\": {}\n}}\n",
+            json_str(krate),
+            path,
+            json_str(self.kind),
+            json_str(&self.item_ident.unraw().to_string()),
+            json_str(&self.macro_ident.unraw().to_string()),
+            params
+        )
+    }
+}
+
+/// Name of the files describing the macro, without an extension.
+///
+/// Unique to the macro within the crate, as long as private items of different modules are named differently.
+pub fn file_stem(krate: &str, path: Option<&syn::Path>, macro_ident: &syn::Ident) -> String {
+    let module = path
+        .map(|p| {
+            p.segments
+                .iter()
+                .map(|seg| format!("{}.", seg.ident.unraw()))
+                .collect::<String>()
+        })
+        .unwrap_or_default();
+
+    format!("{}.{}{}", krate, module, macro_ident.unraw())
+}
+
+/// Directory the signatures are written to, if enabled.
+fn signatures_dir() -> Option<PathBuf> {
+    let var = std::env::var_os(SIGNATURES_VAR).filter(|var| !var.is_empty())?;
+
+    match var == "1" {
+        true => std::env::var_os("OUT_DIR").map(|out| PathBuf::from(out).join("defamed")),
+        false => Some(PathBuf::from(var)),
+    }
+}
+
+/// A JSON string literal.
+fn json_str(value: &str) -> String {
+    let mut res = String::with_capacity(value.len() + 2);
+    res.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c.is_control() => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+
+    res.push('"');
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proc_macro2::Span;
+
+    #[test]
+    fn test_json_str() {
+        assert_eq!(json_str("plain"), "\"plain\"");
+        assert_eq!(json_str("\"a\"\\b\n"), "\"\\\"a\\\"\\\\b\\n\"");
+        assert_eq!(json_str("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn test_to_json() {
+        let item_ident = syn::Ident::new("add", Span::call_site());
+        let path: syn::Path = syn::parse_quote!(inner::math);
+        let params = [
+            DocInfo {
+                ident: "lhs".to_string(),
+                ty: "i32".to_string(),
+                default_value: None,
+                doc: None,
+            },
+            DocInfo {
+                ident: "rhs".to_string(),
+                ty: "i32".to_string(),
+                default_value: Some("1".to_string()),
+                doc: Some("the \"other\" side".to_string()),
+            },
+        ];
+        let signature = Signature {
+            kind: "function",
+            item_ident: &item_ident,
+            macro_ident: &item_ident,
+            path: Some(&path),
+            params: &params,
+        };
+
+        assert_eq!(signature.file_name("lib"), "lib.inner.math.add.json");
+        assert_eq!(
+            signature.to_json("lib"),
+            r#"{
+  "crate": "lib",
+  "path": "inner::math",
+  "kind": "function",
+  "item": "add",
+  "macro": "add",
+  "params": [
+    {"name": "lhs", "type": "i32", "default": null, "doc": null},
+    {"name": "rhs", "type": "i32", "default": "1", "doc": "the \"other\" side"}
+  ]
+}
+"#
+        );
+    }
+}
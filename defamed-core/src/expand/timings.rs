@@ -0,0 +1,76 @@
+//! Compile-time telemetry, enabled by setting `DEFAMED_TIMINGS=1` for the build.
+//!
+//! The time spent in each phase of generating a macro is printed to stderr with its number of arms,
+//! to find the items responsible for slow builds.
+
+use std::time::{Duration, Instant};
+
+/// Environment variable enabling the timings
+const TIMINGS_VAR: &str = "DEFAMED_TIMINGS";
+
+/// Time spent in each phase of generating the macro for a single item.
+pub struct Timings {
+    enabled: bool,
+    last: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    /// Start timing the first phase, if timings are enabled.
+    pub fn start() -> Self {
+        Self {
+            enabled: std::env::var_os(TIMINGS_VAR).is_some_and(|var| var == "1"),
+            last: Instant::now(),
+            phases: vec![],
+        }
+    }
+
+    /// End the current phase and start the next one.
+    pub fn phase(&mut self, name: &'static str) {
+        if self.enabled {
+            let now = Instant::now();
+            self.phases.push((name, now - self.last));
+            self.last = now;
+        }
+    }
+
+    /// End the last phase and print the timings of `macro_ident`.
+    pub fn report(mut self, macro_ident: &syn::Ident, arms: usize) {
+        if self.enabled {
+            self.phase("emit");
+            eprintln!("{}", self.summary(macro_ident, arms));
+        }
+    }
+
+    fn summary(&self, macro_ident: &syn::Ident, arms: usize) -> String {
+        let phases = self
+            .phases
+            .iter()
+            .map(|(name, duration)| format!("{} {:?}", name, duration))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("defamed: `{}!`: {}, {} arms", macro_ident, phases, arms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let mut timings = Timings {
+            enabled: true,
+            ..Timings::start()
+        };
+        timings.phase("parse");
+        timings.phase("permute");
+
+        let ident = syn::Ident::new("item", proc_macro2::Span::call_site());
+        let summary = timings.summary(&ident, 3);
+        assert!(summary.starts_with("defamed: `item!`: parse "));
+        assert!(summary.contains(", permute "));
+        assert!(summary.ends_with(", 3 arms"));
+    }
+}
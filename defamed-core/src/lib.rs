@@ -0,0 +1,63 @@
+//! Building blocks of the macros generated by [`defamed`](https://docs.rs/defamed).
+//!
+//! `defamed` can only be compiled as a proc-macro crate, which cannot export anything but its macros.
+//! The pieces that other proc-macro crates can reuse live here instead,
+//! such as the [`Backend`](backend::Backend) trait its code generation strategies implement.
+//!
+//! The named and default argument model is in [`permute`]:
+//! [`FunctionParams`](permute::params::FunctionParams) and [`StructFields`](permute::fields::StructFields)
+//! parse the parameters of an item along with their `#[def]` attributes,
+//! and the functions of the module enumerate every order those items can be passed in.
+
+#![warn(missing_docs)]
+#![cfg_attr(feature = "proc_macro_diagnostic", feature(proc_macro_diagnostic))]
+
+// outputs of the attribute are converted for the compiler, which also reports diagnostics on nightly
+extern crate proc_macro;
+
+pub mod backend;
+#[doc(hidden)]
+pub mod expand;
+pub mod permute;
+pub mod traits;
+
+/// "Helper" attribute for annotating function parameters
+pub const DEFAULT_HELPER_ATTR: &str = "def";
+
+/// Name of this crate, used to namespace helper attributes
+pub const CRATE_IDENT: &str = "defamed";
+
+/// Checks if an attribute is the helper attribute with the given name, either bare (`#[def]`)
+/// or namespaced by this crate (`#[defamed::def]`).
+pub fn is_helper_attr(attr: &syn::Attribute, helper: &syn::Ident) -> bool {
+    let segments = &attr.path().segments;
+
+    match segments.len() {
+        1 => segments[0].ident == *helper && attr.path().leading_colon.is_none(),
+        2 => segments[0].ident == CRATE_IDENT && segments[1].ident == *helper,
+        _ => false,
+    }
+}
+
+/// Collect all successful results, or all errors combined into one.
+///
+/// Used to report every invalid item in a single compilation instead of bailing on the first.
+pub fn collect_results<T>(
+    results: impl IntoIterator<Item = Result<T, syn::Error>>,
+) -> Result<Vec<T>, syn::Error> {
+    let mut items = Vec::new();
+    let mut error: Option<syn::Error> = None;
+
+    for res in results {
+        match (res, error.as_mut()) {
+            (Ok(item), _) => items.push(item),
+            (Err(e), Some(err)) => err.combine(e),
+            (Err(e), None) => error = Some(e),
+        }
+    }
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(items),
+    }
+}
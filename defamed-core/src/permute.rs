@@ -0,0 +1,1399 @@
+//! Permutation generation for positional and named parameters.
+//!
+//! Items are parsed into [`FunctionParams`](params::FunctionParams) or
+//! [`StructFields`](fields::StructFields), split into required and default items,
+//! and passed to the functions of this module to enumerate the forms a call can take.
+//! The functions are generic over the items, which only need to implement [`Permutable`] to be split.
+//! Every item of a form is a [`PermutedItem`], passed either by position or by name.
+//!
+//! ```
+//! use defamed_core::permute::{self, params::FunctionParams, PermutedItem};
+//!
+//! let sig: syn::Signature = syn::parse_quote!(fn f(base: u32, #[def(1)] offset: u32));
+//! let helper = syn::Ident::new(defamed_core::DEFAULT_HELPER_ATTR, proc_macro2::Span::call_site());
+//! let params = FunctionParams::from_punctuated(sig.inputs, &helper).unwrap();
+//!
+//! let (required, default) = permute::split_defaults(params.params);
+//! let forms = permute::permute_forms(required, default).collect::<Vec<_>>();
+//! assert_eq!(forms.len(), permute::permute_count(1, 1));
+//!
+//! // `f(base = .., offset = ..)` is accepted
+//! assert!(forms[0]
+//!     .iter()
+//!     .all(|item| matches!(item, PermutedItem::Named(_))));
+//! ```
+// #![allow(unused)]
+
+use std::{
+    fmt::{self, Debug},
+    rc::Rc,
+};
+
+use quote::ToTokens;
+use syn::spanned::Spanned;
+
+use crate::traits::{ToDocInfo, ToMacroPattern};
+
+pub mod fields;
+pub mod params;
+
+/// Suffix appended to item names to form the metavariables that capture their values
+const METAVAR_SUFFIX: &str = "_val";
+
+/// Metavariable name for an item, before checking for collisions.
+///
+/// Raw identifiers lose their `r#` prefix, which cannot appear inside an identifier.
+pub fn metavar_name(name: &str) -> String {
+    format!(
+        "{}{}",
+        name.strip_prefix("r#").unwrap_or(name),
+        METAVAR_SUFFIX
+    )
+}
+
+/// Metavariable identifier, pointing at `span` in diagnostics.
+///
+/// The item may come from a `macro_rules!` template, so the identifier always resolves
+/// at the call site, where both the pattern and the body of the generated macro are written.
+pub fn metavar_ident(metavar: &str, span: proc_macro2::Span) -> syn::Ident {
+    syn::Ident::new(metavar, proc_macro2::Span::call_site().located_at(span))
+}
+
+/// Assign a metavariable name to each item name, in order.
+///
+/// Each item is captured by `{name}_val`. If that name is reserved or already taken,
+/// the first free numeric suffix is appended, which never matches another item's `_val` name.
+pub fn unique_metavars(names: &[String], reserved: &[&str]) -> Vec<String> {
+    let natural = names
+        .iter()
+        .map(|name| metavar_name(name))
+        .collect::<Vec<_>>();
+
+    let mut taken = reserved
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<std::collections::HashSet<_>>();
+
+    let assigned = natural
+        .iter()
+        .map(|var| taken.insert(var.clone()).then(|| var.clone()))
+        .collect::<Vec<_>>();
+
+    assigned
+        .into_iter()
+        .zip(natural.iter())
+        .map(|(assigned, var)| {
+            assigned.unwrap_or_else(|| {
+                let unique = (1..)
+                    .map(|n| format!("{}_{}", var, n))
+                    .find(|candidate| !taken.contains(candidate))
+                    .expect("there are infinitely many candidates");
+                taken.insert(unique.clone());
+
+                unique
+            })
+        })
+        .collect()
+}
+
+/// Data from the `#[def]` attribute
+#[derive(Clone, PartialEq)]
+pub enum ParamAttr {
+    /// No helper attribute
+    None,
+    /// Use default trait for initialization
+    Default,
+    /// Use const expr for initialization
+    Value(syn::Expr),
+}
+
+impl ParamAttr {
+    /// The expression used in place of a missing argument, if any.
+    pub fn to_value_tokens(&self) -> Option<proc_macro2::TokenStream> {
+        match self {
+            Self::None => None,
+            Self::Default => Some(quote::quote! {core::default::Default::default()}),
+            Self::Value(v) => Some(v.to_token_stream()),
+        }
+    }
+
+    /// Parse the helper attribute among the attributes of a parameter or field,
+    /// along with the options given next to the default value.
+    ///
+    /// `item` describes the parameter or field in errors.
+    pub fn from_attrs(
+        attrs: &[syn::Attribute],
+        helper: &syn::Ident,
+        item: &str,
+    ) -> Result<(Self, HelperOptions), syn::Error> {
+        let mut helpers = attrs.iter().filter(|a| crate::is_helper_attr(a, helper));
+
+        let attr = match helpers.next() {
+            Some(attr) => attr,
+            None => return Ok((Self::None, HelperOptions::default())),
+        };
+
+        if let Some(conflicting) = helpers.next() {
+            let mut err = syn::Error::new_spanned(
+                conflicting,
+                format!("conflicting `#[{}]` attributes on {}", helper, item),
+            );
+            err.combine(syn::Error::new_spanned(
+                attr,
+                format!("first `#[{}]` attribute here", helper),
+            ));
+
+            return Err(err);
+        }
+
+        match &attr.meta {
+            syn::Meta::Path(_) => Ok((Self::Default, HelperOptions::default())),
+            syn::Meta::List(l) if l.tokens.is_empty() => Err(syn::Error::new(
+                l.span(),
+                "expected at least 1 item in metalist",
+            )),
+            syn::Meta::List(l) => l.parse_args_with(Self::parse_list),
+            syn::Meta::NameValue(nv) => Err(syn::Error::new(
+                nv.span(),
+                format!(
+                    "name-values are not supported. Use #[{}] or #[{}(CONST_EXPRESSION)] instead.",
+                    helper, helper
+                ),
+            )),
+        }
+    }
+}
+
+/// Check that a default value can be evaluated in an inline `const` block, for `const_defaults`.
+///
+/// `Default::default()` is not a const expression, so defaults without a value are rejected up front.
+fn check_const_default(
+    default_value: &ParamAttr,
+    span: proc_macro2::Span,
+    name: &str,
+) -> Result<(), syn::Error> {
+    match default_value {
+        ParamAttr::Default => Err(syn::Error::new(
+            span,
+            format!(
+                "`const_defaults` requires a const value for `{}`, \
+                as `Default::default()` is not a const expression",
+                name
+            ),
+        )),
+        ParamAttr::None | ParamAttr::Value(_) => Ok(()),
+    }
+}
+
+/// Options given in the helper attribute after the default value.
+#[derive(Clone, Default)]
+pub struct HelperOptions {
+    /// Description given by `doc = "..."`
+    pub doc: Option<String>,
+    /// Values tested in place of the default, given by `cases(...)`
+    pub cases: Vec<syn::Expr>,
+}
+
+impl ParamAttr {
+    /// Parse the contents of `#[def(VALUE, doc = "...", cases(...))]`, where any part can be left out.
+    fn parse_list(input: syn::parse::ParseStream) -> syn::Result<(Self, HelperOptions)> {
+        // options alone are not parsed as a value
+        let is_option = |input: syn::parse::ParseStream| {
+            let fork = input.fork();
+            match fork.parse::<syn::Ident>() {
+                Ok(ident) if ident == "doc" => fork.peek(syn::Token![=]),
+                Ok(ident) if ident == "cases" => fork.peek(syn::token::Paren),
+                _ => false,
+            }
+        };
+
+        let value = match is_option(input) {
+            true => Self::Default,
+            false => {
+                let value = Self::Value(input.parse()?);
+                if !input.is_empty() {
+                    input.parse::<syn::Token![,]>()?;
+                }
+
+                value
+            }
+        };
+
+        let mut options = HelperOptions::default();
+        let mut seen = vec![];
+        while !input.is_empty() {
+            if !is_option(input) {
+                return Err(input.error("expected `doc = \"...\"` or `cases(...)`"));
+            }
+
+            let ident = input.parse::<syn::Ident>()?;
+            if seen.contains(&ident) {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("`{}` given more than once", ident),
+                ));
+            }
+
+            match ident == "doc" {
+                true => {
+                    input.parse::<syn::Token![=]>()?;
+                    options.doc = Some(input.parse::<syn::LitStr>()?.value());
+                }
+                false => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    options.cases =
+                        syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated(
+                            &content,
+                        )?
+                        .into_iter()
+                        .collect();
+                }
+            }
+            seen.push(ident);
+
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+
+        Ok((value, options))
+    }
+}
+
+/// A single permuted item
+#[derive(Clone)]
+pub enum PermutedItem<T: Clone> {
+    /// Item is positional with no identifier
+    Positional(T),
+    /// Item is named with an identifier
+    Named(T),
+    /// Item is a default value
+    Default(T),
+}
+
+impl<T: Debug + Clone> Debug for PermutedItem<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Positional(arg0) => f.debug_tuple("Positional").field(arg0).finish(),
+            Self::Named(arg0) => f.debug_tuple("Named").field(arg0).finish(),
+            Self::Default(arg0) => f.debug_tuple("Default").field(arg0).finish(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> PartialEq for PermutedItem<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner() == other.inner()
+    }
+}
+
+impl<T: Clone + ToDocInfo> ToDocInfo for PermutedItem<T> {
+    fn to_doc_info(&self) -> crate::traits::DocInfo {
+        self.inner().to_doc_info()
+    }
+}
+
+/// Written as an argument of a call: `a` if positional, `a = _` if named,
+/// and `a = <default>` if its default value is used.
+impl<T: Clone + ToDocInfo> fmt::Display for PermutedItem<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ident = self.to_doc_info().ident;
+
+        match self {
+            Self::Positional(_) => write!(f, "{}", ident),
+            Self::Named(_) => write!(f, "{} = _", ident),
+            Self::Default(_) => write!(f, "{} = <default>", ident),
+        }
+    }
+}
+
+/// Human-readable shape of a call to `name` in the given form,
+/// such as `f(a, b, c = _, d = <default>)`.
+///
+/// ```
+/// use defamed_core::permute::{self, params::FunctionParams};
+/// use syn::parse_quote;
+///
+/// let params = FunctionParams::builder()
+///     .param("a", parse_quote!(u32))
+///     .default("b", parse_quote!(1))
+///     .default("c", parse_quote!(2))
+///     .build()
+///     .unwrap();
+/// let (required, default) = permute::split_defaults(params.params);
+/// let forms = permute::permute_tuple_struct(required, default).collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     permute::render_signature("f", &forms[1]),
+///     "f(a, b, c = <default>)"
+/// );
+/// ```
+pub fn render_signature<T: Clone + ToDocInfo>(name: &str, form: &[PermutedItem<T>]) -> String {
+    let args = form.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+    format!("{}({})", name, args.join(", "))
+}
+
+impl<T: Clone> PermutedItem<T> {
+    /// Get the inner value
+    pub fn inner(&self) -> &T {
+        match self {
+            Self::Positional(i) => i,
+            Self::Named(i) => i,
+            Self::Default(i) => i,
+        }
+    }
+
+    /// Split the items into used and unused default values while maintaining order.
+    /// Any item that is not a [PermutedItem::Default] is considered used.
+    fn parition_named_defaults(
+        items: &[PermutedItem<T>],
+    ) -> (Vec<PermutedItem<T>>, Vec<PermutedItem<T>>) {
+        let res: (Vec<_>, Vec<_>) = items
+            .iter()
+            .cloned()
+            .partition(|def| !matches!(def, PermutedItem::Default(_)));
+
+        res
+    }
+}
+
+/// Sort key for the macro arm built from a permutation, so arms are emitted in a stable order.
+///
+/// `macro_rules!` arms are tried from top to bottom, so the most common calls come first:
+/// arms with fewer named arguments sort first, then arms with fewer arguments.
+/// All-positional calls that leave defaults unused are matched first, fully named calls last.
+///
+/// Ties are broken by each item in the arm, keyed by its kind and its position in `reference`.
+/// Unused defaults do not appear in the arm and are left out.
+pub fn arm_sort_key<T: Clone + PartialEq>(
+    items: &[PermutedItem<T>],
+    reference: &[PermutedItem<T>],
+) -> (usize, usize, Vec<(u8, usize)>)
+where
+    PermutedItem<T>: ToMacroPattern,
+{
+    let items = items
+        .iter()
+        .filter_map(|item| {
+            let position = reference
+                .iter()
+                .position(|r| r == item)
+                .unwrap_or(usize::MAX);
+
+            match item {
+                PermutedItem::Positional(_) => Some((0, position)),
+                PermutedItem::Named(_) => Some((1, position)),
+                PermutedItem::Default(_) => item.to_macro_pattern().map(|_| (2, position)),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let named = items.iter().filter(|(kind, _)| *kind == 1).count();
+
+    (named, items.len(), items)
+}
+
+/// Remove arms shadowed by an earlier arm, given arms sorted by [`arm_sort_key`].
+///
+/// Arm patterns are built from the same fragments, so two arms accept the same calls only if
+/// their keys are equal, and only the first of them is ever matched.
+pub fn prune_shadowed<K: PartialEq, T>(arms: &mut Vec<(K, T)>) {
+    arms.dedup_by(|(later, _), (earlier, _)| later == earlier);
+}
+
+/// An item the permutation functions can order, which is either required or has a default value.
+///
+/// The functions of this module only clone and move items around,
+/// so any type can be permuted, such as the indices of parsed items.
+pub trait Permutable {
+    /// Whether the item can be omitted, in favour of its default value
+    fn is_default(&self) -> bool;
+}
+
+impl<T: Permutable> Permutable for Rc<T> {
+    fn is_default(&self) -> bool {
+        (**self).is_default()
+    }
+}
+
+impl Permutable for ParamAttr {
+    fn is_default(&self) -> bool {
+        !matches!(self, Self::None)
+    }
+}
+
+/// Split items in declaration order into the required ones and the trailing default ones,
+/// which is how the permutation functions take them.
+///
+/// Items are split at the first default item, so a required item after it is returned as a default one.
+pub fn split_defaults<T: Permutable>(mut items: Vec<T>) -> (Vec<T>, Vec<T>) {
+    let split = items
+        .iter()
+        .position(Permutable::is_default)
+        .unwrap_or(items.len());
+    let default = items.split_off(split);
+
+    (items, default)
+}
+
+/// Accepted forms of an item, generated lazily.
+///
+/// The permute backend collects every form into a buffer, to sort it and prune the shadowed forms,
+/// before rendering the arms. Other backends only read the first form.
+pub type Forms<T> = Box<dyn Iterator<Item = Vec<PermutedItem<T>>>>;
+
+/// Required and default items in declaration order, all named.
+///
+/// Used in place of every permutation by strategies that parse arguments themselves.
+pub fn declaration_order<T: Clone>(required: Vec<T>, default: Vec<T>) -> Vec<PermutedItem<T>> {
+    required
+        .into_iter()
+        .chain(default)
+        .map(PermutedItem::Named)
+        .collect()
+}
+
+/// Every combination of supplied and omitted default items, in declaration order.
+///
+/// Supplied items are positional. The first combination supplies every item.
+pub fn canonical_forms<T: Clone>(
+    required: Vec<T>,
+    default: Vec<T>,
+) -> impl Iterator<Item = Vec<PermutedItem<T>>> {
+    (0..1_usize << default.len()).map(move |omitted| {
+        let defaults =
+            default
+                .iter()
+                .enumerate()
+                .map(|(idx, item)| match omitted & (1 << idx) == 0 {
+                    true => PermutedItem::Positional(item.clone()),
+                    false => PermutedItem::Default(item.clone()),
+                });
+
+        required
+            .iter()
+            .cloned()
+            .map(PermutedItem::Positional)
+            .chain(defaults)
+            .collect()
+    })
+}
+
+/// Generate every accepted form of positional, named and default items,
+/// where named items only appear in declaration order.
+///
+/// The first form is guaranteed to contain only [PermutedItem::Named] elements in the original order.
+pub fn permute_ordered<T: Clone>(
+    required: Vec<T>,
+    default: Vec<T>,
+) -> impl Iterator<Item = Vec<PermutedItem<T>>> {
+    let required_len = required.len();
+    let default_len = default.len();
+    let items = required.into_iter().chain(default).collect::<Vec<_>>();
+
+    (0..=items.len()).flat_map(move |positional| {
+        // defaults that are not positional are either named or omitted
+        let optional = default_len - positional.saturating_sub(required_len);
+        let items = items.clone();
+
+        (0..1_usize << optional).map(move |omitted| {
+            items
+                .iter()
+                .enumerate()
+                .map(|(idx, item)| {
+                    let optional_idx = idx.checked_sub(items.len() - optional);
+
+                    match (idx < positional, optional_idx) {
+                        (true, _) => PermutedItem::Positional(item.clone()),
+                        (false, Some(bit)) if omitted & (1 << bit) != 0 => {
+                            PermutedItem::Default(item.clone())
+                        }
+                        (false, _) => PermutedItem::Named(item.clone()),
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+    })
+}
+
+/// Number of forms generated by [permute] and [permute_forms], without generating them.
+///
+/// Saturates at [usize::MAX].
+pub fn permute_count(required: usize, default: usize) -> usize {
+    // every order of the named required items, for each number of positional ones
+    let named_required = (0..=required)
+        .map(|n| (1..=n).fold(1_usize, |acc, i| acc.saturating_mul(i)))
+        .fold(0_usize, usize::saturating_add);
+    // the named defaults when some of them are positional
+    let positional_default = (0..default)
+        .map(ordered_subsets)
+        .fold(0_usize, usize::saturating_add);
+
+    named_required
+        .saturating_mul(ordered_subsets(default))
+        .saturating_add(positional_default)
+}
+
+/// Number of forms generated by [permute_ordered], without generating them.
+///
+/// Saturates at [usize::MAX].
+pub fn ordered_count(required: usize, default: usize) -> usize {
+    let subsets = subset_count(default);
+
+    (required + 1)
+        .saturating_mul(subsets)
+        .saturating_add(subsets - 1)
+}
+
+/// Number of forms generated by [permute_tuple_struct], one for each number of trailing defaults.
+pub fn tuple_struct_count(default: usize) -> usize {
+    default + 1
+}
+
+/// Number of forms generated by [canonical_forms], one for each subset of defaults.
+///
+/// Saturates at [usize::MAX].
+pub fn canonical_count(default: usize) -> usize {
+    subset_count(default)
+}
+
+/// Number of subsets of `n` items, saturating at [usize::MAX].
+fn subset_count(n: usize) -> usize {
+    1_usize.checked_shl(n as u32).unwrap_or(usize::MAX)
+}
+
+/// Number of orderings of every subset of `n` items.
+fn ordered_subsets(n: usize) -> usize {
+    // n!/(n-k)! for every k, accumulated from the largest subsets
+    (0..=n)
+        .scan(1_usize, |arrangements, k| {
+            let current = *arrangements;
+            *arrangements = arrangements.saturating_mul(n - k);
+            Some(current)
+        })
+        .fold(0_usize, usize::saturating_add)
+}
+
+/// Generate all permutations of positional items and default items.
+///
+/// Permutations are generated lazily, as tuples of positional and default permutations.
+///
+/// The first permutation is guraranteed to contain
+/// only [PermutedItem::Named] elements in the original order (`required`, `default` concatenated).
+pub fn permute<T: Clone + Debug>(
+    required: Vec<T>,
+    default: Vec<T>,
+) -> impl Iterator<Item = (Vec<PermutedItem<T>>, Vec<PermutedItem<T>>)> {
+    let all_positional = required
+        .iter()
+        .cloned()
+        .map(PermutedItem::Positional)
+        .collect::<Vec<_>>();
+
+    let named_permute = permute_required(required);
+
+    // named defaults are combined with every sequence of required items, so they are only generated once
+    let default_permute = Rc::new(permute_named_default(default.clone()).collect::<Vec<_>>());
+
+    // constructing intermediate permutations w/ named and default parameters,
+    // an empty default sequence is only used when there are no defaults
+    let named_pos = named_permute.flat_map(move |seq| {
+        let default_permute = Rc::clone(&default_permute);
+
+        (0..default_permute.len().max(1)).map(move |idx| {
+            (
+                seq.clone(),
+                default_permute.get(idx).cloned().unwrap_or_default(),
+            )
+        })
+    });
+
+    // append default positional special cases to the end
+    let all_positional = permute_pos_default(default).map(move |seq| (all_positional.clone(), seq));
+
+    named_pos.chain(all_positional)
+}
+
+/// Sequences of required items, which are positional up to some index and named in any order after it.
+///
+/// The first sequence is all named in the original order, the last one all positional.
+fn permute_required<T: Clone>(required: Vec<T>) -> impl Iterator<Item = Vec<PermutedItem<T>>> {
+    let all_positional = required
+        .iter()
+        .cloned()
+        .map(PermutedItem::Positional)
+        .collect::<Vec<_>>();
+
+    (0..=required.len()).flat_map(move |idx| {
+        let positional = all_positional[..idx].to_vec();
+
+        permute_named(required[idx..].to_vec())
+            .map(move |named_seq| [positional.as_slice(), named_seq.as_slice()].concat())
+    })
+}
+
+/// Every form of positional, named and default items, as generated by [permute].
+pub fn permute_forms<T: Clone + Debug + 'static>(required: Vec<T>, default: Vec<T>) -> Forms<T> {
+    Box::new(permute(required, default).map(|permutation| [permutation.0, permutation.1].concat()))
+}
+
+/// Special permutation case for tuple structs.
+///
+/// Tuple structs elements are positional only.
+/// Default parameters are permuted as positionals.
+///
+/// Also used for items that do not accept named arguments.
+pub fn permute_tuple_struct<T: Clone>(
+    required: Vec<T>,
+    default: Vec<T>,
+) -> impl Iterator<Item = Vec<PermutedItem<T>>> {
+    let positionals = required
+        .into_iter()
+        .map(|f| PermutedItem::Positional(f))
+        .collect::<Vec<_>>();
+
+    (0..default.len() + 1).map(move |default_idx| {
+        let (def_pos, def_unused) = default.split_at(default_idx);
+        let def_pos_perm = def_pos
+            .iter()
+            .cloned()
+            .map(|f| PermutedItem::Positional(f))
+            .collect::<Vec<_>>();
+        let def_unused_perm = def_unused
+            .iter()
+            .cloned()
+            .map(|f| PermutedItem::Default(f))
+            .collect::<Vec<_>>();
+
+        [positionals.clone(), def_pos_perm, def_unused_perm].concat()
+    })
+}
+
+/// Every order of the indices `0..len`, generated one at a time with Heap's algorithm.
+///
+/// The first order is `0..len` itself, and each following order swaps a single pair of indices.
+/// Items are only cloned once an order is mapped back to them.
+struct IndexPermutations {
+    indices: Vec<usize>,
+    /// Swaps made at each position, in place of the call stack of the recursive algorithm
+    counters: Vec<usize>,
+    /// Position of the next swap
+    pos: usize,
+    started: bool,
+}
+
+impl IndexPermutations {
+    fn new(len: usize) -> Self {
+        Self {
+            indices: (0..len).collect(),
+            counters: vec![0; len],
+            pos: 1,
+            started: false,
+        }
+    }
+}
+
+impl Iterator for IndexPermutations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            return Some(self.indices.clone());
+        }
+
+        while self.pos < self.indices.len() {
+            if self.counters[self.pos] < self.pos {
+                match self.pos % 2 {
+                    0 => self.indices.swap(0, self.pos),
+                    _ => self.indices.swap(self.counters[self.pos], self.pos),
+                }
+
+                self.counters[self.pos] += 1;
+                self.pos = 1;
+                return Some(self.indices.clone());
+            }
+
+            self.counters[self.pos] = 0;
+            self.pos += 1;
+        }
+
+        None
+    }
+}
+
+/// Perform permutations of all items in a slice.
+/// All items will be wrapped in [PermutedItem::Named].
+fn permute_named<T: Clone>(named: Vec<T>) -> impl Iterator<Item = Vec<PermutedItem<T>>> {
+    IndexPermutations::new(named.len()).map(move |order| {
+        order
+            .into_iter()
+            .map(|idx| PermutedItem::Named(named[idx].clone()))
+            .collect::<Vec<_>>()
+    })
+}
+
+/// Perform permutations for default parameters. All permuted values are named.
+/// For permutations of positional defaults see [permute_pos_default].
+///
+/// The first permutation is in the original order of the input.
+///
+/// Each item in the slice must have a default value.
+/// This function will not check for this.
+///
+/// Additionally, default params can be used(named) or unused(default). These are also permuted as well.
+fn permute_named_default<T: Clone + Debug>(
+    defaults: Vec<T>,
+) -> impl Iterator<Item = Vec<PermutedItem<T>>> {
+    (0..(1_usize << defaults.len()))
+        .rev()
+        .flat_map(move |num| {
+            let seq = defaults
+                .iter()
+                .enumerate()
+                .map(|(pos, item)| {
+                    // if bit set, it is used
+                    if (num >> pos) & 1 != 0 {
+                        PermutedItem::Named(item.to_owned())
+                    } else {
+                        PermutedItem::Default(item.to_owned())
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let (used, unused) = PermutedItem::<T>::parition_named_defaults(&seq);
+
+            IndexPermutations::new(used.len()).map(move |order| {
+                order
+                    .into_iter()
+                    .map(|idx| used[idx].clone())
+                    .chain(unused.iter().cloned())
+                    .collect::<Vec<_>>()
+            })
+        })
+        .filter(|item| !item.is_empty())
+}
+
+/// Perform permutations for positional default permutations.
+///
+/// This is for the special case where all preceding non-default parameters
+/// are used as positional parameters.
+fn permute_pos_default<T: Clone + Debug>(
+    defaults: Vec<T>,
+) -> impl Iterator<Item = Vec<PermutedItem<T>>> {
+    (1..=defaults.len()).flat_map(move |idx| {
+        let (positional, named) = defaults.split_at(idx);
+        let pos_params = positional
+            .iter()
+            .map(|p| PermutedItem::Positional(p.to_owned()))
+            .collect::<Vec<_>>();
+
+        // without named defaults, the positional parameters are the only sequence
+        let only_positional = named.is_empty().then(|| pos_params.clone());
+
+        only_positional.into_iter().chain(
+            permute_named_default(named.to_vec())
+                .map(move |named_seq| [pos_params.clone(), named_seq].concat()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_metavar_name() {
+        assert_eq!(metavar_name("depth"), "depth_val");
+        assert_eq!(metavar_name("r#type"), "type_val");
+    }
+
+    #[test]
+    fn test_unique_metavars() {
+        let names = ["x", "x_val", "self", "x"].map(String::from);
+
+        assert_eq!(
+            unique_metavars(&names, &["self_val"]),
+            vec!["x_val", "x_val_val", "self_val_1", "x_val_1"]
+        );
+
+        let names = ["a", "a", "a"].map(String::from);
+        assert_eq!(
+            unique_metavars(&names, &["a_val_1"]),
+            vec!["a_val", "a_val_2", "a_val_3"]
+        );
+    }
+
+    /// Idx to valid rust ident
+    fn idx_to_str(mut idx: usize) -> String {
+        let mut id = vec![];
+        if idx == 0 {
+            id.push(0);
+        }
+
+        while idx != 0 {
+            id.push(idx % 26);
+            idx /= 26;
+        }
+
+        id.into_iter()
+            .map(|i| char::from_u32(i as u32 + 97).unwrap())
+            .collect::<String>()
+    }
+
+    #[test]
+    fn test_permute_tuple_struct() {
+        fn assert_positional_default_split_at<T: Clone>(
+            permutation: &[PermutedItem<T>],
+            split: usize,
+        ) {
+            let (positional, default) = permutation.split_at(split);
+            assert!(positional
+                .iter()
+                .all(|f| matches!(f, PermutedItem::Positional(_))));
+            assert!(default
+                .iter()
+                .all(|f| matches!(f, PermutedItem::Default(_))));
+        }
+
+        let positional = vec!["a", "b"];
+        let defaults = vec!["c", "d"];
+
+        let permutations = permute_tuple_struct(positional, defaults).collect::<Vec<_>>();
+
+        assert_eq!(permutations.len(), 3);
+        assert_positional_default_split_at(&permutations[0], 2);
+        assert_positional_default_split_at(&permutations[1], 3);
+        assert_positional_default_split_at(&permutations[2], 4);
+    }
+
+    #[test]
+    fn test_permute_ordered() {
+        let forms = permute_ordered(vec!["a", "b"], vec!["c", "d"]).collect::<Vec<_>>();
+
+        assert!(forms[0]
+            .iter()
+            .all(|item| matches!(item, PermutedItem::Named(_))));
+        // 3 positional splits of the required items, then 2 of the defaults
+        assert_eq!(forms.len(), 3 * 4 + 2 + 1);
+
+        // every form keeps the declaration order
+        for form in &forms {
+            assert_eq!(
+                form.iter().map(|item| *item.inner()).collect::<Vec<_>>(),
+                ["a", "b", "c", "d"]
+            );
+        }
+
+        // positional arguments only fill defaults once every required item is positional
+        assert!(forms.iter().all(|form| {
+            !matches!(form[2], PermutedItem::Positional(_))
+                || matches!(form[1], PermutedItem::Positional(_))
+        }));
+    }
+
+    #[test]
+    fn test_render_signature() {
+        let params = params::FunctionParams::builder()
+            .param("a", syn::parse_quote! {u32})
+            .param("b", syn::parse_quote! {u32})
+            .default("c", syn::parse_quote! {1})
+            .default("d", syn::parse_quote! {2})
+            .build()
+            .unwrap();
+        let (required, default) = split_defaults(params.params);
+        let forms = permute_forms(required, default)
+            .map(|form| render_signature("f", &form))
+            .collect::<Vec<_>>();
+
+        assert_eq!(forms[0], "f(a = _, b = _, c = _, d = _)");
+        assert!(forms.contains(&"f(a, b, c = _, d = <default>)".to_string()));
+        assert!(forms.contains(&"f(a, b, c = <default>, d = <default>)".to_string()));
+        assert_eq!(render_signature::<params::FunctionParam>("f", &[]), "f()");
+    }
+
+    #[test]
+    fn test_split_defaults() {
+        /// Item without any syntax tree, defaulted if it has a value
+        #[derive(Clone, Debug, PartialEq)]
+        struct Item(&'static str, Option<u32>);
+
+        impl Permutable for Item {
+            fn is_default(&self) -> bool {
+                self.1.is_some()
+            }
+        }
+
+        let items = vec![Item("a", None), Item("b", Some(1)), Item("c", Some(2))];
+        let (required, default) = split_defaults(items.clone());
+        assert_eq!(required, items[..1]);
+        assert_eq!(default, items[1..]);
+
+        let forms = permute_forms(required, default).collect::<Vec<_>>();
+        assert_eq!(forms.len(), permute_count(1, 2));
+        assert!(forms
+            .iter()
+            .flatten()
+            .all(|item| !matches!(item, PermutedItem::Default(Item(_, None)))));
+
+        let (required, default) = split_defaults(vec![Item("a", None)]);
+        assert_eq!((required.len(), default.len()), (1, 0));
+        let (required, default) = split_defaults(Vec::<Item>::new());
+        assert!(required.is_empty() && default.is_empty());
+    }
+
+    #[test]
+    fn test_form_counts() {
+        for required in 0..5_usize {
+            for default in 0..5_usize {
+                let items = || ((0..required).collect(), (10..10 + default).collect());
+
+                let (r, d) = items();
+                assert_eq!(
+                    permute_count(required, default),
+                    permute::<usize>(r, d).count()
+                );
+                let (r, d) = items();
+                assert_eq!(
+                    ordered_count(required, default),
+                    permute_ordered::<usize>(r, d).count()
+                );
+                let (r, d) = items();
+                assert_eq!(
+                    tuple_struct_count(default),
+                    permute_tuple_struct::<usize>(r, d).count()
+                );
+                let (r, d) = items();
+                assert_eq!(
+                    canonical_count(default),
+                    canonical_forms::<usize>(r, d).count()
+                );
+            }
+        }
+
+        assert_eq!(permute_count(30, 30), usize::MAX);
+    }
+
+    #[test]
+    fn test_canonical_forms() {
+        let forms = canonical_forms(vec!["a"], vec!["b", "c"]).collect::<Vec<_>>();
+
+        assert_eq!(forms.len(), 4);
+        assert!(forms[0]
+            .iter()
+            .all(|item| matches!(item, PermutedItem::Positional(_))));
+        assert!(forms.iter().all(|form| form.len() == 3));
+
+        // the required item is always supplied
+        let mut omitted = forms
+            .iter()
+            .map(|form| {
+                assert!(matches!(form[0], PermutedItem::Positional("a")));
+                form.iter()
+                    .filter(|item| matches!(item, PermutedItem::Default(_)))
+                    .count()
+            })
+            .collect::<Vec<_>>();
+        omitted.sort();
+        assert_eq!(omitted, [0, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_permute_all_default() {
+        let defaults = vec!["a", "b", "c"];
+        let permutations = permute(vec![], defaults.clone()).collect::<Vec<_>>();
+
+        // all forms start with positional defaults, followed by named ones
+        let forms = permutations
+            .iter()
+            .map(|(required, default)| {
+                assert!(required.is_empty());
+                let positional = default
+                    .iter()
+                    .take_while(|item| matches!(item, PermutedItem::Positional(_)))
+                    .count();
+                let named = default
+                    .iter()
+                    .filter_map(|item| match item {
+                        PermutedItem::Named(n) => Some(*n),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+
+                (positional, named)
+            })
+            .collect::<Vec<_>>();
+
+        // empty invocation
+        assert!(forms.contains(&(0, vec![])));
+
+        // every named subset in every order
+        for subset in 0..(1 << defaults.len()) {
+            let named = defaults
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| subset & (1 << idx) != 0)
+                .map(|(_, d)| *d)
+                .collect::<Vec<_>>();
+
+            for order in permute_named(named) {
+                let order = order.iter().map(|item| *item.inner()).collect::<Vec<_>>();
+                assert!(forms.contains(&(0, order.clone())), "missing {:?}", order);
+            }
+        }
+
+        // every positional prefix
+        for positional in 0..=defaults.len() {
+            assert!(forms
+                .iter()
+                .any(|(p, named)| *p == positional && named.is_empty()));
+        }
+    }
+
+    #[test]
+    fn test_arm_sort_key() {
+        let item_fn: syn::ItemFn = syn::parse_quote! {
+            fn item(a: i32, b: i32, #[def] c: i32, #[def] d: i32) {}
+        };
+        let default_attr =
+            syn::Ident::new(crate::DEFAULT_HELPER_ATTR, proc_macro2::Span::call_site());
+        let params = params::FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr)
+            .unwrap()
+            .params
+            .into_iter()
+            .map(Rc::new)
+            .collect::<Vec<_>>();
+        let (required, default) = params.split_at(2);
+
+        let permutations = permute(required.to_vec(), default.to_vec())
+            .map(|(r, d)| [r, d].concat())
+            .collect::<Vec<_>>();
+        let reference = permutations[0].clone();
+
+        // every permutation refers to the same parameters
+        assert!(permutations
+            .iter()
+            .flatten()
+            .all(|item| params.iter().any(|p| Rc::ptr_eq(p, item.inner()))));
+
+        let sorted = |mut arms: Vec<Vec<PermutedItem<_>>>| {
+            arms.sort_by_cached_key(|arm| arm_sort_key(arm, &reference));
+            arms.iter()
+                .map(|arm| arm_sort_key(arm, &reference))
+                .collect::<Vec<_>>()
+        };
+
+        let forward = sorted(permutations.clone());
+        let reversed = sorted(permutations.into_iter().rev().collect());
+        assert_eq!(forward, reversed);
+
+        // all-positional without defaults first, fully named last
+        assert_eq!(forward.first().unwrap(), &(0, 2, vec![(0, 0), (0, 1)]));
+        assert_eq!(forward.last().unwrap().0, 4);
+
+        // fewer named arguments come first
+        assert!(forward.windows(2).all(|w| w[0].0 <= w[1].0));
+
+        // every permutation has its own pattern, duplicated ones are pruned
+        let mut arms = forward
+            .iter()
+            .flat_map(|key| [(key.clone(), ()), (key.clone(), ())])
+            .collect::<Vec<_>>();
+        prune_shadowed(&mut arms);
+        assert_eq!(
+            arms.into_iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            forward
+        );
+    }
+
+    #[test]
+    fn test_index_permutations() {
+        // same order as the recursive algorithm
+        assert_eq!(
+            IndexPermutations::new(3).collect::<Vec<_>>(),
+            [
+                [0, 1, 2],
+                [1, 0, 2],
+                [2, 0, 1],
+                [0, 2, 1],
+                [1, 2, 0],
+                [2, 1, 0]
+            ]
+        );
+
+        assert_eq!(
+            IndexPermutations::new(0).collect::<Vec<_>>(),
+            [Vec::<usize>::new()]
+        );
+
+        for len in 1..7 {
+            let mut orders = IndexPermutations::new(len).collect::<Vec<_>>();
+            let count = orders.len();
+
+            assert_eq!(count, (1..=len).product());
+            orders.sort();
+            orders.dedup();
+            assert_eq!(orders.len(), count);
+        }
+    }
+
+    /// Test inner named permute function
+    #[test]
+    fn test_permute_inner_named() {
+        let items = vec!["a", "b", "c", "d"];
+
+        let permutations = permute_named(items).collect::<Vec<_>>();
+        // println!("{:?}", permutations);
+        assert_eq!(permutations.len(), 24);
+
+        let _ = permutations
+            .into_iter()
+            .flatten()
+            .map(|item| {
+                assert!(matches!(item, PermutedItem::Named(_)));
+            })
+            .collect::<Vec<_>>();
+    }
+
+    /// Test inner default permute function
+    #[test]
+    fn test_permute_inner_named_defaults() {
+        let mut items = vec!["a", "b"];
+
+        let permutations = permute_named_default(items.clone()).collect::<Vec<_>>();
+
+        // 0 0
+        // 0 1
+        // 1 0
+        // 1 1
+        // 1 1 again because used defaults have to be permuted
+        assert_eq!(permutations.len(), 5);
+
+        let first = permutations.first().unwrap();
+        assert!(first
+            .iter()
+            .all(|item| matches!(item, PermutedItem::Named(_))));
+        assert_eq!(
+            *first,
+            vec![PermutedItem::Named("a"), PermutedItem::Named("b"),]
+        );
+
+        items.clear();
+        let permutations = permute_pos_default(items.clone()).collect::<Vec<_>>();
+        assert!(permutations.is_empty());
+    }
+
+    #[test]
+    fn test_permute_inner_named_defaults_9() {
+        let items = (0..9).map(idx_to_str).collect::<Vec<_>>();
+
+        for i in 1..=9 {
+            let inputs = &items[..i];
+            let permutations = permute_named_default(inputs.to_vec()).collect::<Vec<_>>();
+            let first = permutations.first().unwrap();
+
+            println!("9 defaults: {} branches", permutations.len());
+            println!("first item: {:?}", first);
+
+            let expected = inputs
+                .iter()
+                .map(|item| PermutedItem::Named(item.to_owned()))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                expected, *first,
+                "first permutation must have the same order as the input"
+            );
+        }
+    }
+
+    /// Test inner positional defaults permute function
+    #[test]
+    fn test_permute_inner_positional_defaults() {
+        let items = vec!["a", "b", "c"];
+
+        let permutations = permute_pos_default(items.clone()).collect::<Vec<_>>();
+
+        // 0 0
+        // 0 1
+        // 1 0
+        // 1 1
+        assert_eq!(permutations.len(), 8);
+        let first = permutations.first().unwrap();
+
+        assert!(matches!(first[0], PermutedItem::Positional(_)));
+        assert!(matches!(first[1], PermutedItem::Named(_)));
+        assert!(matches!(first[2], PermutedItem::Named(_)));
+
+        assert_eq!(
+            *first,
+            vec![
+                PermutedItem::Positional("a"),
+                PermutedItem::Named("b"),
+                PermutedItem::Named("c")
+            ]
+        );
+    }
+
+    /// Test full permutation
+    #[test]
+    fn test_permute_positional_named() {
+        let items = vec!["a", "b", "c", "d"];
+
+        let permutations = permute(items, vec![]).collect::<Vec<_>>();
+
+        let first_perm = permutations.first().unwrap();
+
+        assert!(
+            first_perm
+                .0
+                .iter()
+                .all(|item| matches!(item, PermutedItem::Named(_))),
+            "first permutation must contain all named parameters"
+        );
+        assert!(
+            first_perm
+                .1
+                .iter()
+                .all(|item| matches!(item, PermutedItem::Named(_))),
+            "first permutation must contain all named parameters"
+        );
+        let full_perm = [first_perm.0.clone(), first_perm.1.clone()].concat();
+
+        assert_eq!(
+            full_perm,
+            vec![
+                PermutedItem::Named("a"),
+                PermutedItem::Named("b"),
+                PermutedItem::Named("c"),
+                PermutedItem::Named("d"),
+            ],
+            "first permutation must maintain the initial order"
+        );
+        assert_eq!(permutations.len(), 34);
+    }
+
+    /// Test positional and default parameters
+    #[test]
+    fn test_permute_positional_default() {
+        let items = vec!["a", "b", "c", "d"];
+        let defaults = vec!["e", "f"];
+
+        let permutations = permute(items, defaults).collect::<Vec<_>>();
+
+        let first_perm = permutations.first().unwrap();
+
+        assert!(
+            first_perm
+                .0
+                .iter()
+                .all(|item| matches!(item, PermutedItem::Named(_))),
+            "first permutation must contain all named parameters"
+        );
+        assert!(
+            first_perm
+                .1
+                .iter()
+                .all(|item| matches!(item, PermutedItem::Named(_))),
+            "first permutation must contain all named parameters"
+        );
+
+        let full_perm = [first_perm.0.clone(), first_perm.1.clone()].concat();
+        assert_eq!(
+            full_perm,
+            vec![
+                PermutedItem::Named("a"),
+                PermutedItem::Named("b"),
+                PermutedItem::Named("c"),
+                PermutedItem::Named("d"),
+                PermutedItem::Named("e"),
+                PermutedItem::Named("f"),
+            ],
+            "first permutation must maintain the initial order"
+        );
+
+        // 5 permutations for default parameters
+        // and 3 additional permutations for positional default parameters (not permuted)
+        assert_eq!(permutations.len(), 34 * 5 + 3);
+    }
+
+    /// Test 1-10 positional parameters
+    #[test]
+    fn test_permute_9_positional() {
+        for i in 1..=9 {
+            let items = (0..i).map(idx_to_str).collect::<Vec<_>>();
+
+            let permutations = permute(items.clone(), vec![]).collect::<Vec<_>>();
+
+            println!("{} positionals: {} branches", i, permutations.len());
+
+            let first_perm = permutations.first().unwrap();
+            assert_eq!(
+                first_perm.0,
+                items.clone()
+                    .into_iter()
+                    .map(|item| PermutedItem::Named(item.to_owned()))
+                    .collect::<Vec<_>>(),
+                "permutation for {} positional params: first permutation must have the same order as the input",
+                i
+            );
+        }
+    }
+
+    /// Test 1-10 default parameters
+    #[test]
+    fn test_permute_9_default() {
+        for i in 1..=9 {
+            let items = (0..i).map(idx_to_str).collect::<Vec<_>>();
+            let permutations = permute(vec![], items.clone()).collect::<Vec<_>>();
+
+            println!("{} defaults: {} branches", i, permutations.len());
+
+            let first_perm = permutations.first().unwrap();
+            assert_eq!(
+                    first_perm.1,
+                    items.clone()
+                        .into_iter()
+                        .map(|item| PermutedItem::Default(item.to_owned()))
+                        .collect::<Vec<_>>(),
+                    "permutation for {} default params: first permutation must have the same order as the input",
+                    i
+                );
+        }
+    }
+
+    #[test]
+    fn test_permute_9_default_positional() {
+        const NUM: usize = 9;
+
+        let items = (0..NUM).map(idx_to_str).collect::<Vec<_>>();
+
+        for i in 0..=NUM {
+            let (pos, def) = items.split_at(i);
+
+            let permutations = permute(pos.to_vec(), def.to_vec()).collect::<Vec<_>>();
+
+            println!(
+                "pos: {}, def: {}\tbranches: {}",
+                pos.len(),
+                def.len(),
+                permutations.len()
+            );
+
+            let first_perm = permutations.first().unwrap();
+
+            assert_eq!(
+                first_perm.0,
+                pos.iter()
+                    .map(|item| PermutedItem::Named(item.to_owned()))
+                    .collect::<Vec<_>>(),
+                "pos: {}, def: {}: first permutation must have the same order as the input",
+                pos.len(),
+                def.len()
+            );
+        }
+    }
+}
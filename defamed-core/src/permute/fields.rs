@@ -1,4 +1,7 @@
+//! Struct fields with default values, parsed from a struct definition.
+
 use std::fmt;
+use std::rc::Rc;
 
 use quote::quote;
 use quote::ToTokens;
@@ -6,18 +9,23 @@ use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 
 use crate::traits::DocInfo;
+use crate::traits::PatternSyntax;
 use crate::traits::StripAttributes;
 use crate::traits::ToDocInfo;
 use crate::traits::ToMacroPattern;
 
-use super::{ParamAttr, PermutedItem};
+use super::{ParamAttr, Permutable, PermutedItem};
 
 /// Parsed struct fields
 #[derive(Clone, Debug)]
 pub struct StructFields {
+    /// Name of the struct
     #[allow(unused)]
     pub ident: syn::Ident,
+    /// Fields in declaration order
     pub fields: Vec<StructField>,
+    /// Name of the helper attribute
+    helper: syn::Ident,
 }
 
 /// A parsed struct field.
@@ -25,12 +33,28 @@ pub struct StructFields {
 /// used for macro pattern matching.
 #[derive(Clone)]
 pub struct StructField {
+    /// Visibility of the field
     pub vis: syn::Visibility,
+    /// Attributes of the field, including the helper attribute
     pub attrs: Vec<syn::Attribute>,
+    /// Field name, or a generated one for tuple struct fields
     pub ident: syn::Ident,
+    /// Field identifier rendered once
+    name: String,
+    /// Field of a tuple struct
     pub is_tuple: bool,
+    /// Field type
     pub ty: syn::Type,
+    /// Default value of the field, if any
     pub default_value: ParamAttr,
+    /// Description given in the default attribute
+    doc: Option<String>,
+    /// Metavariable that captures the field value in the generated macro
+    metavar: syn::Ident,
+    /// Type that `Self` in the default value is expanded to, where the macro is called
+    self_path: Option<proc_macro2::TokenStream>,
+    /// Default value is evaluated in an inline `const` block, for `const_defaults`
+    in_const: bool,
 
     /// Overrides all other fields for [ToMacroPattern],
     /// This represents the struct update syntax without a value (`..`).
@@ -60,9 +84,14 @@ impl fmt::Debug for StructField {
     }
 }
 
+// structural comparison, ignoring spans.
+// the rendered names are compared first, as they tell apart the fields of a struct
 impl PartialEq for StructField {
     fn eq(&self, other: &Self) -> bool {
-        self.ident.to_token_stream().to_string() == other.ident.to_token_stream().to_string()
+        self.name == other.name
+            && self.ty == other.ty
+            && self.default_value == other.default_value
+            && self.dot_dot == other.dot_dot
     }
 }
 
@@ -84,75 +113,95 @@ impl PartialEq for StructField {
 //     }
 // }
 
-impl ToMacroPattern for PermutedItem<StructField> {
-    fn to_macro_pattern(&self) -> Option<proc_macro2::TokenStream> {
-        if self.inner().dot_dot {
+impl ToMacroPattern for PermutedItem<Rc<StructField>> {
+    fn to_macro_pattern_with(&self, syntax: PatternSyntax) -> Option<proc_macro2::TokenStream> {
+        let StructField {
+            ident,
+            metavar,
+            dot_dot,
+            ..
+        } = &**self.inner();
+
+        if *dot_dot {
             return Some(quote! {..});
         }
 
         match self {
-            Self::Positional(StructField { ident, .. }) => {
-                let pat = syn::Ident::new(&format!("{}_val", ident), ident.span());
-                Some(quote! {$#pat: expr})
-            }
-
-            Self::Named(StructField { ident, .. }) => {
-                // asd
+            Self::Positional(_) => Some(syntax.capture(metavar)),
 
-                let pat = syn::Ident::new(&format!("{}_val", ident), ident.span());
+            Self::Named(_) => Some(syntax.named(ident, syntax.capture(metavar))),
 
-                Some(quote! {#ident: $#pat: expr})
-            }
-
-            Self::Default(StructField { default_value, .. }) => match default_value {
-                ParamAttr::None => unimplemented!("default value must be present"),
-                ParamAttr::Default | ParamAttr::Value(_) => None,
-            },
+            // unused defaults do not appear in the macro pattern
+            Self::Default(_) => None,
         }
     }
 
+    /// Fields are named like in a struct literal.
+    fn pattern_syntax(&self) -> PatternSyntax {
+        PatternSyntax::StructLiteral
+    }
+
     fn to_func_call_pattern(&self) -> proc_macro2::TokenStream {
-        if self.inner().dot_dot {
+        let StructField {
+            ident,
+            is_tuple,
+            metavar,
+            default_value,
+            dot_dot,
+            ..
+        } = &**self.inner();
+        let value = self.to_default_value();
+
+        if *dot_dot {
             return quote! {};
         }
 
         match self {
-            PermutedItem::Positional(StructField {
-                ident, is_tuple, ..
-            }) => {
-                let pat = syn::Ident::new(&format!("{}_val", ident), ident.span());
-
-                match is_tuple {
-                    true => quote! {$#pat},
-                    false => quote! {#ident: $#pat},
-                }
-            }
-            PermutedItem::Named(StructField { ident, .. }) => {
-                let pat = syn::Ident::new(&format!("{}_val", ident), ident.span());
-
-                quote! {#ident: $#pat}
+            PermutedItem::Positional(_) => match is_tuple {
+                true => quote! {$#metavar},
+                false => quote! {#ident: $#metavar},
+            },
+            PermutedItem::Named(_) => {
+                quote! {#ident: $#metavar}
             }
-            PermutedItem::Default(StructField {
-                ident,
-                is_tuple,
-                default_value,
-                ..
-            }) => match (default_value, is_tuple) {
-                (ParamAttr::None, _) => unimplemented!("default value must be present"),
+            PermutedItem::Default(_) => match (default_value, is_tuple) {
+                (ParamAttr::None, _) => {
+                    syn::Error::new(ident.span(), "default value must be present")
+                        .to_compile_error()
+                }
                 (ParamAttr::Default, true) => quote! {core::default::Default::default()},
                 (ParamAttr::Default, false) => quote! {#ident: core::default::Default::default()},
-                (ParamAttr::Value(expr), true) => quote! {#expr},
-                (ParamAttr::Value(expr), false) => quote! {#ident: #expr},
+                (ParamAttr::Value(_), true) => quote! {#value},
+                (ParamAttr::Value(_), false) => quote! {#ident: #value},
             },
         }
     }
+
+    fn to_default_value(&self) -> Option<proc_macro2::TokenStream> {
+        let StructField {
+            default_value,
+            self_path,
+            in_const,
+            ..
+        } = &**self.inner();
+        let tokens = match default_value.to_value_tokens()? {
+            tokens if *in_const => quote! {const { #tokens }},
+            tokens => tokens,
+        };
+
+        Some(match self_path {
+            Some(path) => super::params::replace_self(tokens, path),
+            None => tokens,
+        })
+    }
 }
 
 impl StripAttributes for StructFields {
     type Original = syn::Fields;
 
     fn strip_attributes(&self) -> Self::Original {
-        let x = self.fields.first().expect("at least one field expected");
+        // structs without fields are rejected before this point
+        let is_tuple = self.fields.first().is_some_and(|f| f.is_tuple);
 
         let fields = self
             .fields
@@ -161,7 +210,7 @@ impl StripAttributes for StructFields {
                 attrs: f
                     .attrs
                     .iter()
-                    .filter(|a| !a.path().is_ident(crate::DEFAULT_HELPER_ATTR))
+                    .filter(|a| !crate::is_helper_attr(a, &self.helper))
                     .cloned()
                     .collect::<Vec<_>>(),
                 vis: f.vis.clone(),
@@ -176,7 +225,7 @@ impl StripAttributes for StructFields {
             })
             .collect();
 
-        match x.is_tuple {
+        match is_tuple {
             true => syn::Fields::Unnamed(syn::FieldsUnnamed {
                 paren_token: Default::default(),
                 unnamed: fields,
@@ -192,13 +241,14 @@ impl StripAttributes for StructFields {
 impl ToDocInfo for StructField {
     fn to_doc_info(&self) -> DocInfo {
         DocInfo {
-            ident: self.ident.to_string(),
+            ident: self.name.clone(),
             ty: self.ty.to_token_stream().to_string(),
             default_value: match &self.default_value {
                 ParamAttr::None => None,
                 ParamAttr::Default => Some("Default::default()".to_string()),
                 ParamAttr::Value(expr) => Some(expr.to_token_stream().to_string()),
             },
+            doc: self.doc.clone(),
         }
     }
 }
@@ -208,27 +258,65 @@ impl StructFields {
     pub fn from_named(
         ident: syn::Ident,
         fields: Punctuated<syn::Field, syn::Token![,]>,
+        helper: &syn::Ident,
     ) -> Result<Self, syn::Error> {
-        let fields = fields
-            .into_iter()
-            .map(|f| StructField::from_field_type(f, None))
-            .collect::<Result<_, _>>()?;
+        let fields = crate::collect_results(
+            fields
+                .into_iter()
+                .map(|f| StructField::from_field_type(f, None, helper)),
+        )?;
+
+        Ok(Self {
+            ident,
+            fields: StructField::assign_metavars(fields),
+            helper: helper.clone(),
+        })
+    }
 
-        Ok(Self { ident, fields })
+    /// Expand `Self` in the default values to `path`, which resolves where the macro is called.
+    pub fn resolve_self(&mut self, path: proc_macro2::TokenStream) {
+        for field in self.fields.iter_mut() {
+            field.self_path = Some(path.clone());
+        }
+    }
+
+    /// Fields with a default but no description or doc comment, along with the span of their name.
+    pub fn undocumented_defaults(&self) -> impl Iterator<Item = (proc_macro2::Span, &str)> {
+        self.fields
+            .iter()
+            .filter(|f| !f.dot_dot && !matches!(f.default_value, ParamAttr::None))
+            .filter(|f| f.doc.is_none() && !f.attrs.iter().any(|a| a.path().is_ident("doc")))
+            .map(|f| (f.ident.span(), f.name.as_str()))
+    }
+
+    /// Evaluate every default value in an inline `const` block, which only compiles for const expressions.
+    pub fn require_const(&mut self) -> Result<(), syn::Error> {
+        for field in self.fields.iter_mut().filter(|f| !f.dot_dot) {
+            super::check_const_default(&field.default_value, field.ident.span(), &field.name)?;
+            field.in_const = true;
+        }
+
+        Ok(())
     }
 
     /// Parse unnamed fields
     pub fn from_unnamed(
         ident: syn::Ident,
         fields: Punctuated<syn::Field, syn::Token![,]>,
+        helper: &syn::Ident,
     ) -> Result<Self, syn::Error> {
-        let fields = fields
-            .into_iter()
-            .enumerate()
-            .map(|(idx, field)| StructField::from_field_type(field, Some(idx)))
-            .collect::<Result<_, _>>()?;
-
-        Ok(Self { ident, fields })
+        let fields = crate::collect_results(
+            fields
+                .into_iter()
+                .enumerate()
+                .map(|(idx, field)| StructField::from_field_type(field, Some(idx), helper)),
+        )?;
+
+        Ok(Self {
+            ident,
+            fields: StructField::assign_metavars(fields),
+            helper: helper.clone(),
+        })
     }
 
     /// Returns the first non-default item after the first default item, if any.
@@ -240,6 +328,24 @@ impl StructFields {
 
         iter.find(|f| matches!(f.default_value, ParamAttr::None))
     }
+
+    /// Returns the first default item and the first non-default item after it,
+    /// if the defaults are not all trailing.
+    pub fn first_misplaced_default(&self) -> Option<(&StructField, &StructField)> {
+        let invalid = self.first_invalid()?;
+        let default = self
+            .fields
+            .iter()
+            .find(|f| !matches!(f.default_value, ParamAttr::None))?;
+
+        Some((default, invalid))
+    }
+}
+
+impl Permutable for StructField {
+    fn is_default(&self) -> bool {
+        self.default_value.is_default()
+    }
 }
 
 impl StructField {
@@ -247,43 +353,21 @@ impl StructField {
     pub fn from_field_type(
         field: syn::Field,
         tuple_elem: Option<usize>,
+        helper: &syn::Ident,
     ) -> Result<Self, syn::Error> {
         // look for default attr
-        let mut default_value = ParamAttr::None;
-        if !field.attrs.is_empty() {
-            for attr in &field.attrs {
-                if attr.path().is_ident(crate::DEFAULT_HELPER_ATTR) {
-                    let meta = attr.meta.clone();
-
-                    match meta {
-                        syn::Meta::Path(_) => default_value = ParamAttr::Default,
-                        syn::Meta::List(l) => {
-                            let l_span = l.span();
-
-                            let first_item = l.tokens.into_iter().next().ok_or(syn::Error::new(
-                                l_span,
-                                "expected at least 1 item in metalist",
-                            ))?;
-
-                            let e: syn::Expr = syn::parse2(first_item.to_token_stream())?;
-                            default_value = ParamAttr::Value(e);
-                        }
-                        syn::Meta::NameValue(nv) => {
-                            let e = syn::Error::new(
-                                    nv.span(),
-                                    format!("name-values are not supported. Use #[{}] or #[{}(CONST_EXPRESSION)] instead.",
-                                        crate::DEFAULT_HELPER_ATTR,
-                                        crate::DEFAULT_HELPER_ATTR
-                                    ),
-                                );
-                            return Err(e);
-                        }
-                    }
-
-                    break;
-                }
-            }
+        let item = match &field.ident {
+            Some(ident) => format!("field `{}`", ident),
+            None => format!("field `{}`", tuple_elem.unwrap_or_default()),
         };
+        let (default_value, options) = ParamAttr::from_attrs(&field.attrs, helper, &item)?;
+        if let Some(case) = options.cases.first() {
+            return Err(syn::Error::new(
+                case.span(),
+                "`cases` are only tested for function parameters",
+            ));
+        }
+        let doc = options.doc;
 
         let res = match tuple_elem {
             Some(mut tup_id) => {
@@ -306,43 +390,86 @@ impl StructField {
 
                 assert!(!tup_ident.is_empty());
 
+                let ident = syn::Ident::new(&tup_ident, field.ty.span());
+
                 Self {
                     vis: field.vis,
                     attrs: field.attrs,
-                    ident: syn::Ident::new(&tup_ident, field.ty.span()),
+                    metavar: Self::default_metavar(&tup_ident, &ident),
+                    name: tup_ident,
+                    ident,
                     is_tuple: true,
                     ty: field.ty,
                     default_value,
+                    doc,
+                    self_path: None,
+                    in_const: false,
+                    dot_dot: false,
+                }
+            }
+            None => {
+                let ident = field.ident.ok_or_else(|| {
+                    syn::Error::new(field.ty.span(), "named field must have an identifier")
+                })?;
+
+                let name = ident.to_string();
+
+                Self {
+                    vis: field.vis,
+                    attrs: field.attrs,
+                    metavar: Self::default_metavar(&name, &ident),
+                    name,
+                    ident,
+                    is_tuple: false,
+                    ty: field.ty,
+                    default_value,
+                    doc,
+                    self_path: None,
+                    in_const: false,
                     dot_dot: false,
                 }
             }
-            None => Self {
-                vis: field.vis,
-                attrs: field.attrs,
-                ident: field.ident.expect("named field must have an identifier"),
-                is_tuple: false,
-                ty: field.ty,
-                default_value,
-                dot_dot: false,
-            },
         };
 
         Ok(res)
     }
 
+    /// Metavariable for a field, before checking for collisions with other fields.
+    fn default_metavar(name: &str, ident: &syn::Ident) -> syn::Ident {
+        super::metavar_ident(&super::metavar_name(name), ident.span())
+    }
+
+    /// Give every field a unique metavariable.
+    fn assign_metavars(mut fields: Vec<Self>) -> Vec<Self> {
+        let names = fields.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+
+        for (field, metavar) in fields.iter_mut().zip(super::unique_metavars(&names, &[])) {
+            field.metavar = super::metavar_ident(&metavar, field.ident.span());
+        }
+
+        fields
+    }
+
     /// Constructs a `StructField` that represents `..`.
     /// All other fields are irrelevant.
     pub fn dot_dot() -> Self {
+        let ident = syn::Ident::new(
+            "___DOT_DOT_NO_COLLISIONS_DOT_DOT___",
+            proc_macro2::Span::call_site(),
+        );
+
         Self {
             vis: syn::Visibility::Inherited,
             attrs: vec![],
-            ident: syn::Ident::new(
-                "___DOT_DOT_NO_COLLISIONS_DOT_DOT___",
-                proc_macro2::Span::call_site(),
-            ),
+            metavar: ident.clone(),
+            name: ident.to_string(),
+            ident,
             is_tuple: false,
             ty: syn::parse_quote! {u8},
             default_value: ParamAttr::None,
+            doc: None,
+            self_path: None,
+            in_const: false,
             dot_dot: true,
         }
     }
@@ -379,7 +506,8 @@ mod tests {
             syn::Fields::Unnamed(_) | syn::Fields::Unit => panic!("item must be named struct"),
         };
 
-        let fields = StructFields::from_named(item_struct.ident, fields.named).unwrap();
+        let fields =
+            StructFields::from_named(item_struct.ident, fields.named, &default_attr).unwrap();
         // let fields = match fields {
         //     Ok(f) => f,
         //     Err(e) => {
@@ -411,7 +539,8 @@ mod tests {
             syn::Fields::Unit => panic!("expected unnamed fields"),
         };
 
-        let fields = StructFields::from_unnamed(item_struct.ident, fields.unnamed).unwrap();
+        let fields =
+            StructFields::from_unnamed(item_struct.ident, fields.unnamed, &default_attr).unwrap();
 
         let inner = fields.fields;
 
@@ -441,7 +570,8 @@ mod tests {
             syn::Fields::Unnamed(_) | syn::Fields::Unit => panic!("item must be named struct"),
         };
 
-        let fields = StructFields::from_named(item_struct.ident, fields.named).unwrap();
+        let fields =
+            StructFields::from_named(item_struct.ident, fields.named, &default_attr).unwrap();
 
         let first_invalid = fields.first_invalid();
 
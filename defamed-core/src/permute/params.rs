@@ -0,0 +1,1157 @@
+//! Function parameters with default values, parsed from a signature.
+
+use std::{fmt::Debug, rc::Rc};
+
+use quote::{quote, ToTokens};
+use syn::spanned::Spanned;
+
+use crate::traits::{PatternSyntax, ToDocInfo, ToMacroPattern};
+
+use super::{ParamAttr, Permutable, PermutedItem};
+
+/// Metavariable name for the receiver of a method, which no parameter metavariable is given
+pub const RECEIVER_METAVAR: &str = "self_val";
+
+/// Parsed function parameters
+#[derive(Clone)]
+pub struct FunctionParams {
+    /// Receiver as written in the signature, without the helper attribute
+    receiver: Option<syn::Receiver>,
+    /// Parameters in declaration order, without the receiver
+    pub params: Vec<FunctionParam>,
+    /// Name of the helper attribute
+    helper: syn::Ident,
+}
+
+/// Default function parameter
+#[derive(Clone)]
+pub struct FunctionParam {
+    /// Param name
+    pat: syn::Pat,
+    /// Param name rendered once, as written in the signature
+    name: String,
+    ty: syn::Type,
+    attrs: Vec<syn::Attribute>,
+    /// Metavariable that captures the argument in the generated macro
+    metavar: syn::Ident,
+    /// A const that can be used as a default value
+    pub default_value: ParamAttr,
+    /// Description given in the default attribute
+    doc: Option<String>,
+    /// Values tested in place of the default by `test_matrix`
+    cases: Vec<syn::Expr>,
+    /// Type that `Self` in the default value is expanded to, where the macro is called
+    self_path: Option<proc_macro2::TokenStream>,
+    /// Default value is evaluated in an inline `const` block, for `const_defaults`
+    in_const: bool,
+    /// Position among the generic arguments of the call, for const parameters with a default
+    generic_position: Option<usize>,
+    /// Variadic tail of a foreign function, passed to the macro as a list of arguments
+    variadic: bool,
+}
+
+impl Debug for FunctionParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FunctionParam")
+            .field("pat", &self.name)
+            .field("ty", &self.ty.to_token_stream().to_string())
+            .field("default_value", &self.default_value)
+            .finish()
+    }
+}
+
+impl Debug for ParamAttr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Default => write!(f, "Default"),
+            Self::Value(arg0) => write!(f, "Value({})", arg0.to_token_stream()),
+        }
+    }
+}
+
+// structural comparison, ignoring spans.
+// the rendered names are compared first, as they tell apart the parameters of a signature
+impl PartialEq for FunctionParam {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.ty == other.ty && self.default_value == other.default_value
+    }
+}
+
+impl ToMacroPattern for PermutedItem<Rc<FunctionParam>> {
+    fn to_macro_pattern_with(&self, syntax: PatternSyntax) -> Option<proc_macro2::TokenStream> {
+        let FunctionParam {
+            pat,
+            metavar,
+            variadic,
+            ..
+        } = &**self.inner();
+
+        // variadic arguments are listed in brackets, to tell them apart from the other arguments
+        let value = match variadic {
+            true => {
+                let capture = syntax.capture(metavar);
+                quote! {[$(#capture),* $(,)?]}
+            }
+            false => syntax.capture(metavar),
+        };
+
+        match self {
+            PermutedItem::Positional(_) => Some(value),
+            PermutedItem::Named(_) => Some(syntax.named(pat, value)),
+            PermutedItem::Default(_) => None,
+        }
+    }
+
+    fn to_func_call_pattern(&self) -> proc_macro2::TokenStream {
+        let FunctionParam {
+            pat,
+            metavar,
+            default_value,
+            variadic,
+            ..
+        } = &**self.inner();
+
+        match self {
+            PermutedItem::Positional(_) | PermutedItem::Named(_) => match variadic {
+                true => quote! {$($#metavar),*},
+                false => quote! {$#metavar},
+            },
+            PermutedItem::Default(_) => match default_value {
+                ParamAttr::None => {
+                    syn::Error::new(pat.span(), "default value must be present").to_compile_error()
+                }
+                _ => self.to_default_value().expect("default value is present"),
+            },
+        }
+    }
+
+    fn to_default_value(&self) -> Option<proc_macro2::TokenStream> {
+        let param = &**self.inner();
+        let tokens = param.default_tokens()?;
+
+        Some(match &param.self_path {
+            Some(path) => replace_self(tokens, path),
+            None => tokens,
+        })
+    }
+
+    fn generic_position(&self) -> Option<usize> {
+        self.inner().generic_position
+    }
+
+    fn is_variadic(&self) -> bool {
+        self.inner().variadic
+    }
+}
+
+/// Replace every `Self` among `tokens` with `path`.
+pub(crate) fn replace_self(
+    tokens: proc_macro2::TokenStream,
+    path: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    tokens
+        .into_iter()
+        .flat_map(|token| match token {
+            proc_macro2::TokenTree::Ident(ident) if ident == "Self" => path.clone(),
+            proc_macro2::TokenTree::Group(group) => {
+                let mut replaced =
+                    proc_macro2::Group::new(group.delimiter(), replace_self(group.stream(), path));
+                replaced.set_span(group.span());
+                proc_macro2::TokenTree::Group(replaced).into()
+            }
+            other => other.into(),
+        })
+        .collect()
+}
+
+/// Whether `ty` names `PhantomData`, with or without a path.
+fn is_phantom_data(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "PhantomData"),
+        _ => false,
+    }
+}
+
+/// Span of the first `Self` among `tokens`.
+fn find_self(tokens: proc_macro2::TokenStream) -> Option<proc_macro2::Span> {
+    tokens.into_iter().find_map(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) if ident == "Self" => Some(ident.span()),
+        proc_macro2::TokenTree::Group(group) => find_self(group.stream()),
+        _ => None,
+    })
+}
+
+impl ToDocInfo for FunctionParam {
+    fn to_doc_info(&self) -> crate::traits::DocInfo {
+        crate::traits::DocInfo {
+            ident: self.name.clone(),
+            ty: self.ty.to_token_stream().to_string(),
+            default_value: match &self.default_value {
+                _ if self.variadic => Some("[]".to_string()),
+                ParamAttr::None => None,
+                ParamAttr::Default => Some("Default::default()".to_string()),
+                ParamAttr::Value(expr) => Some(expr.to_token_stream().to_string()),
+            },
+            doc: self.doc.clone(),
+        }
+    }
+}
+
+impl FunctionParams {
+    /// Build the parameters of a signature one at a time, instead of parsing them.
+    ///
+    /// ```
+    /// use defamed_core::permute::params::FunctionParams;
+    /// use syn::parse_quote;
+    ///
+    /// let params = FunctionParams::builder()
+    ///     .param("x", parse_quote!(u32))
+    ///     .default("y", parse_quote!(1))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(params.params[0].name(), "x");
+    /// assert_eq!(params.params[1].default_tokens().unwrap().to_string(), "1");
+    /// ```
+    pub fn builder() -> FunctionParamsBuilder {
+        FunctionParamsBuilder {
+            receiver: None,
+            params: vec![],
+            helper: None,
+        }
+    }
+
+    /// Parse the parameters of a signature, with the default values given by the `helper` attribute.
+    ///
+    /// Errors for every invalid parameter are combined into one.
+    pub fn from_punctuated(
+        punctuated: syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma>,
+        helper: &syn::Ident,
+    ) -> Result<Self, syn::Error> {
+        let mut s = Self {
+            receiver: None,
+            params: Vec::new(),
+            helper: helper.clone(),
+        };
+        // all invalid params are reported at once
+        let mut params = Vec::new();
+
+        for punct in punctuated {
+            match punct {
+                syn::FnArg::Receiver(mut recv) => {
+                    if s.receiver.is_some() {
+                        params.push(Err(syn::Error::new(
+                            recv.span(),
+                            "Function cannot accept multiple receivers",
+                        )));
+                        continue;
+                    }
+
+                    // lifetimes, `mut` and explicit types are kept as written
+                    recv.attrs.retain(|a| !crate::is_helper_attr(a, helper));
+                    s.receiver = Some(recv);
+                }
+                syn::FnArg::Typed(t) => params.push(FunctionParam::from_pat_type(t, helper)),
+            }
+        }
+
+        s.params = crate::collect_results(params)?;
+        s.default_phantom_data();
+        s.assign_metavars();
+
+        Ok(s)
+    }
+
+    /// Default the `PhantomData` parameters without a helper attribute to `PhantomData`,
+    /// as long as only default parameters follow them, so the order of parameters stays valid.
+    fn default_phantom_data(&mut self) {
+        for param in self.params.iter_mut().rev() {
+            match &param.default_value {
+                ParamAttr::None if is_phantom_data(&param.ty) => {
+                    param.default_value =
+                        ParamAttr::Value(syn::parse_quote! {::core::marker::PhantomData})
+                }
+                ParamAttr::None => break,
+                ParamAttr::Default | ParamAttr::Value(_) => (),
+            }
+        }
+    }
+
+    /// Add the const parameters of `generics` that have a default value,
+    /// which the macro accepts like trailing default parameters.
+    ///
+    /// Functions cannot declare defaults for their generic parameters,
+    /// so the defaults are removed from `generics`.
+    pub fn extract_const_defaults(
+        &mut self,
+        generics: &mut syn::Generics,
+    ) -> Result<(), syn::Error> {
+        let mut position = 0;
+        let mut defaulted = false;
+
+        for param in generics.params.iter_mut() {
+            match param {
+                syn::GenericParam::Lifetime(_) => continue,
+                syn::GenericParam::Const(c) if c.default.is_some() => {
+                    let name = c.ident.to_string();
+                    if self.params.iter().any(|p| p.name == name) {
+                        return Err(syn::Error::new(
+                            c.ident.span(),
+                            format!(
+                                "const parameter `{}` has the same name as a parameter, \
+                                which the macro cannot tell apart",
+                                name
+                            ),
+                        ));
+                    }
+
+                    let default = c.default.take().expect("default value is present");
+                    c.eq_token = None;
+                    self.params
+                        .push(FunctionParam::from_const_param(c, default, position));
+                    defaulted = true;
+                }
+                other if defaulted => {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        "generic parameters with a default must be trailing",
+                    ))
+                }
+                _ => (),
+            }
+
+            position += 1;
+        }
+
+        self.assign_metavars();
+
+        Ok(())
+    }
+
+    /// Add the variadic tail of a foreign function, which the macro accepts like a trailing default parameter.
+    /// Its arguments are passed in brackets, and none are passed by default.
+    ///
+    /// The tail is named `args` if it has no name.
+    pub fn push_variadic(&mut self, variadic: &syn::Variadic) -> Result<(), syn::Error> {
+        let ident = match &variadic.pat {
+            Some((pat, _)) => match &**pat {
+                syn::Pat::Ident(syn::PatIdent { ident, .. }) => ident.clone(),
+                other => {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        "Only identifiers are supported as variadic patterns",
+                    ))
+                }
+            },
+            None => syn::Ident::new("args", variadic.dots.span()),
+        };
+        let name = ident.to_string();
+
+        if self.params.iter().any(|p| p.name == name) {
+            return Err(syn::Error::new(
+                variadic.span(),
+                format!(
+                    "variadic arguments are passed as `{}`, which is already a parameter, \
+                    name them with `<name>: ...`",
+                    name
+                ),
+            ));
+        }
+
+        self.params.push(FunctionParam {
+            pat: syn::parse_quote! {#ident},
+            ty: syn::Type::Verbatim(quote! {...}),
+            attrs: vec![],
+            metavar: super::metavar_ident(&super::metavar_name(&name), ident.span()),
+            name,
+            default_value: ParamAttr::Value(syn::Expr::Verbatim(Default::default())),
+            doc: None,
+            cases: vec![],
+            self_path: None,
+            in_const: false,
+            generic_position: None,
+            variadic: true,
+        });
+        self.assign_metavars();
+
+        Ok(())
+    }
+
+    /// Whether the parameters end in a variadic tail.
+    pub fn is_variadic(&self) -> bool {
+        self.params.last().is_some_and(|p| p.variadic)
+    }
+
+    /// Assign a unique metavariable to every parameter.
+    fn assign_metavars(&mut self) {
+        let names = self
+            .params
+            .iter()
+            .map(|p| p.name().to_owned())
+            .collect::<Vec<_>>();
+        let metavars = super::unique_metavars(&names, &[RECEIVER_METAVAR]);
+        for (param, metavar) in self.params.iter_mut().zip(metavars) {
+            param.metavar = super::metavar_ident(&metavar, param.pat.span());
+        }
+    }
+
+    /// Converts `Self` back to a punctuated sequence of `syn::FnArg`, with all matching inner attributes stripped.
+    pub fn to_punctuated(&self) -> syn::punctuated::Punctuated<syn::FnArg, syn::token::Comma> {
+        let mut res = self
+            .receiver
+            .iter()
+            .cloned()
+            .map(syn::FnArg::Receiver)
+            .collect::<Vec<_>>();
+
+        // const parameters stay in the generics, and the variadic tail in the signature
+        for param in self
+            .params
+            .iter()
+            .filter(|p| p.generic_position.is_none() && !p.variadic)
+        {
+            let pat = param.pat.to_owned();
+            let ty = param.ty.to_owned();
+            let s_attrs = param
+                .attrs
+                .iter()
+                .filter(|a| !crate::is_helper_attr(a, &self.helper))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let arg = syn::FnArg::Typed(syn::PatType {
+                attrs: s_attrs,
+                pat: Box::new(pat),
+                colon_token: Default::default(),
+                ty: Box::new(ty),
+            });
+
+            res.push(arg);
+        }
+
+        res.into_iter().collect()
+    }
+
+    /// Returns the first non-default item after the first default item, if any.
+    pub fn first_invalid_param(&self) -> Option<&FunctionParam> {
+        let mut iter = self
+            .params
+            .iter()
+            .skip_while(|p| matches!(p.default_value, ParamAttr::None));
+
+        iter.find(|f| matches!(f.default_value, ParamAttr::None))
+    }
+
+    /// Returns the first default item and the first non-default item after it,
+    /// if the defaults are not all trailing.
+    pub fn first_misplaced_default(&self) -> Option<(&FunctionParam, &FunctionParam)> {
+        let invalid = self.first_invalid_param()?;
+        let default = self
+            .params
+            .iter()
+            .find(|p| !matches!(p.default_value, ParamAttr::None))?;
+
+        Some((default, invalid))
+    }
+
+    /// Span of the first `Self` in a default value, if any.
+    pub fn first_self_default(&self) -> Option<proc_macro2::Span> {
+        self.params
+            .iter()
+            .filter_map(|p| match &p.default_value {
+                ParamAttr::Value(expr) => find_self(expr.to_token_stream()),
+                ParamAttr::None | ParamAttr::Default => None,
+            })
+            .next()
+    }
+
+    /// Expand `Self` in the default values to `path`, which resolves where the macro is called.
+    pub fn resolve_self(&mut self, path: proc_macro2::TokenStream) {
+        for param in self.params.iter_mut() {
+            param.self_path = Some(path.clone());
+        }
+    }
+
+    /// Parameters with a default but no description, along with the span of their name.
+    ///
+    /// Const parameters and variadic arguments cannot be described.
+    pub fn undocumented_defaults(&self) -> impl Iterator<Item = (proc_macro2::Span, &str)> {
+        self.params
+            .iter()
+            .filter(|p| !p.variadic && p.generic_position.is_none())
+            .filter(|p| !matches!(p.default_value, ParamAttr::None) && p.doc.is_none())
+            .map(|p| (p.pat.span(), p.name.as_str()))
+    }
+
+    /// Evaluate every default value in an inline `const` block, which only compiles for const expressions.
+    ///
+    /// Const parameters are already evaluated at compile time, and variadic arguments have no default.
+    pub fn require_const(&mut self) -> Result<(), syn::Error> {
+        for param in self
+            .params
+            .iter_mut()
+            .filter(|p| !p.variadic && p.generic_position.is_none())
+        {
+            super::check_const_default(&param.default_value, param.pat.span(), &param.name)?;
+            param.in_const = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Builder for [FunctionParams], created by [FunctionParams::builder].
+///
+/// The parameters are the same as those parsed from the equivalent signature,
+/// so `PhantomData` parameters followed only by defaults are defaulted as well.
+#[derive(Clone)]
+pub struct FunctionParamsBuilder {
+    receiver: Option<syn::Receiver>,
+    /// Parameters in declaration order, or an error for an invalid name
+    params: Vec<Result<FunctionParam, syn::Error>>,
+    helper: Option<syn::Ident>,
+}
+
+impl FunctionParamsBuilder {
+    /// Receiver of the method, such as `&self`.
+    pub fn receiver(mut self, receiver: syn::Receiver) -> Self {
+        self.receiver = Some(receiver);
+        self
+    }
+
+    /// Name of the helper attribute, [DEFAULT_HELPER_ATTR](crate::DEFAULT_HELPER_ATTR) if not set.
+    ///
+    /// The attribute is stripped from the receiver and parameters when converting them back to a signature.
+    pub fn helper(mut self, helper: syn::Ident) -> Self {
+        self.helper = Some(helper);
+        self
+    }
+
+    /// Add a required parameter.
+    pub fn param(self, name: &str, ty: syn::Type) -> Self {
+        self.push(name, ty, ParamAttr::None)
+    }
+
+    /// Add a parameter defaulting to `value`, whose type is left to be inferred (`_`).
+    ///
+    /// Use [typed_default](Self::typed_default) if the parameters are converted back to a signature.
+    pub fn default(self, name: &str, value: syn::Expr) -> Self {
+        self.push(
+            name,
+            syn::Type::Infer(syn::parse_quote! {_}),
+            ParamAttr::Value(value),
+        )
+    }
+
+    /// Add a parameter of type `ty` defaulting to `value`, like `#[def(value)]`.
+    pub fn typed_default(self, name: &str, ty: syn::Type, value: syn::Expr) -> Self {
+        self.push(name, ty, ParamAttr::Value(value))
+    }
+
+    /// Add a parameter defaulting to the [Default] value of `ty`, like `#[def]`.
+    pub fn default_trait(self, name: &str, ty: syn::Type) -> Self {
+        self.push(name, ty, ParamAttr::Default)
+    }
+
+    fn push(mut self, name: &str, ty: syn::Type, default_value: ParamAttr) -> Self {
+        let param = syn::parse_str::<syn::Ident>(name)
+            .map(|ident| FunctionParam::from_ident(ident, ty, default_value))
+            .map_err(|e| {
+                syn::Error::new(
+                    e.span(),
+                    format!("`{}` is not a valid parameter name", name),
+                )
+            });
+
+        self.params.push(param);
+        self
+    }
+
+    /// Build the parameters, or return the errors for every invalid name combined into one.
+    pub fn build(self) -> Result<FunctionParams, syn::Error> {
+        let mut s = FunctionParams {
+            receiver: self.receiver,
+            params: crate::collect_results(self.params)?,
+            helper: self.helper.unwrap_or_else(|| {
+                syn::Ident::new(crate::DEFAULT_HELPER_ATTR, proc_macro2::Span::call_site())
+            }),
+        };
+        s.default_phantom_data();
+        s.assign_metavars();
+
+        Ok(s)
+    }
+}
+
+impl Permutable for FunctionParam {
+    fn is_default(&self) -> bool {
+        self.default_value.is_default()
+    }
+}
+
+impl FunctionParam {
+    /// Create a parameter bound to `ident`, without attributes.
+    fn from_ident(ident: syn::Ident, ty: syn::Type, default_value: ParamAttr) -> Self {
+        let name = ident.to_string();
+
+        Self {
+            pat: syn::parse_quote! {#ident},
+            ty,
+            attrs: vec![],
+            metavar: super::metavar_ident(&super::metavar_name(&name), ident.span()),
+            name,
+            default_value,
+            doc: None,
+            cases: vec![],
+            self_path: None,
+            in_const: false,
+            generic_position: None,
+            variadic: false,
+        }
+    }
+
+    /// Parse a type ascription pattern into `Self`.
+    ///
+    /// Parameters are passed to the macro by name, so they must be bound to a plain identifier.
+    pub fn from_pat_type(punct: syn::PatType, helper: &syn::Ident) -> Result<Self, syn::Error> {
+        let pat = &punct.pat;
+        let ty = &punct.ty;
+
+        // the name is read from the identifier itself, the pattern may be wrapped
+        // in an invisible group when the item is produced by a `macro_rules!` template
+        let name = match &**pat {
+            syn::Pat::Ident(syn::PatIdent {
+                by_ref: None,
+                mutability: None,
+                subpat: None,
+                ident,
+                ..
+            }) => ident.to_string(),
+            syn::Pat::Wild(_) => "_".to_owned(),
+            other => {
+                return Err(syn::Error::new(
+                    other.span(),
+                    "Only identifiers are supported as parameter patterns, \
+                    bind the parameter to a name and destructure or rebind it in the body",
+                ))
+            }
+        };
+
+        let (default_value, options) =
+            ParamAttr::from_attrs(&punct.attrs, helper, &format!("parameter `{}`", name))?;
+
+        Ok(Self {
+            pat: *pat.clone(),
+            ty: *ty.clone(),
+            attrs: punct.attrs,
+            metavar: super::metavar_ident(&super::metavar_name(&name), pat.span()),
+            name,
+            default_value,
+            doc: options.doc,
+            cases: options.cases,
+            self_path: None,
+            in_const: false,
+            generic_position: None,
+            variadic: false,
+        })
+    }
+
+    /// Create a parameter from a const parameter with a `default` value,
+    /// at `position` among the generic arguments of the call.
+    fn from_const_param(param: &syn::ConstParam, default: syn::Expr, position: usize) -> Self {
+        let ident = &param.ident;
+        let name = ident.to_string();
+
+        Self {
+            pat: syn::parse_quote! {#ident},
+            ty: param.ty.clone(),
+            attrs: vec![],
+            metavar: super::metavar_ident(&super::metavar_name(&name), ident.span()),
+            name,
+            default_value: ParamAttr::Value(default),
+            doc: None,
+            cases: vec![],
+            self_path: None,
+            in_const: false,
+            generic_position: Some(position),
+            variadic: false,
+        }
+    }
+
+    /// Span of the parameter pattern
+    pub fn inner_span(&self) -> proc_macro2::Span {
+        self.pat.span()
+    }
+
+    /// Param type
+    pub fn ty(&self) -> &syn::Type {
+        &self.ty
+    }
+
+    /// Param name, as written in the signature
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Default value as passed to the function, without expanding `Self`.
+    pub fn default_tokens(&self) -> Option<proc_macro2::TokenStream> {
+        let tokens = self.default_value.to_value_tokens()?;
+
+        Some(match self.in_const {
+            true => quote! {const { #tokens }},
+            false => tokens,
+        })
+    }
+
+    /// Values tested in place of the default, given by `cases(...)`
+    pub fn cases(&self) -> &[syn::Expr] {
+        &self.cases
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proc_macro2::Span;
+    use quote::quote;
+    use syn::{punctuated::Punctuated, token::Comma, FnArg};
+
+    #[test]
+    fn test_init_params() {
+        let default_ident =
+            syn::Ident::new(crate::DEFAULT_HELPER_ATTR, proc_macro2::Span::call_site());
+
+        let tokens = vec![
+            quote! { a: i32 },
+            quote! { b: u8 },
+            quote! { #[#default_ident] c: usize },
+            quote! { #[#default_ident(42)] d: i64 },
+        ];
+
+        let punct: Punctuated<FnArg, Comma> = tokens
+            .into_iter()
+            .map(|t| syn::parse2::<FnArg>(t).unwrap())
+            .collect();
+
+        let params = FunctionParams::from_punctuated(punct, &default_ident).unwrap();
+
+        assert_eq!(params.params.len(), 4);
+    }
+
+    #[test]
+    fn test_namespaced_helper_attr() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[defamed::def] b: i32, #[defamed::def(5)] c: i32, #[other::def] d: i32) {}
+        })
+        .unwrap();
+
+        let params = FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).unwrap();
+
+        assert!(matches!(params.params[1].default_value, ParamAttr::Default));
+        assert!(matches!(
+            params.params[2].default_value,
+            ParamAttr::Value(_)
+        ));
+        assert!(matches!(params.params[3].default_value, ParamAttr::None));
+
+        // namespaced helpers are stripped from the original signature
+        let stripped = params.to_punctuated();
+        assert_eq!(
+            stripped.to_token_stream().to_string(),
+            quote! {a: i32, b: i32, c: i32, #[other::def] d: i32}.to_string()
+        );
+    }
+
+    #[test]
+    fn test_template_params() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+
+        // `$name:pat` and `$ty:ty` fragments are passed on in invisible groups
+        let pat = proc_macro2::Group::new(proc_macro2::Delimiter::None, quote! { lhs });
+        let ty = proc_macro2::Group::new(proc_macro2::Delimiter::None, quote! { i32 });
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item(#pat: #ty, #[def] r#type: #ty) {}
+        })
+        .unwrap();
+
+        let params = FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).unwrap();
+
+        assert_eq!(params.params[0].name(), "lhs");
+        assert_eq!(params.params[0].metavar.to_string(), "lhs_val");
+        assert_eq!(params.params[1].name(), "r#type");
+        assert_eq!(params.params[1].metavar.to_string(), "type_val");
+    }
+
+    #[test]
+    fn test_custom_helper_attr() {
+        let helper = syn::Ident::new("opt", Span::call_site());
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def] b: i32, #[opt(5)] c: i32, #[defamed::opt] d: i32) {}
+        })
+        .unwrap();
+
+        let params = FunctionParams::from_punctuated(item_fn.sig.inputs, &helper).unwrap();
+
+        assert!(matches!(params.params[1].default_value, ParamAttr::None));
+        assert!(matches!(
+            params.params[2].default_value,
+            ParamAttr::Value(_)
+        ));
+        assert!(matches!(params.params[3].default_value, ParamAttr::Default));
+    }
+
+    #[test]
+    fn test_default_doc() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item(
+                #[def(30, doc = "timeout in seconds")] a: u64,
+                #[def(doc = "retry on failure")] b: bool,
+                #[def(-1)] c: i32,
+            ) {}
+        })
+        .unwrap();
+
+        let params = FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).unwrap();
+        let docs = params
+            .params
+            .iter()
+            .map(|p| p.to_doc_info().doc)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            docs,
+            [
+                Some("timeout in seconds".to_string()),
+                Some("retry on failure".to_string()),
+                None
+            ]
+        );
+        assert!(matches!(
+            params.params[0].default_value,
+            ParamAttr::Value(_)
+        ));
+        assert!(matches!(params.params[1].default_value, ParamAttr::Default));
+        assert_eq!(params.params[2].to_doc_info().default_value.unwrap(), "- 1");
+
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item(#[def(30, title = "timeout")] a: u64) {}
+        })
+        .unwrap();
+        assert!(FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).is_err());
+    }
+
+    #[test]
+    fn test_default_cases() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item(
+                #[def(30, cases(0, 60))] a: u64,
+                #[def(cases(true), doc = "retry on failure")] b: bool,
+                #[def(1)] c: i32,
+            ) {}
+        })
+        .unwrap();
+
+        let params = FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).unwrap();
+        let cases = params
+            .params
+            .iter()
+            .map(|p| p.cases().len())
+            .collect::<Vec<_>>();
+
+        assert_eq!(cases, [2, 1, 0]);
+        assert!(matches!(params.params[1].default_value, ParamAttr::Default));
+        assert_eq!(
+            params.params[1].to_doc_info().doc.as_deref(),
+            Some("retry on failure")
+        );
+
+        for invalid in [
+            quote! { fn item(#[def(1, cases(2), cases(3))] a: i32) {} },
+            quote! { fn item(#[def(1, cases = 2)] a: i32) {} },
+        ] {
+            let item_fn: syn::ItemFn = syn::parse2(invalid).unwrap();
+            assert!(FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).is_err());
+        }
+    }
+
+    #[test]
+    fn test_pattern_params() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+
+        for inputs in [
+            quote! {mut a: i32},
+            quote! {(a, b): (i32, i32)},
+            quote! {Wrapper(a): Wrapper},
+            quote! {a @ 1..=2: i32},
+        ] {
+            let item_fn: syn::ItemFn = syn::parse2(quote! {fn item(#inputs) {}}).unwrap();
+            assert!(FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).is_err());
+        }
+
+        let item_fn: syn::ItemFn = syn::parse2(quote! {fn item(a: i32, _: i32) {}}).unwrap();
+        assert!(FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).is_ok());
+    }
+
+    #[test]
+    fn test_multiple_receivers() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let punct: Punctuated<FnArg, Comma> = vec![quote! { &self }, quote! { self }]
+            .into_iter()
+            .map(|t| syn::parse2::<FnArg>(t).unwrap())
+            .collect();
+
+        assert!(FunctionParams::from_punctuated(punct, &default_attr).is_err());
+    }
+
+    #[test]
+    fn test_receivers_round_trip() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+
+        for receiver in [
+            quote! {self},
+            quote! {mut self},
+            quote! {&self},
+            quote! {&'a mut self},
+            quote! {self: &'a Self},
+            quote! {mut self: Box<Self>},
+            quote! {self: core::pin::Pin<&'a mut Self>},
+        ] {
+            let item_fn: syn::ItemFn = syn::parse2(quote! {
+                fn item<'a>(#receiver, a: &'a str, b: &str, #[def] c: Option<&'a str>) {}
+            })
+            .unwrap();
+            let params =
+                FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).unwrap();
+
+            assert_eq!(
+                params.to_punctuated().to_token_stream().to_string(),
+                quote! {#receiver, a: &'a str, b: &str, c: Option<&'a str>}.to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_const_defaults() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let mut item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item<'a, T, const N: usize = 4, const M: u8 = 0>(a: &'a T, #[def] b: i32) {}
+        })
+        .unwrap();
+        let mut params =
+            FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).unwrap();
+        params
+            .extract_const_defaults(&mut item_fn.sig.generics)
+            .unwrap();
+
+        let names = params.params.iter().map(|p| p.name()).collect::<Vec<_>>();
+        assert_eq!(names, ["a", "b", "N", "M"]);
+        assert_eq!(params.params[2].generic_position, Some(1));
+        assert_eq!(params.params[3].generic_position, Some(2));
+
+        // defaults are removed from the generics, and const parameters from the arguments
+        assert_eq!(
+            item_fn.sig.generics.to_token_stream().to_string(),
+            quote! {<'a, T, const N: usize, const M: u8>}.to_string()
+        );
+        assert_eq!(
+            params.to_punctuated().to_token_stream().to_string(),
+            quote! {a: &'a T, b: i32}.to_string()
+        );
+
+        // parameters without a default cannot follow
+        let mut generics: syn::Generics = syn::parse_quote! {<const N: usize = 4, T>};
+        let mut params =
+            FunctionParams::from_punctuated(Default::default(), &default_attr).unwrap();
+        assert!(params.extract_const_defaults(&mut generics).is_err());
+
+        // and neither can parameters of the same name
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item<const N: usize = 4>(N: usize) {}
+        })
+        .unwrap();
+        let mut params =
+            FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).unwrap();
+        let mut generics = item_fn.sig.generics;
+        assert!(params.extract_const_defaults(&mut generics).is_err());
+    }
+
+    #[test]
+    fn test_phantom_data_defaults() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item(
+                a: PhantomData<u8>,
+                b: i32,
+                c: core::marker::PhantomData<u8>,
+                #[def] d: i32,
+                e: PhantomData<u16>,
+            ) {}
+        })
+        .unwrap();
+        let params = FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).unwrap();
+        let phantom: syn::Expr = syn::parse_quote! {::core::marker::PhantomData};
+
+        // only trailing parameters are defaulted
+        assert_eq!(params.params[0].default_value, ParamAttr::None);
+        assert_eq!(params.params[1].default_value, ParamAttr::None);
+        assert_eq!(
+            params.params[2].default_value,
+            ParamAttr::Value(phantom.clone())
+        );
+        assert_eq!(params.params[3].default_value, ParamAttr::Default);
+        assert_eq!(params.params[4].default_value, ParamAttr::Value(phantom));
+    }
+
+    #[test]
+    fn test_aggregate_param_errors() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item(a: i32, #[def = 1] b: i32, #[def()] c: i32, #[def(2)] d: i32) {}
+        })
+        .unwrap();
+
+        let err = match FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr) {
+            Ok(_) => panic!("params must be invalid"),
+            Err(e) => e,
+        };
+
+        assert_eq!(err.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn test_param_equality() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let parse = |tokens: proc_macro2::TokenStream| match syn::parse2(tokens).unwrap() {
+            FnArg::Typed(t) => FunctionParam::from_pat_type(t, &default_attr).unwrap(),
+            FnArg::Receiver(_) => unreachable!(),
+        };
+
+        // spans and formatting do not matter
+        assert_eq!(
+            parse(quote! { x: Vec<u8> }),
+            parse(quote! { x : Vec < u8 > })
+        );
+        assert_eq!(
+            parse(quote! { #[def(1)] x: u8 }),
+            parse(quote! { #[def(1)] x: u8 })
+        );
+
+        assert_ne!(parse(quote! { x: u8 }), parse(quote! { x: u16 }));
+        assert_ne!(parse(quote! { x: u8 }), parse(quote! { #[def] x: u8 }));
+        assert_ne!(
+            parse(quote! { #[def(1)] x: u8 }),
+            parse(quote! { #[def(2)] x: u8 })
+        );
+    }
+
+    #[test]
+    fn test_conflicting_helper_attrs() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let item_fn: syn::ItemFn = syn::parse2(quote! {
+            fn item(#[def] #[defamed::def(3)] x: u32) {}
+        })
+        .unwrap();
+
+        let err = match FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr) {
+            Ok(_) => panic!("params must be invalid"),
+            Err(e) => e,
+        };
+        let messages = err.into_iter().map(|e| e.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            messages,
+            vec![
+                "conflicting `#[def]` attributes on parameter `x`",
+                "first `#[def]` attribute here"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder() {
+        let default_ident = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+        let parsed = FunctionParams::from_punctuated(
+            syn::parse_quote! {
+                &self,
+                a: u32,
+                #[#default_ident(1)] b: u8,
+                #[#default_ident] c: String,
+                d: PhantomData<u8>
+            },
+            &default_ident,
+        )
+        .unwrap();
+        let built = FunctionParams::builder()
+            .receiver(syn::parse_quote! {&self})
+            .param("a", syn::parse_quote! {u32})
+            .typed_default("b", syn::parse_quote! {u8}, syn::parse_quote! {1})
+            .default_trait("c", syn::parse_quote! {String})
+            .param("d", syn::parse_quote! {PhantomData<u8>})
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            format!("{:?}", built.params),
+            format!("{:?}", parsed.params)
+        );
+        assert_eq!(
+            built.to_punctuated().to_token_stream().to_string(),
+            parsed.to_punctuated().to_token_stream().to_string()
+        );
+
+        let inferred = FunctionParams::builder()
+            .default("x", syn::parse_quote! {2})
+            .build()
+            .unwrap();
+        assert_eq!(
+            inferred.to_punctuated().to_token_stream().to_string(),
+            "x : _"
+        );
+
+        let err = FunctionParams::builder()
+            .param("self", syn::parse_quote! {u32})
+            .param("a b", syn::parse_quote! {u32})
+            .build()
+            .err()
+            .unwrap();
+        assert_eq!(err.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn test_first_invalid_param() {
+        let default_attr = syn::Ident::new(crate::DEFAULT_HELPER_ATTR, Span::call_site());
+
+        let item_struct = quote! {
+            fn item(
+                x: i32,
+                #[#default_attr] y: i32,
+                z: i32
+            ) {
+
+            }
+        };
+
+        let item_fn: syn::ItemFn = syn::parse2(item_struct).unwrap();
+
+        let fields = FunctionParams::from_punctuated(item_fn.sig.inputs, &default_attr).unwrap();
+
+        let first_invalid = fields.first_invalid_param();
+
+        println!("first invalid: {:?}", first_invalid);
+
+        match first_invalid {
+            Some(iv) => {
+                assert_eq!(*iv, fields.params[2]);
+            }
+            None => panic!("last field must be invalid"),
+        }
+    }
+}
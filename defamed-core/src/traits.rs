@@ -0,0 +1,172 @@
+//! Conversions of the items of an annotated item into the fragments of its macro.
+
+use proc_macro2 as pm2;
+use quote::{quote, ToTokens};
+
+/// Syntax of the patterns matching the arguments of a macro, selected by the backend rendering them.
+///
+/// | syntax            | positional     | named                  |
+/// |-------------------|----------------|------------------------|
+/// | [`Expr`](Self::Expr)                   | `$value: expr` | `name = $value: expr` |
+/// | [`TokenTree`](Self::TokenTree)         | `$value: tt`   | `name = $value: tt`   |
+/// | [`StructLiteral`](Self::StructLiteral) | `$value: expr` | `name: $value: expr`  |
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PatternSyntax {
+    /// Arguments are expressions, and named with `=`
+    #[default]
+    Expr,
+    /// Arguments are single token trees, which are forwarded without being parsed as expressions.
+    /// Longer arguments must be wrapped in parentheses or braces.
+    TokenTree,
+    /// Arguments are expressions, and named with `:` like the fields of a struct literal
+    StructLiteral,
+}
+
+impl PatternSyntax {
+    /// Fragment specifier the value of an argument is captured with.
+    pub fn fragment(self) -> pm2::Ident {
+        let fragment = match self {
+            Self::Expr | Self::StructLiteral => "expr",
+            Self::TokenTree => "tt",
+        };
+
+        pm2::Ident::new(fragment, pm2::Span::call_site())
+    }
+
+    /// Token between the name of a named argument and its value.
+    pub fn named_separator(self) -> pm2::TokenStream {
+        match self {
+            Self::Expr | Self::TokenTree => quote! {=},
+            Self::StructLiteral => quote! {:},
+        }
+    }
+
+    /// Pattern capturing the value of an argument in `metavar`.
+    pub fn capture(self, metavar: &pm2::Ident) -> pm2::TokenStream {
+        let fragment = self.fragment();
+        quote! {$#metavar: #fragment}
+    }
+
+    /// Pattern matching `value` passed as the argument named `name`.
+    pub fn named(self, name: impl ToTokens, value: pm2::TokenStream) -> pm2::TokenStream {
+        let separator = self.named_separator();
+        quote! {#name #separator #value}
+    }
+}
+
+/// Turn `Self` into fragments of rust code.
+///
+/// Implemented by the permuted parameters and fields of the items annotated by `defamed`,
+/// and by any item a [`Backend`](crate::backend::Backend) generates the arms of a macro for.
+pub trait ToMacroPattern {
+    /// Create a macro match pattern, with the syntax of [pattern_syntax](Self::pattern_syntax).
+    ///
+    /// ```ignore
+    /// macro_rules! some_macro (
+    ///     ($pos_a_val: expr, $pos_b_val: expr) => {
+    ///     //^^^^^^^^^^^^^^^ this is one pattern
+    ///     // a macro match pattern consists of one or more comma-separated patterns.
+    ///     // params that contain a default value and are not used should return None.
+    ///
+    ///     }
+    /// )
+    /// ```
+    fn to_macro_pattern(&self) -> Option<pm2::TokenStream> {
+        self.to_macro_pattern_with(self.pattern_syntax())
+    }
+
+    /// Create a macro match pattern with the given syntax.
+    ///
+    /// Returns `None` for default values that are not passed.
+    fn to_macro_pattern_with(&self, syntax: PatternSyntax) -> Option<pm2::TokenStream>;
+
+    /// Syntax the item is matched with, unless the backend selects another one.
+    fn pattern_syntax(&self) -> PatternSyntax {
+        PatternSyntax::Expr
+    }
+
+    /// Create a function call pattern.
+    ///
+    /// Captured metavariables must be emitted on their own, without being wrapped or re-parsed,
+    /// so that type errors point at the caller's expression.
+    ///
+    /// ```ignore
+    /// macro_rules! some_macro (
+    ///     ($pos_a_val: expr, $pos_b_val: expr) => {
+    ///         function_call($pos_a_val, $pos_b_val)
+    ///     //                ^^^^^^^^^^ this is one pattern
+    ///     // a function call pattern consists of one or more comma-separated patterns
+    ///     }
+    /// )
+    /// ```
+    fn to_func_call_pattern(&self) -> pm2::TokenStream;
+
+    /// The default value expression of the underlying item, regardless of how it is permuted.
+    ///
+    /// Returns `None` for required items.
+    fn to_default_value(&self) -> Option<pm2::TokenStream>;
+
+    /// Position among the generic arguments of the call, for items passed in its turbofish
+    /// instead of its argument list.
+    fn generic_position(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether the item is a variadic tail, whose arguments are passed in brackets.
+    fn is_variadic(&self) -> bool {
+        false
+    }
+}
+
+/// Strip matching attributes from a type.
+/// For function parameters, this is the `#[def]` attribute.
+pub trait StripAttributes {
+    /// The original type
+    type Original;
+
+    /// Copy of `self` without the matching attributes
+    fn strip_attributes(&self) -> Self::Original;
+}
+
+/// Documentation info for an item
+#[derive(Clone, Debug)]
+pub struct DocInfo {
+    /// Name of the item
+    pub ident: String,
+    /// Type of the item, rendered as tokens
+    pub ty: String,
+    /// Default value of the item, rendered as tokens, or `None` if it is required
+    pub default_value: Option<String>,
+    /// Description given in the default attribute
+    pub doc: Option<String>,
+}
+
+impl std::fmt::Display for DocInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.default_value {
+            Some(val) => write!(f, "`{}`: `{}`, defaults to `{}`", self.ident, self.ty, val),
+            None => write!(f, "`{}`: `{}`, required", self.ident, self.ty),
+        }
+    }
+}
+
+/// Document `Self`.
+pub trait ToDocInfo {
+    /// Documentation info for `self`
+    fn to_doc_info(&self) -> DocInfo;
+}
+
+/// Shared items are documented like the item itself.
+impl<T: ToDocInfo> ToDocInfo for std::rc::Rc<T> {
+    fn to_doc_info(&self) -> DocInfo {
+        (**self).to_doc_info()
+    }
+}
+
+// /// Generate all permutations of a set of items.
+// pub trait GeneratePermutations {
+//     type Item;
+
+//     /// Generate all permutations of the items.
+//     fn generate_permutations(&self) -> Vec<Vec<Self::Item>>;
+// }
@@ -15,7 +15,7 @@ mod tests;
 /// assert_eq!(add_default, "base");
 /// assert_eq!(add_known, "base concat");
 /// ```
-#[defamed::defamed(crate)]
+#[defamed::defamed(crate, doctest, alias = "root")]
 pub fn some_root_function<'a>(base: &'a str, #[def] concat: Option<&str>) -> Cow<'a, str> {
     let _ = complex_function!(1, 2);
     let _ = complex_function!(lhs = 2, rhs = 2);
@@ -26,28 +26,133 @@ pub fn some_root_function<'a>(base: &'a str, #[def] concat: Option<&str>) -> Cow
     }
 }
 
+/// This macro is exported under its own name, so it can also be imported
+/// by `#[macro_use] extern crate` consumers.
+#[defamed::defamed(crate, macro_use, doctest)]
+pub fn legacy_function(base: u32, #[def(1, doc = "amount added to the base")] step: u32) -> u32 {
+    base + step
+}
+
+/// Conditionally compiled functions only generate a macro when the function itself is compiled.
+#[defamed::defamed]
+#[cfg(not(test))]
+#[allow(unused)]
+fn cfg_dependent(#[def(1)] value: u8) -> u8 {
+    value
+}
+
+#[defamed::defamed]
+#[cfg(test)]
+fn cfg_dependent(#[def(2)] value: u8) -> u8 {
+    value
+}
+
+/// Skipped functions keep compiling as plain functions, with their defaults removed.
+#[defamed::defamed(skip)]
+pub fn skipped_function(base: u32, #[def(1)] step: u32) -> u32 {
+    base + step
+}
+
 /// It is possible to annotate functions without any parameters,
-/// but nothing useful is generated.
+/// which generates a macro that takes no arguments.
 #[defamed::defamed(crate)]
-fn no_params() {}
+fn no_params() -> i32 {
+    42
+}
 
 pub mod inner {
 
     /// Mask the base value with a mask and shift the result right by `r_shift` bits.
     /// Returns `true` if the LSB of the result is set, `false` otherwise.
-    #[defamed::defamed(inner)]
+    #[defamed::defamed(inner, doctest)]
     pub fn nested_inner_function(base: u8, mask: u8, #[def] r_shift: u8) -> bool {
         let inter = base & mask;
         let shifted = inter >> r_shift;
 
-        let _unit = super::no_params!();
+        let _answer = super::no_params!();
 
         shifted & 1 != 0
     }
+
+    /// Every public function in this module generates a macro
+    #[defamed::defamed(inner)]
+    pub mod geometry {
+        /// Area of a rectangle
+        pub fn area(width: u32, #[def(1)] height: u32) -> u32 {
+            scale(width * height)
+        }
+
+        /// Functions that are annotated separately expand on their own
+        #[defamed::defamed(inner::geometry)]
+        pub fn perimeter(width: u32, #[def(1)] height: u32) -> u32 {
+            scale(2 * (width + height))
+        }
+
+        /// Public functions can opt out of macro generation
+        #[defamed::defamed(skip)]
+        pub fn volume(width: u32, height: u32, #[def(1)] depth: u32) -> u32 {
+            width * height * depth
+        }
+
+        /// Private functions are left untouched
+        fn scale(value: u32) -> u32 {
+            value
+        }
+    }
+
+    /// Macros are as visible as their item
+    #[allow(unused)]
+    pub mod scoped {
+        /// Usable anywhere in the crate, but not exported
+        #[defamed::defamed(inner::scoped)]
+        pub(crate) fn crate_visible(value: u32, #[def(1)] factor: u32) -> u32 {
+            value * factor
+        }
+
+        /// Usable in `inner` and its descendants
+        #[defamed::defamed(inner::scoped)]
+        pub(super) fn parent_visible(value: u32, #[def(2)] factor: u32) -> u32 {
+            value * factor
+        }
+
+        /// Usable in `inner::scoped` and its descendants
+        #[defamed::defamed(inner::scoped)]
+        pub(in crate::inner::scoped) fn module_visible(value: u32, #[def(3)] factor: u32) -> u32 {
+            value * factor
+        }
+
+        /// Usable in this module only
+        #[defamed::defamed]
+        fn private(value: u32, #[def(4)] factor: u32) -> u32 {
+            value * factor
+        }
+
+        pub(crate) fn all_scopes() -> u32 {
+            module_visible!(1) + private!(1)
+        }
+    }
+
+    #[allow(unused)]
+    pub(crate) fn parent_scope() -> u32 {
+        scoped::parent_visible!(1)
+    }
+}
+
+/// Re-exports bring the macro along, under the same or a new name
+pub use inner::geometry::{area as rect_area, perimeter};
+
+mod hidden {
+    /// Functions in private modules are called through the path of their re-export
+    #[defamed::defamed(crate, doctest)]
+    pub fn reexported(value: u32, #[def(1)] offset: u32) -> u32 {
+        value + offset
+    }
 }
 
+pub use hidden::reexported;
+
 /// Some struct definition
-#[defamed::defamed(crate)]
+#[defamed::defamed(crate, doctest)]
 pub struct DefaultStruct<'a> {
     pub index: usize,
     #[def]
@@ -64,11 +169,105 @@ impl<'a> DefaultStruct<'a> {
 }
 
 /// Some struct tuple definition
-#[defamed::defamed(crate)]
+#[defamed::defamed(crate, doctest, self_test)]
 #[derive(Clone, Debug, PartialEq)]
 pub struct DefaultTupleStruct(pub usize, #[def] pub usize, #[def('a')] pub char);
 
+/// A counter that can be stepped by arbitrary amounts
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Counter {
+    pub count: i32,
+}
+
+#[defamed::defamed(readable, self_test)]
+impl Counter {
+    /// Increment the counter, returning the new count
+    #[doc(alias = "add")]
+    pub fn increment(&mut self, #[def(1)] step: i32) -> i32 {
+        self.count += step;
+        self.count
+    }
+
+    /// Peek at the count with an optional offset applied
+    pub fn peek(&self, #[def] offset: i32, #[def(false)] negate: bool) -> i32 {
+        let count = self.count + offset;
+
+        match negate {
+            true => -count,
+            false => count,
+        }
+    }
+
+    /// Map the count, with bounds on the method kept as written
+    pub fn mapped<F, R>(&self, map: F, #[def] offset: i32) -> R
+    where
+        F: FnOnce(i32) -> R,
+        R: std::ops::Add<i32, Output = R>,
+    {
+        map(self.count) + offset
+    }
+
+    /// Methods that only take a receiver get a macro too
+    pub fn is_zero(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Methods can opt out of macro generation
+    #[defamed::defamed(skip)]
+    pub fn reset(&mut self, #[def] count: i32) {
+        self.count = count;
+    }
+
+    /// Associated functions are called through the type
+    pub fn with_count(#[def] count: i32) -> Self {
+        Self { count }
+    }
+
+    /// Count that [`Counter::started`] begins at
+    pub const START: i32 = 10;
+
+    /// Defaults can refer to `Self`, which names the type where the macro is called
+    pub fn started(#[def(Self::START)] count: i32) -> Self {
+        Self { count }
+    }
+}
+
+/// Helper attributes can be namespaced to avoid clashing with other attributes named `def`.
 #[defamed::defamed]
+#[allow(unused)]
+fn namespaced_helper(
+    base: i32,
+    #[defamed::def(10)] offset: i32,
+    #[defamed::def] scale: i32,
+) -> i32 {
+    base + offset * scale
+}
+
+/// Uses a custom helper attribute name.
+#[defamed::defamed(attr = "opt", self_test)]
+#[derive(Debug, PartialEq)]
+#[allow(unused)]
+struct RenamedHelper {
+    base: i32,
+    #[opt(4)]
+    offset: i32,
+}
+
+/// Raw identifiers as parameter names.
+#[defamed::defamed(self_test)]
+#[allow(unused)]
+fn raw_idents(r#type: i32, #[def(1)] r#match: i32) -> i32 {
+    r#type * r#match
+}
+
+/// Parameter names that overlap with the generated metavariable names.
+#[defamed::defamed]
+#[allow(unused)]
+fn overlapping_names(x: i32, #[def(10)] x_val: i32) -> i32 {
+    x - x_val
+}
+
+#[defamed::defamed(self_test)]
 fn complex_function(
     lhs: i32,
     rhs: i32,
@@ -85,16 +284,524 @@ fn complex_function(
     }
 }
 
-// #[defamed::defamed]
-// fn all_default(
-//     #[def(1)] a: i32,
-//     #[def(2)] b: i32,
-//     #[def(3)] c: i32,
-//     // #[def(4)] d: i32,
-//     // #[def(5)] e: i32,
-// ) -> i32 {
-//     a + b + c
-// }
+/// Every parameter has a default, so the macro can be called without arguments.
+#[defamed::defamed]
+#[allow(unused)]
+fn all_default(
+    #[def(1)] a: i32,
+    #[def(2)] b: i32,
+    #[def(3)] c: i32,
+    #[def(4)] d: i32,
+    #[def(5)] e: i32,
+) -> i32 {
+    a + b + c + d + e
+}
+
+/// Many parameters, expanded with a muncher instead of every permutation.
+#[defamed::defamed(strategy = "muncher", self_test)]
+#[allow(unused, clippy::too_many_arguments)]
+fn many_params(
+    base: i32,
+    #[def(1)] a: i32,
+    #[def(2)] b: i32,
+    #[def(3)] c: i32,
+    #[def(4)] d: i32,
+    #[def(5)] e: i32,
+    #[def(6)] f: i32,
+    #[def(7)] g: i32,
+    #[def(8)] h: i32,
+) -> i32 {
+    base * (a + b + c + d + e + f + g + h)
+}
+
+/// Too many parameters to permute, so the strategy is selected automatically.
+#[defamed::defamed]
+#[allow(unused, clippy::too_many_arguments)]
+fn wide_params(
+    a: i32,
+    b: i32,
+    c: i32,
+    d: i32,
+    e: i32,
+    f: i32,
+    #[def(1)] g: i32,
+    #[def(1)] h: i32,
+    #[def(1)] i: i32,
+    #[def(1)] j: i32,
+    #[def(1)] k: i32,
+    #[def(1)] l: i32,
+) -> i32 {
+    a + b + c + d + e + f + g + h + i + j + k + l
+}
+
+/// Struct expanded with a muncher.
+#[defamed::defamed(crate, strategy = "muncher", self_test)]
+#[derive(Debug, PartialEq)]
+pub struct MunchedStruct {
+    pub id: u32,
+    #[def(10)]
+    pub size: u32,
+    #[def]
+    pub name: &'static str,
+}
+
+/// Tuple struct expanded with a muncher.
+#[defamed::defamed(crate, strategy = "muncher", self_test)]
+#[derive(Debug, PartialEq)]
+pub struct MunchedTuple(pub u32, #[def(2)] pub u32);
+
+/// Methods expanded with a muncher.
+#[defamed::defamed(strategy = "muncher", self_test)]
+impl Counter {
+    pub fn scaled(&self, #[def(1)] factor: i32, #[def] offset: i32) -> i32 {
+        self.count * factor + offset
+    }
+}
+
+/// Named arguments sorted into declaration order before matching.
+#[defamed::defamed(strategy = "canonical", self_test)]
+#[allow(unused)]
+fn canonical_params(base: i32, #[def(1)] a: i32, #[def(2)] b: i32, #[def(3)] c: i32) -> i32 {
+    base * 1000 + a * 100 + b * 10 + c
+}
+
+/// Struct with its fields sorted into declaration order before matching.
+#[defamed::defamed(crate, strategy = "canonical", self_test)]
+#[derive(Debug, PartialEq)]
+pub struct CanonicalStruct {
+    pub id: u32,
+    #[def(10)]
+    pub size: u32,
+    #[def]
+    pub name: &'static str,
+}
+
+/// Methods with arguments sorted into declaration order before matching.
+#[defamed::defamed(strategy = "canonical", self_test)]
+impl Counter {
+    pub fn shifted(&self, #[def] left: i32, #[def] right: i32) -> i32 {
+        self.count + left - right
+    }
+}
+
+/// Receivers passed by name, as `self = <receiver>`.
+#[defamed::defamed(receiver = "named", self_test)]
+impl Counter {
+    pub fn distance(&self, #[def] to: i32, #[def(1)] scale: i32) -> i32 {
+        (self.count - to).abs() * scale
+    }
+}
+
+/// Receivers passed by name to a muncher.
+#[defamed::defamed(strategy = "muncher", receiver = "named", self_test)]
+impl Counter {
+    pub fn clamped(&self, #[def(0)] min: i32, #[def(i32::MAX)] max: i32) -> i32 {
+        self.count.clamp(min, max)
+    }
+}
+
+/// Receivers passed by name, with arguments sorted into declaration order.
+#[defamed::defamed(strategy = "canonical", receiver = "named", self_test)]
+impl Counter {
+    pub fn wrapped(&self, #[def(10)] modulus: i32) -> i32 {
+        self.count.rem_euclid(modulus)
+    }
+}
+
+/// A widget with a size
+#[derive(Debug, Default, PartialEq)]
+pub struct Widget {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Methods with extension traits, called postfix with their arguments collected in a struct.
+#[defamed::defamed(crate, ext_trait, self_test)]
+impl Widget {
+    /// Resize the widget, returning the new area
+    pub fn resize(&mut self, width: u32, #[def(1)] height: u32, #[def] scale: Option<u32>) -> u32 {
+        let scale = scale.unwrap_or(1);
+        self.width = width * scale;
+        self.height = height * scale;
+        self.width * self.height
+    }
+
+    /// Amount that [`Widget::grow`] adds to each side
+    pub const GROWTH: u32 = 2;
+
+    /// Grow the widget, returning the new area
+    pub fn grow(&mut self, #[def(Self::GROWTH)] by: u32) -> u32 {
+        self.width += by;
+        self.height += by;
+        self.area()
+    }
+
+    /// Methods without parameters have no extension trait
+    pub fn area(&self) -> u32 {
+        self.width * self.height
+    }
+
+    /// Associated functions are called through the path of the type
+    pub fn square(#[def(1)] side: u32) -> Self {
+        Self {
+            width: side,
+            height: side,
+        }
+    }
+}
+
+/// Arguments collected in a hidden struct.
+#[defamed::defamed(crate, strategy = "args_struct", self_test)]
+pub fn struct_args(base: &'static str, #[def] suffix: String, #[def(1)] repeat: usize) -> String {
+    format!("{}{}", base, suffix).repeat(repeat)
+}
+
+/// Named arguments are only accepted in declaration order.
+#[defamed::defamed(ordered_named, self_test)]
+#[allow(unused)]
+fn ordered_params(lhs: i32, rhs: i32, #[def(1)] scale: i32, #[def] offset: i32) -> i32 {
+    (lhs - rhs) * scale + offset
+}
+
+/// Only positional arguments, with trailing defaults.
+#[defamed::defamed(no_named, self_test)]
+#[allow(unused)]
+fn positional_params(lhs: i32, #[def(1)] scale: i32, #[def] offset: i32) -> i32 {
+    lhs * scale + offset
+}
+
+/// Looks up a limit asynchronously.
+#[allow(unused)]
+async fn fetch_limit() -> u32 {
+    10
+}
+
+/// Defaults of async functions can be awaited, and are evaluated by the returned future.
+#[defamed::defamed(self_test)]
+#[allow(unused)]
+async fn awaited_default(used: u32, #[def(fetch_limit().await)] limit: u32) -> u32 {
+    used.min(limit)
+}
+
+/// Awaited defaults with arguments munched one at a time.
+#[defamed::defamed(strategy = "muncher", self_test)]
+#[allow(unused)]
+async fn awaited_munched(#[def(fetch_limit().await)] limit: u32, #[def(1)] step: u32) -> u32 {
+    limit / step
+}
+
+/// Const parameters with a default are passed like default parameters, in the turbofish of the call.
+#[defamed::defamed]
+#[allow(unused)]
+fn chunk_sums<const N: usize = 2>(data: &[u32], #[def] offset: u32) -> Vec<u32> {
+    data.chunks(N)
+        .map(|chunk| chunk.iter().sum::<u32>() + offset)
+        .collect()
+}
+
+/// Generic parameters before the const parameters are inferred.
+#[defamed::defamed(strategy = "muncher")]
+#[allow(unused)]
+fn padded<T: Copy, const LEN: usize = 3, const FILL: u8 = 0>(items: &[T], pad: T) -> Vec<T> {
+    let mut padded = items.to_vec();
+    padded.resize(items.len().max(LEN) + FILL as usize, pad);
+    padded
+}
+
+/// Trailing `PhantomData` parameters are defaulted without an attribute.
+#[defamed::defamed]
+#[allow(unused)]
+fn tagged<T: Default>(
+    value: u32,
+    #[def(1)] scale: u32,
+    tag: std::marker::PhantomData<T>,
+) -> (u32, T) {
+    (value * scale, T::default())
+}
+
+/// Writes log lines somewhere
+pub trait Logger {
+    /// Prefix of the lines written
+    fn prefix(&self) -> &'static str;
+}
+
+/// A logger that writes nowhere
+pub struct NoopLogger;
+
+impl Logger for NoopLogger {
+    fn prefix(&self) -> &'static str {
+        "noop"
+    }
+}
+
+/// Trait objects coerce from default values, like from the arguments of a call.
+#[defamed::defamed(self_test)]
+#[allow(unused)]
+fn logged(
+    line: &str,
+    #[def(Box::new(NoopLogger))] logger: Box<dyn Logger>,
+    #[def(Some(Box::new(NoopLogger)))] fallback: Option<Box<dyn Logger>>,
+    #[def(Box::new(|line| line.len()))] measure: Box<dyn Fn(&str) -> usize>,
+) -> String {
+    let fallback = fallback.map_or("none", |f| f.prefix());
+    format!(
+        "{}|{}: {} {}",
+        logger.prefix(),
+        fallback,
+        line,
+        measure(line)
+    )
+}
+
+/// Trait object defaults with arguments collected in a struct.
+#[defamed::defamed(strategy = "args_struct", self_test)]
+#[allow(unused)]
+fn logged_struct(
+    line: &'static str,
+    #[def(Box::new(NoopLogger))] logger: Box<dyn Logger>,
+) -> String {
+    format!("{}: {}", logger.prefix(), line)
+}
+
+/// Trait object defaults with arguments munched one at a time.
+#[defamed::defamed(strategy = "muncher", self_test)]
+#[allow(unused)]
+fn logged_munched(line: &str, #[def(&NoopLogger)] logger: &dyn Logger) -> String {
+    format!("{}: {}", logger.prefix(), line)
+}
+
+/// Trait object defaults of struct fields.
+#[defamed::defamed]
+#[allow(unused)]
+struct LoggedTask {
+    name: &'static str,
+    #[def(Box::new(NoopLogger))]
+    logger: Box<dyn Logger>,
+}
+
+#[defamed::defamed]
+#[allow(unused)]
+extern "C" {
+    /// Functions declared in `extern` blocks generate macros as well
+    fn abs(#[def] value: std::ffi::c_int) -> std::ffi::c_int;
+
+    /// Variadic arguments are passed in brackets, and forwarded as they are
+    fn snprintf(
+        buf: *mut std::ffi::c_char,
+        size: usize,
+        #[def(c"%d".as_ptr())] format: *const std::ffi::c_char,
+        args: ...
+    ) -> std::ffi::c_int;
+}
+
+#[defamed::defamed(strategy = "canonical")]
+#[allow(unused)]
+extern "C" {
+    /// Variadic arguments with arguments sorted into declaration order
+    #[link_name = "snprintf"]
+    fn format_into(
+        buf: *mut std::ffi::c_char,
+        #[def(16)] size: usize,
+        #[def(c"%d".as_ptr())] format: *const std::ffi::c_char,
+        ...
+    ) -> std::ffi::c_int;
+}
+
+/// Bounds in `where` clauses are kept as written, and inferred at the call site.
+#[defamed::defamed]
+#[allow(unused)]
+fn where_bounds<I, T, F>(items: I, score: F, #[def(1)] weight: i32) -> i32
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<str>,
+    F: for<'a> Fn(&'a str) -> i32,
+{
+    items
+        .into_iter()
+        .map(|item| score(item.as_ref()) * weight)
+        .sum()
+}
+
+/// Structs keep their `where` clauses as well.
+#[defamed::defamed(crate)]
+#[derive(Debug, PartialEq)]
+pub struct WhereStruct<T>
+where
+    T: Default + Clone,
+    Vec<T>: PartialEq,
+{
+    pub value: T,
+    #[def]
+    pub extra: Vec<T>,
+}
+
+/// Tuple structs write their `where` clauses after the fields.
+#[defamed::defamed(crate)]
+#[derive(Debug, PartialEq)]
+pub struct WhereTuple<T>(pub T, #[def] pub Option<T>)
+where
+    for<'a> &'a T: PartialEq;
+
+/// Default type parameters are kept, and methods are called on the type their `impl` names.
+#[defamed::defamed(crate)]
+#[derive(Debug, PartialEq)]
+pub struct Labelled<T = String> {
+    pub value: T,
+    #[def]
+    pub count: usize,
+}
+
+#[defamed::defamed(self_test)]
+impl Labelled {
+    pub fn repeated(&self, #[def(1)] times: usize) -> String {
+        self.value.repeat(times)
+    }
+}
+
+#[defamed::defamed(self_test)]
+impl Labelled<u8> {
+    pub fn scaled(&self, #[def(1)] by: u8) -> u8 {
+        self.value * by
+    }
+
+    /// Associated functions are called with the generic arguments of the `impl` block
+    pub fn byte(#[def(1)] value: u8) -> Self {
+        Self { value, count: 0 }
+    }
+}
+
+/// A collection of items, with methods in a generic `impl` block.
+#[derive(Debug, Default, PartialEq)]
+pub struct Container<T> {
+    pub items: Vec<T>,
+}
+
+#[defamed::defamed]
+impl<T: Clone + Default> Container<T> {
+    /// Item at `idx`, or the fallback if there is none
+    pub fn get_or(&self, idx: usize, #[def] fallback: T) -> T {
+        self.items.get(idx).cloned().unwrap_or(fallback)
+    }
+
+    /// Append `count` copies of an item, returning the new length
+    pub fn push_copies(&mut self, item: T, #[def(1)] count: usize) -> usize {
+        self.items.extend(std::iter::repeat_n(item, count));
+        self.items.len()
+    }
+
+    /// Associated functions infer the generic arguments of the type
+    pub fn filled(#[def] item: T, #[def(1)] count: usize) -> Self {
+        Self {
+            items: vec![item; count],
+        }
+    }
+}
+
+/// Methods declared in a trait, returning `impl Trait`, generate macros calling any implementor.
+#[defamed::defamed]
+pub trait Shape {
+    fn area(&self) -> f64;
+
+    fn describe(&self, #[def(2)] precision: usize, #[def] unit: &str) -> impl std::fmt::Display {
+        format!("{:.*}{}", precision, self.area(), unit)
+    }
+}
+
+#[derive(Debug)]
+pub struct Square(pub f64);
+
+/// Methods of trait impls generate macros of their own.
+#[defamed::defamed]
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+
+    fn describe(&self, #[def(1)] precision: usize, #[def] unit: &str) -> impl std::fmt::Display {
+        format!("square of {:.*}{}", precision, self.area(), unit)
+    }
+}
+
+/// Named and elided lifetimes and higher-ranked bounds are kept as written.
+#[defamed::defamed(self_test)]
+#[allow(unused)]
+fn first_word<'a>(
+    text: &'a str,
+    #[def(' ')] sep: char,
+    #[def] keep: Option<&dyn for<'w> Fn(&'w str) -> bool>,
+) -> Option<&'a str> {
+    text.split(sep)
+        .find(|word| keep.is_none_or(|keep| keep(word)))
+}
+
+#[defamed::defamed(self_test)]
+impl Counter {
+    /// Label of the counter, borrowing the prefix
+    #[allow(unused)]
+    fn label<'a>(&'a self, #[def("count")] prefix: &'a str) -> std::borrow::Cow<'a, str> {
+        format!("{}: {}", prefix, self.count).into()
+    }
+}
+
+/// Codegen attributes stay on the function, which the macro calls directly.
+#[defamed::defamed(self_test)]
+#[no_mangle]
+#[allow(unused)]
+extern "C" fn defamed_test_checksum(data: u32, #[def(31)] seed: u32) -> u32 {
+    data.wrapping_mul(seed)
+}
+
+/// Functions with target features can only be called where the features are enabled,
+/// which the args struct keeps by enabling them on its own call.
+#[cfg(target_arch = "x86_64")]
+#[defamed::defamed(strategy = "args_struct", self_test)]
+#[target_feature(enable = "sse2")]
+#[allow(unused)]
+fn sum_lanes(lanes: [u32; 4], #[def] offset: u32) -> u32 {
+    lanes.iter().sum::<u32>() + offset
+}
+
+/// A test with parameters runs once with all defaults, and once for every case of each default.
+#[defamed::defamed(test_matrix)]
+#[test]
+fn clamp_within(
+    #[def(5, cases(-3, 12))] value: i32,
+    #[def(0)] low: i32,
+    #[def(10, cases(5, i32::MAX))] high: i32,
+) {
+    assert!((low..=high).contains(&value.clamp(low, high)));
+}
+
+/// Test helpers returning a result fail the generated tests on errors.
+#[defamed::defamed(test_matrix, strategy = "muncher")]
+#[allow(unused)]
+fn parses_width(
+    #[def("80", cases("0", "65535"))] input: &str,
+) -> Result<(), std::num::ParseIntError> {
+    input.parse::<u16>().map(|_| ())
+}
+
+/// Declares items annotated with the macro, from fragments of every kind.
+macro_rules! templated_items {
+    ($name:ident, $value:ident, $ty:ty, $def:expr, $record:ident) => {
+        #[defamed::defamed(self_test)]
+        #[allow(unused)]
+        fn $name($value: $ty, #[def($def)] offset: $ty) -> $ty {
+            $value + offset
+        }
+
+        #[defamed::defamed(self_test)]
+        #[derive(Debug, PartialEq)]
+        #[allow(unused)]
+        struct $record {
+            start: $ty,
+            #[def($def)]
+            step: $ty,
+        }
+    };
+}
+
+templated_items!(shift, value, i64, 10, Stride);
 
 // generate a function with 10 positional arguments and 5 default arguments
 // defamed::defamed! {
@@ -119,3 +826,128 @@ fn complex_function(
 //     a + b + c + d + e + f + g
 // }
 // // }
+
+#[allow(unused)]
+const fn kibibytes(count: usize) -> usize {
+    count * 1024
+}
+
+/// Defaults of `const_defaults` are evaluated at compile time.
+#[defamed::defamed(const_defaults)]
+#[allow(unused)]
+fn buffer_size(#[def(kibibytes(4))] size: usize, #[def(1)] count: usize) -> usize {
+    size * count
+}
+
+/// Including the defaults collected by the args struct.
+#[defamed::defamed(const_defaults, strategy = "args_struct")]
+#[allow(unused)]
+fn pool_size(#[def(kibibytes(64))] size: usize, #[def(2)] count: usize) -> usize {
+    size * count
+}
+
+/// And the defaults of struct fields.
+#[defamed::defamed(const_defaults)]
+#[derive(Debug, PartialEq)]
+#[allow(unused)]
+struct Limits {
+    #[def(kibibytes(1))]
+    soft: usize,
+    #[def(usize::MAX)]
+    hard: usize,
+}
+
+/// Every default of `require_docs` is described, by its attribute or the doc comment of its field.
+#[defamed::defamed(require_docs)]
+#[allow(unused)]
+fn documented(base: u32, #[def(8, doc = "bits shifted to the left")] shift: u32) -> u32 {
+    base << shift
+}
+
+#[defamed::defamed(require_docs)]
+#[derive(Debug, PartialEq)]
+#[allow(unused)]
+struct Documented {
+    name: &'static str,
+    /// Times the name is repeated
+    #[def(1)]
+    repeat: usize,
+    #[def(" ", doc = "separator between repetitions")]
+    separator: &'static str,
+}
+
+/// Small public function, inlined into the callers of its exported macro.
+#[defamed::defamed(crate, auto_inline)]
+pub fn clamp_ratio(value: f32, #[def(1.0)] max: f32) -> f32 {
+    value.min(max)
+}
+
+/// Macro only generated with the `macros` feature, unlike the function.
+#[defamed::defamed(feature = "macros")]
+#[allow(unused)]
+fn gated(base: i32, #[def(5)] offset: i32) -> i32 {
+    base + offset
+}
+
+/// Generated for Rust 1.39, without syntax stabilized later.
+#[defamed::defamed(msrv = "1.39", self_test)]
+#[allow(unused)]
+fn legacy(base: i32, #[def(10)] offset: i32) -> i32 {
+    base + offset
+}
+
+/// Arguments are single token trees, forwarded to the function without being parsed.
+#[defamed::defamed(pattern = "tt", self_test)]
+#[allow(unused)]
+fn token_args(base: i32, #[def(10)] offset: i32, #[def(100)] scale: i32) -> i32 {
+    (base + offset) * scale
+}
+
+/// Token tree arguments sorted by the canonical strategy.
+#[defamed::defamed(pattern = "tt", strategy = "canonical", self_test)]
+#[allow(unused)]
+fn token_args_sorted(base: i32, #[def(10)] offset: i32) -> i32 {
+    base + offset
+}
+
+/// Arguments named like struct fields, collected by the args struct strategy.
+#[defamed::defamed(pattern = "struct_literal", strategy = "args_struct")]
+#[allow(unused)]
+fn literal_args(base: i32, #[def(10)] offset: i32) -> i32 {
+    base + offset
+}
+
+/// Arguments named like struct fields, munched one at a time.
+#[defamed::defamed(pattern = "struct_literal", strategy = "muncher", self_test)]
+#[allow(unused)]
+fn literal_args_munched(base: i32, #[def(10)] offset: i32) -> i32 {
+    base + offset
+}
+
+/// Items inherit the options of their profile.
+#[defamed::defamed(profile = "configured")]
+#[allow(unused)]
+fn configured(base: i32, #[opt(2)] step: i32) -> i32 {
+    base * step
+}
+
+/// Options of the item take precedence, and a strategy replaces the restriction of named arguments.
+#[defamed::defamed(profile = "configured", strategy = "muncher")]
+#[allow(unused)]
+fn reconfigured(base: i32, #[opt(3)] step: i32) -> i32 {
+    base * step
+}
+
+/// Flags set for the crate or a profile are turned off with `= false`.
+#[defamed::defamed(profile = "configured", no_named = false, readable = false)]
+#[allow(unused)]
+fn unrestricted(base: i32, #[opt(3)] step: i32) -> i32 {
+    base * step
+}
+
+/// Items inherit the options of their profile before those of the crate.
+#[defamed::defamed(profile = "compact")]
+#[allow(unused)]
+fn profiled(base: i32, #[def(4)] step: i32) -> i32 {
+    base * step
+}
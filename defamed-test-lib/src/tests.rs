@@ -57,19 +57,617 @@ fn test_default_tuple_struct() {
     assert_eq!(c, DefaultTupleStruct(2, 4, 'f'));
 }
 
+#[test]
+fn test_impl_methods() {
+    let mut counter = Counter::with_count(5);
+
+    assert_eq!(counter_increment!(counter), 6);
+    assert_eq!(counter_increment!(counter, 4), 10);
+    assert_eq!(counter_increment!(counter, step = -10), 0);
+
+    assert_eq!(counter_peek!(counter), 0);
+    assert_eq!(counter_peek!(counter, 2), 2);
+    assert_eq!(counter_peek!(&counter, negate = true, offset = 3), -3);
+    assert_eq!(counter_peek!(Counter::with_count(1), 1, true), -2);
+
+    assert!(counter_is_zero!(counter));
+    assert!(!counter_is_zero!(Counter::with_count(1)));
+}
+
+#[test]
+fn test_module_level() {
+    assert_eq!(inner::geometry::area!(2), 2);
+    assert_eq!(inner::geometry::area!(width = 2, height = 3), 6);
+    assert_eq!(inner::geometry::perimeter!(2), 6);
+    assert_eq!(inner::geometry::perimeter!(2, height = 3), 10);
+}
+
+#[test]
+fn test_skipped_items() {
+    assert_eq!(skipped_function(1, 2), 3);
+    assert_eq!(inner::geometry::volume(2, 3, 4), 24);
+
+    let mut counter = Counter::with_count(5);
+    counter.reset(1);
+    assert_eq!(counter_peek!(counter), 1);
+}
+
+#[test]
+fn test_cfg_propagation() {
+    assert_eq!(cfg_dependent!(), 2);
+    assert_eq!(cfg_dependent!(value = 3), 3);
+}
+
+#[test]
+fn test_namespaced_helper() {
+    assert_eq!(namespaced_helper!(1), 1);
+    assert_eq!(namespaced_helper!(1, scale = 2), 21);
+    assert_eq!(namespaced_helper!(base = 1, offset = 3, scale = 2), 7);
+}
+
+#[test]
+fn test_renamed_helper() {
+    assert_eq!(
+        RenamedHelper! { base: 1, .. },
+        RenamedHelper { base: 1, offset: 4 }
+    );
+    assert_eq!(
+        RenamedHelper! { base: 1, offset: 2 },
+        RenamedHelper { base: 1, offset: 2 }
+    );
+}
+
+#[test]
+fn test_no_params() {
+    assert_eq!(no_params!(), 42);
+}
+
+#[test]
+fn test_raw_idents() {
+    assert_eq!(raw_idents!(3), 3);
+    assert_eq!(raw_idents!(3, 2), 6);
+    assert_eq!(raw_idents!(r#type = 3, r#match = 4), 12);
+}
+
+#[test]
+fn test_overlapping_names() {
+    assert_eq!(overlapping_names!(1), -9);
+    assert_eq!(overlapping_names!(x = 3, x_val = 1), 2);
+}
+
 #[test]
 fn test_all_default() {
-    // let a = all_default!();
-    // let b = all_default!(1);
-    // let c = all_default!(1, 2);
-    // let d = all_default!(1, 2, 3);
-    // // let e = all_default!(1, 2, 3, 4);
-    // // let f = all_default!(1, 2, 3, 4, 5);
-
-    // let identical = [a, b, c, d];
-    // assert!(identical.iter().all(|i| *i == 15));
-
-    // all_default!(e = 1, a = 2, c = 3, d = 4, b = 5);
-    // all_default!(c = 1, d = 3, b = 2);
-    // all_default!(a = 1, b = 2, c = 3, d = 4, e = 5);
+    let a = all_default!();
+    let b = all_default!(1);
+    let c = all_default!(1, 2);
+    let d = all_default!(1, 2, 3);
+    let e = all_default!(1, 2, 3, 4);
+    let f = all_default!(1, 2, 3, 4, 5);
+
+    let identical = [a, b, c, d, e, f];
+    assert!(identical.iter().all(|i| *i == 15));
+
+    assert_eq!(all_default!(e = 1, a = 2, c = 3, d = 4, b = 5), 15);
+    assert_eq!(all_default!(c = 1, d = 3, b = 2), 12);
+    assert_eq!(all_default!(1, 2, e = 10), 20);
+    assert_eq!(all_default!(e = 0), 10);
+}
+
+#[test]
+fn test_muncher_strategy() {
+    assert_eq!(many_params!(1), 36);
+    assert_eq!(many_params!(2, 0, 0), 66);
+    assert_eq!(many_params!(1, h = 0, a = 0), 27);
+    assert_eq!(many_params!(base = 1, g = 0, c = 0), 26);
+    assert_eq!(many_params!(1, 1, 1, 1, 1, 1, 1, 1, 1), 8);
+
+    assert_eq!(
+        MunchedStruct! { id: 1 },
+        MunchedStruct {
+            id: 1,
+            size: 10,
+            name: ""
+        }
+    );
+    assert_eq!(
+        MunchedStruct! { name: "x", id: 2, .. },
+        MunchedStruct {
+            id: 2,
+            size: 10,
+            name: "x"
+        }
+    );
+
+    assert_eq!(wide_params!(1, 1, 1, 1, 1, 1), 12);
+    assert_eq!(wide_params!(1, 1, 1, 1, 1, 1, l = 0, g = 0), 10);
+    assert_eq!(wide_params!(f = 0, e = 0, d = 0, c = 0, b = 0, a = 0), 6);
+
+    assert_eq!(MunchedTuple!(1), MunchedTuple(1, 2));
+    assert_eq!(MunchedTuple!(1, 3), MunchedTuple(1, 3));
+
+    let counter = Counter::with_count(3);
+    assert_eq!(counter_scaled!(counter), 3);
+    assert_eq!(counter_scaled!(counter, offset = 1), 4);
+    assert_eq!(counter_scaled!(&counter, 2, 1), 7);
+}
+
+#[test]
+fn test_canonical_strategy() {
+    assert_eq!(canonical_params!(1), 1123);
+    assert_eq!(canonical_params!(1, 4), 1423);
+    assert_eq!(canonical_params!(1, c = 5, a = 6), 1625);
+    assert_eq!(canonical_params!(b = 7, base = 2), 2173);
+
+    assert_eq!(
+        CanonicalStruct! { name: "x", id: 1 },
+        CanonicalStruct {
+            id: 1,
+            size: 10,
+            name: "x"
+        }
+    );
+    assert_eq!(
+        CanonicalStruct! { size: 2, id: 3, .. },
+        CanonicalStruct {
+            id: 3,
+            size: 2,
+            name: ""
+        }
+    );
+
+    let counter = Counter::with_count(3);
+    assert_eq!(counter_shifted!(counter), 3);
+    assert_eq!(counter_shifted!(counter, right = 1, left = 5), 7);
+    assert_eq!(counter_shifted!(&counter, 1), 4);
+}
+
+#[test]
+fn test_args_struct_strategy() {
+    assert_eq!(struct_args!("a"), "a");
+    assert_eq!(struct_args!("a", "b".to_string()), "ab");
+    assert_eq!(struct_args!("a", repeat = 2), "aa");
+    assert_eq!(struct_args!(repeat = 2, base = "a"), "aa");
+    assert_eq!(struct_args!("a", "b".into(), 3), "ababab");
+}
+
+#[test]
+fn test_ordered_named() {
+    assert_eq!(ordered_params!(3, 1), 2);
+    assert_eq!(ordered_params!(lhs = 3, rhs = 1), 2);
+    assert_eq!(ordered_params!(3, rhs = 1, offset = 2), 4);
+    assert_eq!(ordered_params!(3, 1, 2, offset = 1), 5);
+    assert_eq!(ordered_params!(3, 1, scale = 3, offset = 1), 7);
+}
+
+#[test]
+fn test_no_named() {
+    assert_eq!(positional_params!(3), 3);
+    assert_eq!(positional_params!(3, 2), 6);
+    assert_eq!(positional_params!(3, 2, 1), 7);
+}
+
+#[test]
+fn test_params_metadata() {
+    assert_eq!(
+        complex_function!(@params),
+        &[
+            ("lhs", "i32", None),
+            ("rhs", "i32", None),
+            ("add", "bool", Some("true")),
+            (
+                "divide_result_by",
+                "Option < i32 >",
+                Some("Default::default()")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_where_clauses() {
+    assert_eq!(where_bounds!(["a", "bc"], |s: &str| s.len() as i32), 3);
+    assert_eq!(
+        where_bounds!(
+            score = |s: &str| s.len() as i32,
+            items = vec!["abc".to_string()],
+            weight = 2
+        ),
+        6
+    );
+
+    let counter = Counter::with_count(2);
+    assert_eq!(counter_mapped!(counter, |c| c * 10), 20);
+    assert_eq!(counter_mapped!(counter, |c| c - 1, offset = 5), 6);
+
+    assert_eq!(
+        WhereStruct! { value: 1, .. },
+        WhereStruct {
+            value: 1,
+            extra: vec![]
+        }
+    );
+    assert_eq!(WhereTuple!('a'), WhereTuple('a', None));
+}
+
+#[test]
+fn test_default_type_params() {
+    let label = Labelled! { value: "ab".to_string(), .. };
+    assert_eq!(labelled_repeated!(label, times = 2), "abab");
+
+    let number = Labelled! { value: 3_u8, count: 1 };
+    assert_eq!(labelled_scaled!(number, 2), 6);
+}
+
+#[test]
+fn test_generic_impl() {
+    let mut container = Container::<char>::default();
+
+    assert_eq!(container_push_copies!(container, 'a'), 1);
+    assert_eq!(container_push_copies!(container, item = 'b', count = 2), 3);
+    assert_eq!(container_get_or!(container, 2), 'b');
+    assert_eq!(container_get_or!(container, 5), '\0');
+    assert_eq!(container_get_or!(&container, idx = 9, fallback = 'z'), 'z');
+}
+
+#[test]
+fn test_trait_methods() {
+    let square = Square(1.5);
+
+    // the defaults of the trait apply to any implementor
+    assert_eq!(shape_describe!(square).to_string(), "square of 2.25");
+    assert_eq!(
+        shape_describe!(square, unit = "m2").to_string(),
+        "square of 2.25m2"
+    );
+    assert_eq!(square_describe!(square).to_string(), "square of 2.2");
+    assert_eq!(
+        square_describe!(square, 3, "m2").to_string(),
+        "square of 2.250m2"
+    );
+    assert_eq!(shape_area!(square), 2.25);
+}
+
+#[test]
+fn test_lifetimes() {
+    let text = String::from("ab cd-ef");
+
+    assert_eq!(first_word!(&text), Some("ab"));
+    assert_eq!(first_word!(&text, '-'), Some("ab cd"));
+    assert_eq!(
+        first_word!(&text, keep = Some(&|word: &str| word.starts_with('c'))),
+        Some("cd-ef")
+    );
+
+    let counter = Counter::with_count(3);
+    let prefix = String::from("total");
+    assert_eq!(counter_label!(counter), "count: 3");
+    assert_eq!(counter_label!(&counter, prefix = &prefix), "total: 3");
+}
+
+#[test]
+fn test_codegen_attrs() {
+    assert_eq!(defamed_test_checksum!(2), 62);
+    assert_eq!(defamed_test_checksum!(2, seed = 3), 6);
+
+    // calls without the target features enabled are unsafe, through the macro as well
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        assert_eq!(sum_lanes!([1, 2, 3, 4]), 10);
+        assert_eq!(sum_lanes!([1, 2, 3, 4], offset = 5), 15);
+    }
+}
+
+#[test]
+fn test_nested_function() {
+    // the macro is scoped to the rest of the block
+    #[defamed::defamed(self_test)]
+    fn halve(value: i32, #[def(false)] round_up: bool) -> i32 {
+        (value + round_up as i32) / 2
+    }
+
+    assert_eq!(halve!(5), 2);
+    assert_eq!(halve!(5, round_up = true), 3);
+}
+
+#[test]
+fn test_templated_items() {
+    assert_eq!(shift!(1), 11);
+    assert_eq!(shift!(1, offset = 2), 3);
+    assert_eq!(shift!(value = 1, offset = 2), 3);
+
+    assert_eq!(Stride! { start: 1, .. }, Stride { start: 1, step: 10 });
+    assert_eq!(Stride! { start: 1, step: 2 }, Stride { start: 1, step: 2 });
+}
+
+#[test]
+fn test_macro_visibility() {
+    assert_eq!(inner::scoped::crate_visible!(2), 2);
+    assert_eq!(inner::scoped::crate_visible!(2, factor = 5), 10);
+    assert_eq!(inner::parent_scope(), 2);
+    assert_eq!(inner::scoped::all_scopes(), 7);
+}
+
+#[test]
+fn test_reexports() {
+    assert_eq!(perimeter!(2), inner::geometry::perimeter!(2));
+    assert_eq!(crate::perimeter!(2, height = 3), 10);
+    assert_eq!(rect_area!(2, height = 3), 6);
+    assert_eq!(reexported!(2), 3);
+}
+
+#[test]
+fn test_named_receiver() {
+    let counter = Counter::with_count(-3);
+
+    assert_eq!(counter_distance!(self = counter), 3);
+    assert_eq!(counter_distance!(self = &counter, 2), 5);
+    assert_eq!(counter_distance!(self = counter, scale = 2, to = 1), 8);
+
+    assert_eq!(counter_clamped!(self = counter), 0);
+    assert_eq!(counter_clamped!(self = counter, min = -5, max = -4), -4);
+
+    assert_eq!(counter_wrapped!(self = counter), 7);
+    assert_eq!(counter_wrapped!(self = counter, modulus = 2), 1);
+}
+
+#[test]
+fn test_ext_trait() {
+    let mut widget = Widget::default();
+
+    assert_eq!(widget.resize_with(WidgetResizeArgs! { width: 4, .. }), 4);
+    assert_eq!(
+        widget,
+        Widget {
+            width: 4,
+            height: 1
+        }
+    );
+
+    let args = WidgetResizeArgs! { width: 2, height: 3, scale: Some(2) };
+    assert_eq!(widget.resize_with(args), 24);
+    assert_eq!(widget.area(), 24);
+
+    // the method macro is generated as well
+    assert_eq!(widget_resize!(widget, 5), 5);
+}
+
+#[test]
+fn test_associated_functions() {
+    assert_eq!(counter_with_count!(), Counter { count: 0 });
+    assert_eq!(counter_with_count!(count = 3), Counter::with_count(3));
+
+    assert_eq!(labelled_byte!().value, 1);
+    assert_eq!(labelled_byte!(value = 7).value, 7);
+
+    let filled: Container<char> = container_filled!(count = 2);
+    assert_eq!(filled.items, vec!['\0', '\0']);
+    assert_eq!(container_filled!('a', 3).items, vec!['a'; 3]);
+
+    assert_eq!(widget_square!().area(), 1);
+    assert_eq!(crate::widget_square!(side = 3).area(), 9);
+}
+
+#[test]
+fn test_self_defaults() {
+    assert_eq!(
+        counter_started!(),
+        Counter {
+            count: Counter::START
+        }
+    );
+    assert_eq!(counter_started!(count = 1), Counter { count: 1 });
+
+    let mut widget = widget_square!();
+    assert_eq!(widget_grow!(widget), 9);
+    assert_eq!(widget_grow!(widget, by = 1), 16);
+    assert_eq!(widget.grow_with(WidgetGrowArgs! { .. }), 36);
+}
+
+/// Polls a future that never waits to completion.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Waker};
+
+    let mut future = std::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn test_awaited_defaults() {
+    // callers do not need to be async themselves
+    assert_eq!(block_on(awaited_default!(20)), 10);
+    assert_eq!(block_on(awaited_default!(20, limit = 5)), 5);
+    assert_eq!(block_on(awaited_default!(used = 20, limit = 30)), 20);
+
+    assert_eq!(block_on(awaited_munched!()), 10);
+    assert_eq!(block_on(awaited_munched!(step = 2)), 5);
+    assert_eq!(block_on(awaited_munched!(20, 4)), 5);
+}
+
+#[test]
+fn test_const_defaults() {
+    let data = [1, 2, 3, 4, 5, 6];
+
+    assert_eq!(chunk_sums!(&data), vec![3, 7, 11]);
+    assert_eq!(chunk_sums!(&data, N = 3), vec![6, 15]);
+    assert_eq!(chunk_sums!(&data, N = 3, offset = 1), vec![7, 16]);
+    assert_eq!(chunk_sums!(&data, 1, 6), vec![22]);
+
+    const WIDE: usize = 4;
+    assert_eq!(chunk_sums!(data = &data, N = WIDE), vec![10, 11]);
+
+    assert_eq!(padded!(&[1, 2], 0), vec![1, 2, 0]);
+    assert_eq!(padded!(&[1, 2], 0, LEN = 1), vec![1, 2]);
+    assert_eq!(padded!(&['a'], '-', FILL = 1, LEN = 2), vec!['a', '-', '-']);
+}
+
+#[test]
+fn test_phantom_data_defaults() {
+    let (value, tag): (u32, String) = tagged!(3);
+    assert_eq!((value, tag.as_str()), (3, ""));
+
+    assert_eq!(tagged!(3, 2, tag = std::marker::PhantomData::<u8>), (6, 0));
+    assert_eq!(
+        tagged!(3, tag = std::marker::PhantomData::<bool>),
+        (3, false)
+    );
+}
+
+#[test]
+fn test_trait_object_defaults() {
+    struct StdoutLogger;
+
+    impl Logger for StdoutLogger {
+        fn prefix(&self) -> &'static str {
+            "stdout"
+        }
+    }
+
+    assert_eq!(logged!("ready"), "noop|noop: ready 5");
+    assert_eq!(
+        logged!("ready", fallback = None, measure = Box::new(|_| 0)),
+        "noop|none: ready 0"
+    );
+    assert_eq!(
+        logged!("ready", logger = Box::new(StdoutLogger)),
+        "stdout|noop: ready 5"
+    );
+
+    assert_eq!(logged_struct!("ready"), "noop: ready");
+    assert_eq!(
+        logged_struct!("ready", logger = Box::new(StdoutLogger)),
+        "stdout: ready"
+    );
+
+    assert_eq!(logged_munched!("ready"), "noop: ready");
+    assert_eq!(logged_munched!("ready", &StdoutLogger), "stdout: ready");
+
+    let task = LoggedTask! { name: "task", .. };
+    assert_eq!((task.name, task.logger.prefix()), ("task", "noop"));
+}
+
+#[test]
+fn test_foreign_functions() {
+    use std::ffi::{c_int, CStr};
+
+    assert_eq!(unsafe { abs!() }, 0);
+    assert_eq!(unsafe { abs!(value = -3) }, 3);
+
+    fn formatted(buf: &[std::ffi::c_char], written: c_int) -> &str {
+        let formatted = unsafe { CStr::from_ptr(buf.as_ptr()) };
+        assert_eq!(written as usize, formatted.count_bytes());
+        formatted.to_str().unwrap()
+    }
+
+    let mut buf = [0; 16];
+
+    let written = unsafe { snprintf!(buf.as_mut_ptr(), buf.len(), args = [42 as c_int]) };
+    assert_eq!(formatted(&buf, written), "42");
+
+    let written = unsafe {
+        snprintf!(
+            buf.as_mut_ptr(),
+            buf.len(),
+            c"%d-%d".as_ptr(),
+            [1 as c_int, 2 as c_int],
+        )
+    };
+    assert_eq!(formatted(&buf, written), "1-2");
+
+    let written = unsafe { snprintf!(buf.as_mut_ptr(), buf.len(), format = c"none".as_ptr()) };
+    assert_eq!(formatted(&buf, written), "none");
+
+    // unnamed variadic arguments are passed as `args`
+    let written = unsafe { format_into!(buf.as_mut_ptr(), args = [7 as c_int]) };
+    assert_eq!(formatted(&buf, written), "7");
+
+    let written = unsafe {
+        format_into!(
+            buf.as_mut_ptr(),
+            args = [3 as c_int, 4 as c_int],
+            format = c"%d%d".as_ptr()
+        )
+    };
+    assert_eq!(formatted(&buf, written), "34");
+}
+
+#[test]
+fn test_test_matrix() {
+    // the helper of the generated tests is still called through its macro
+    clamp_within!(high = 3, value = 4);
+    assert!(parses_width!("-1").is_err());
+}
+
+#[test]
+fn test_crate_config() {
+    assert_eq!(configured!(5), 10);
+    assert_eq!(configured!(5, 3), 15);
+    assert_eq!(reconfigured!(step = 4, base = 5), 20);
+    assert_eq!(unrestricted!(base = 5, step = 4), 20);
+    assert_eq!(profiled!(step = 2, base = 5), 10);
+    assert_eq!(profiled!(5), 20);
+}
+
+#[test]
+fn test_const_only_defaults() {
+    assert_eq!(buffer_size!(), 4096);
+    assert_eq!(buffer_size!(count = 2), 8192);
+    assert_eq!(pool_size!(count = 1), 65536);
+    assert_eq!(
+        Limits! { hard: 2048, .. },
+        Limits {
+            soft: 1024,
+            hard: 2048
+        }
+    );
+}
+
+#[test]
+fn test_documented_defaults() {
+    assert_eq!(documented!(1), 256);
+    assert_eq!(
+        Documented! { name: "a", .. },
+        Documented {
+            name: "a",
+            repeat: 1,
+            separator: " "
+        }
+    );
+}
+
+#[test]
+fn test_msrv() {
+    assert_eq!(legacy!(1), 11);
+    assert_eq!(legacy!(base = 1, offset = 2), 3);
+}
+
+#[test]
+fn test_pattern_syntax() {
+    assert_eq!(token_args!(1), 1100);
+    assert_eq!(token_args!(1, scale = 2), 22);
+    assert_eq!(token_args!(base = (1 + 1), scale = 1), 12);
+    assert_eq!(token_args!({ 2 * 2 }, 0, 1), 4);
+    assert_eq!(token_args_sorted!(offset = 0, base = 1), 1);
+
+    assert_eq!(literal_args!(1), 11);
+    assert_eq!(literal_args!(offset: 2, base: 1), 3);
+    assert_eq!(literal_args_munched!(1, offset: 1 + 1), 3);
+}
+
+#[test]
+fn test_auto_inline() {
+    assert_eq!(clamp_ratio!(1.5), 1.0);
+    assert_eq!(clamp_ratio!(1.5, max = 2.0), 1.5);
+}
+
+#[test]
+fn test_feature_gated() {
+    assert_eq!(gated(1, 2), 3);
+    #[cfg(feature = "macros")]
+    assert_eq!(gated!(1), 6);
 }
@@ -0,0 +1,12 @@
+//! Errors reported for invalid items and invocations, compared against the `.stderr` files in `tests/ui`.
+//!
+//! After changing a diagnostic on purpose, regenerate the files with:
+//! ```sh
+//! TRYBUILD=overwrite cargo test -p defamed-test-lib --test ui
+//! ```
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
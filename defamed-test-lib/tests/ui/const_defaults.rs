@@ -0,0 +1,14 @@
+#[defamed::defamed(const_defaults)]
+fn connect(#[def(std::env::args().count())] retries: usize, #[def(30)] timeout: u64) -> u64 {
+    retries as u64 * timeout
+}
+
+#[defamed::defamed(const_defaults)]
+fn listen(port: u16, #[def] backlog: u32) -> u32 {
+    port as u32 + backlog
+}
+
+fn main() {
+    let _ = connect!(timeout = 10);
+    let _ = listen(80, 1);
+}
@@ -0,0 +1,15 @@
+struct Container<T> {
+    items: Vec<T>,
+}
+
+#[defamed::defamed]
+impl<T: Clone> Container<T> {
+    fn extended(&self, #[def(Vec::<T>::new())] extra: Vec<T>) -> Vec<T> {
+        [self.items.clone(), extra].concat()
+    }
+}
+
+fn main() {
+    let container = Container { items: vec![1] };
+    let _ = container.extended(vec![2]);
+}
@@ -0,0 +1,8 @@
+#[defamed::defamed]
+fn add(#[def] lhs: i32, rhs: i32) -> i32 {
+    lhs + rhs
+}
+
+fn main() {
+    let _ = add(1, 2);
+}
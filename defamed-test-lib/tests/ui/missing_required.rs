@@ -0,0 +1,8 @@
+#[defamed::defamed]
+fn add(lhs: i32, #[def] rhs: i32) -> i32 {
+    lhs + rhs
+}
+
+fn main() {
+    let _ = add!(rhs = 2);
+}
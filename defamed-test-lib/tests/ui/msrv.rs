@@ -0,0 +1,14 @@
+#[defamed::defamed(msrv = "1.70", const_defaults)]
+fn connect(#[def(3)] retries: usize, #[def(30)] timeout: u64) -> u64 {
+    retries as u64 * timeout
+}
+
+#[defamed::defamed(msrv = "1.30")]
+fn listen(port: u16, #[def] backlog: u32) -> u32 {
+    port as u32 + backlog
+}
+
+fn main() {
+    let _ = connect(3, 30);
+    let _ = listen(80, 1);
+}
@@ -0,0 +1,21 @@
+struct Counter {
+    count: i32,
+}
+
+#[defamed::defamed(receiver = "named")]
+impl Counter {
+    fn add(&self, #[def(1)] step: i32) -> i32 {
+        self.count + step
+    }
+}
+
+#[defamed::defamed(receiver = "named")]
+fn add(lhs: i32, #[def(1)] rhs: i32) -> i32 {
+    lhs + rhs
+}
+
+fn main() {
+    let counter = Counter { count: 0 };
+    let _ = counter_add!(self = counter, step = 2);
+    let _ = counter_add!(counter, step = 2);
+}
@@ -0,0 +1,14 @@
+fn runtime_value() -> usize {
+    1
+}
+
+#[defamed::defamed]
+const fn scale(value: usize, #[def(runtime_value())] factor: usize) -> usize {
+    value * factor
+}
+
+const SCALED: usize = scale!(2);
+
+fn main() {
+    let _ = SCALED;
+}
@@ -0,0 +1,9 @@
+#[defamed::defamed]
+fn add(mut lhs: i32, #[def] rhs: i32) -> i32 {
+    lhs += rhs;
+    lhs
+}
+
+fn main() {
+    let _ = add(1, 2);
+}
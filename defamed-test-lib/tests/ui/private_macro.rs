@@ -0,0 +1,15 @@
+mod shapes {
+    #[defamed::defamed]
+    fn area(width: u32, #[def(1)] height: u32) -> u32 {
+        width * height
+    }
+
+    pub fn square(side: u32) -> u32 {
+        area!(side, height = side)
+    }
+}
+
+fn main() {
+    let _ = shapes::square(2);
+    let _ = shapes::area!(2);
+}
@@ -0,0 +1,10 @@
+#[defamed::defamed(crate)]
+pub struct Point {
+    pub x: i32,
+    #[def]
+    y: i32,
+}
+
+fn main() {
+    let _ = Point { x: 1, y: 2 }.y;
+}
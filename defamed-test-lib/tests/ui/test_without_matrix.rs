@@ -0,0 +1,7 @@
+#[defamed::defamed]
+#[test]
+fn clamp_within(#[def(5)] value: i32, #[def(10)] high: i32) {
+    assert!(value <= high);
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[defamed::defamed(pattern = "tt")]
+fn offset(base: i32, #[def(1)] step: i32) -> i32 {
+    base + step
+}
+
+fn main() {
+    // `-1` is two token trees
+    offset!(2, step = -1);
+}
@@ -0,0 +1,8 @@
+#[defamed::defamed(max_arms = 2)]
+fn sum(a: i32, #[def] b: i32, #[def] c: i32) -> i32 {
+    a + b + c
+}
+
+fn main() {
+    let _ = sum(1, 2, 3);
+}
@@ -0,0 +1,19 @@
+#[defamed::defamed(require_docs)]
+fn connect(host: &str, #[def(30)] timeout: u64, #[def(doc = "retry on failure")] retry: bool) {
+    let _ = (host, timeout, retry);
+}
+
+#[defamed::defamed(require_docs = "warn")]
+struct Window {
+    title: &'static str,
+    /// Width in pixels
+    #[def(640)]
+    width: u32,
+    #[def(480)]
+    height: u32,
+}
+
+fn main() {
+    connect("localhost", 30, false);
+    let _ = Window! { title: "main", .. };
+}
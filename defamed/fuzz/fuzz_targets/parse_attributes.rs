@@ -0,0 +1,43 @@
+//! Feeds arbitrary source into the parsers of parameters and attribute options,
+//! which must report invalid input as errors instead of panicking.
+//!
+//! ```sh
+//! cd defamed && cargo +nightly fuzz run parse_attributes
+//! ```
+
+#![no_main]
+
+use std::str::FromStr;
+
+use defamed_core::{
+    expand::config,
+    permute::params::{FunctionParam, FunctionParams},
+    DEFAULT_HELPER_ATTR,
+};
+use libfuzzer_sys::fuzz_target;
+use proc_macro2 as pm2;
+use syn::parse::Parser;
+
+fuzz_target!(|data: &[u8]| {
+    // only input that lexes as Rust tokens can reach the attribute
+    let tokens = match std::str::from_utf8(data).map(pm2::TokenStream::from_str) {
+        Ok(Ok(tokens)) => tokens,
+        _ => return,
+    };
+    let helper = syn::Ident::new(DEFAULT_HELPER_ATTR, pm2::Span::call_site());
+
+    // options of the attribute
+    let _ = syn::parse2::<config::MacroConfig>(tokens.clone());
+
+    // parameters of a function, including the helper attributes on them
+    let parser = syn::punctuated::Punctuated::<syn::FnArg, syn::Token![,]>::parse_terminated;
+    if let Ok(args) = parser.parse2(tokens) {
+        for arg in &args {
+            if let syn::FnArg::Typed(pat_type) = arg {
+                let _ = FunctionParam::from_pat_type(pat_type.clone(), &helper);
+            }
+        }
+
+        let _ = FunctionParams::from_punctuated(args, &helper);
+    }
+});
@@ -1,19 +1,8 @@
 #![doc = include_str!("../README.md")]
 
-mod block_logic;
-mod macro_gen;
-mod permute;
-mod traits;
-
+use defamed_core::expand::{block_logic, config};
 use proc_macro as pm;
 use proc_macro2 as pm2;
-use syn::spanned::Spanned;
-
-/// Identifier for public macros defined in the root module
-pub(crate) const ROOT_VISIBILITY_IDENT: &str = "crate";
-
-/// "Helper" attribute for annotating function parameters
-pub(crate) const DEFAULT_HELPER_ATTR: &str = "def";
 
 /// Attempt to parse multiple items at once, returning a tuple of results.
 macro_rules! syn_parses {
@@ -77,38 +66,75 @@ macro_rules! syn_parses {
 /// ```
 #[proc_macro_attribute]
 pub fn defamed(attrs: pm::TokenStream, input: pm::TokenStream) -> pm::TokenStream {
-    let item_path = match attrs.is_empty() {
-        true => None,
-        false => {
-            // asd
-            let ex: syn::Expr = syn::parse_macro_input!(attrs);
-
-            if let syn::Expr::Path(syn::ExprPath { path, .. }) = &ex {
-                // if let syn::Expr::Path(p) = expr.as_ref() {
-                Some(path.clone())
+    let config = match syn::parse::<config::MacroConfig>(attrs) {
+        Ok(c) => c,
+        Err(e) => return recover_input(input, &config::MacroConfig::default().helper_attr(), e),
+    };
 
-                // } else {
-            } else {
-                return syn::Error::new(ex.span(), "Expected path expression")
-                    .to_compile_error()
-                    .into();
-            }
-        }
+    let (expected_str, parsed) = syn_parses!(
+        input,
+        syn::ItemStruct,
+        syn::ItemFn,
+        syn::ItemImpl,
+        syn::ItemTrait,
+        syn::ItemMod,
+        syn::ItemForeignMod
+    );
+    // options of methods are only inherited by `impl` blocks and traits
+    let methods = matches!(parsed, (_, _, Ok(_), _, _, _) | (_, _, _, Ok(_), _, _));
+    let helper = config.helper_attr();
+    let config = match config.with_crate_defaults(methods) {
+        Ok(c) => c,
+        Err(e) => return recover_input(input, &helper, e),
     };
 
-    let (expected_str, parsed) = syn_parses!(input, syn::ItemStruct, syn::ItemFn);
+    // no macros are generated for skipped items, or for any item to rule them out while bisecting
+    // a bug or a slow build, but their helper attributes are still removed
+    if config.skip || cfg!(feature = "strip") {
+        return match syn::parse::<syn::Item>(input.clone()) {
+            Ok(mut item) => {
+                block_logic::strip_helpers(&mut item, &config.helper_attr());
+                // the helper of a test matrix still cannot run on its own
+                if let (syn::Item::Fn(f), true) = (&mut item, config.test_matrix) {
+                    block_logic::compile_with_tests(&mut f.attrs);
+                }
+                quote::ToTokens::into_token_stream(item).into()
+            }
+            Err(_) => input,
+        };
+    }
 
     let res = match parsed {
-        (Ok(s), _) => block_logic::item_struct(s, item_path),
-        (_, Ok(f)) => block_logic::item_fn(f, item_path),
+        (Ok(s), _, _, _, _, _) => block_logic::item_struct(s, config),
+        (_, Ok(f), _, _, _, _) => block_logic::item_fn(f, config),
+        (_, _, Ok(i), _, _, _) => block_logic::item_impl(i, config),
+        (_, _, _, Ok(t), _, _) => block_logic::item_trait(t, config),
+        (_, _, _, _, Ok(m), _) => block_logic::item_mod(m, config),
+        (_, _, _, _, _, Ok(m)) => block_logic::item_foreign_mod(m, config),
 
-        _ => syn::Error::new(
-            pm2::Span::call_site(),
-            format!("Item not supported. Expected: {}", expected_str),
-        )
-        .to_compile_error()
-        .into(),
+        _ => {
+            return recover_input(
+                input,
+                &config.helper_attr(),
+                syn::Error::new(
+                    pm2::Span::call_site(),
+                    format!("Item not supported. Expected: {}", expected_str),
+                ),
+            )
+        }
     };
 
     res.into()
 }
+
+/// Emits the annotated item, if it can be parsed, alongside an error.
+fn recover_input(
+    input: pm::TokenStream,
+    helper: &syn::Ident,
+    error: syn::Error,
+) -> pm::TokenStream {
+    match syn::parse::<syn::Item>(input) {
+        Ok(item) => block_logic::recover(item, helper, error).into(),
+        Err(_) => error.to_compile_error().into(),
+    }
+}
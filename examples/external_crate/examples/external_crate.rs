@@ -1,4 +1,5 @@
 //! This example shows how to import `defamed` macros from a library crate.
+#[macro_use]
 extern crate defamed_test_lib;
 
 use defamed_test_lib::inner;
@@ -16,4 +17,8 @@ fn main() {
     let r_1 = defamed_test_lib::some_root_function("base", None);
     let r_2 = defamed_test_lib::some_root_function!("base");
     assert_eq!(r_1, r_2);
+
+    // macros exported with `macro_use` are imported by `#[macro_use] extern crate`
+    assert_eq!(legacy_function!(1), 2);
+    assert_eq!(legacy_function!(1, step = 2), 3);
 }